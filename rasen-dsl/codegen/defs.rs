@@ -2,13 +2,18 @@
 
 use proc_macro2::{Ident, Span};
 
-const INTS: [(&'static str, &'static str, &'static str); 3] = [
+const INTS: [(&'static str, &'static str, &'static str); 5] = [
     ("Bool", "b", "bool"),
     ("Int", "i", "i32"),
     ("UInt", "u", "u32"),
+    ("Int64", "i64", "i64"),
+    ("UInt64", "u64", "u64"),
+];
+const FLOATS: [(&'static str, &'static str, &'static str); 3] = [
+    ("Half", "h", "f16"),
+    ("Float", "", "f32"),
+    ("Double", "d", "f64"),
 ];
-const FLOATS: [(&'static str, &'static str, &'static str); 2] =
-    [("Float", "", "f32"), ("Double", "d", "f64")];
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub enum Category {
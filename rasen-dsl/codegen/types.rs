@@ -15,7 +15,7 @@ fn type_scalar(name: &Ident, kind: &'static str) -> [TokenStream; 5] {
     match kind {
         "bool" => {},
 
-        "i32" => {
+        "i32" | "i64" => {
             traits.push(quote! {
                 impl GenType for #name {
                     #[inline]
@@ -34,7 +34,7 @@ fn type_scalar(name: &Ident, kind: &'static str) -> [TokenStream; 5] {
                 }
             });
         },
-        "u32" => {
+        "u32" | "u64" => {
             traits.push(quote! {
                 impl GenType for #name {
                     #[inline]
@@ -49,18 +49,25 @@ fn type_scalar(name: &Ident, kind: &'static str) -> [TokenStream; 5] {
 
                 impl Numerical for #name {
                     #[inline]
-                    fn pow(self, rhs: Self) -> Self { self.pow(rhs) }
+                    fn pow(self, rhs: Self) -> Self { self.pow(std::convert::TryInto::try_into(rhs).unwrap()) }
                 }
             });
         },
 
         _ => {
+            // The software half type doesn't support float literals
+            let (zero, one) = if kind == "f16" {
+                (quote! { f16::ZERO }, quote! { f16::ONE })
+            } else {
+                (quote! { 0.0 }, quote! { 1.0 })
+            };
+
             traits.push(quote! {
                 impl GenType for #name {
                     #[inline]
-                    fn zero() -> Self { 0.0 }
+                    fn zero() -> Self { #zero }
                     #[inline]
-                    fn one() -> Self { 1.0 }
+                    fn one() -> Self { #one }
                     #[inline]
                     fn min(self, rhs: Self) -> Self { self.min(rhs) }
                     #[inline]
@@ -239,7 +246,7 @@ fn type_vector(
             }
         });
 
-        if kind != "i32" && kind != "u32" {
+        if kind == "f16" || kind == "f32" || kind == "f64" {
             let rhs_fields1: Vec<_> = (0..size)
                 .map(|index| {
                     let index = index as usize;
@@ -16,7 +16,7 @@ use crate::{
 };
 use rasen::{
     module::FunctionRef,
-    prelude::{Graph, Module as ModuleImpl, Node, VariableName, BuiltIn},
+    prelude::{Attributes, Graph, Module as ModuleImpl, Node, VariableName, BuiltIn},
 };
 
 type Shared<T> = Rc<RefCell<T>>;
@@ -117,12 +117,30 @@ impl Module {
     }
 
     pub fn input<T>(&self, index: u32, name: impl IntoVariableName) -> Value<Parse, T>
+    where
+        T: AsTypeName,
+        Parse: Container<T, Value = ParseNode>,
+    {
+        self.input_with(index, name, Attributes::default())
+    }
+
+    pub fn input_with<T>(
+        &self,
+        index: u32,
+        name: impl IntoVariableName,
+        attributes: Attributes,
+    ) -> Value<Parse, T>
     where
         T: AsTypeName,
         Parse: Container<T, Value = ParseNode>,
     {
         with_graph(|graph| {
-            Value(graph.add_node(Node::Input(index, T::TYPE_NAME, name.into_variable_name())))
+            Value(graph.add_node(Node::Input(
+                index,
+                T::TYPE_NAME,
+                name.into_variable_name(),
+                attributes,
+            )))
         })
     }
 
@@ -140,9 +158,27 @@ impl Module {
     where
         T: AsTypeName,
         Parse: Container<T, Value = ParseNode>,
+    {
+        self.output_with(index, name, Attributes::default(), value)
+    }
+
+    pub fn output_with<T>(
+        &self,
+        index: u32,
+        name: impl IntoVariableName,
+        attributes: Attributes,
+        value: Value<Parse, T>,
+    ) where
+        T: AsTypeName,
+        Parse: Container<T, Value = ParseNode>,
     {
         with_graph(|graph| {
-            let node = graph.add_node(Node::Output(index, T::TYPE_NAME, name.into_variable_name()));
+            let node = graph.add_node(Node::Output(
+                index,
+                T::TYPE_NAME,
+                name.into_variable_name(),
+                attributes,
+            ));
             graph.add_edge(value.0, node, 0);
         });
     }
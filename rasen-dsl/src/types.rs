@@ -1,6 +1,6 @@
 //! Exports Rust counterparts for all the common GLSL types, along with a few marker traits
 
-use rasen::prelude::{Dim, TypeName};
+use rasen::prelude::{f16, Dim, TypeName};
 
 use std::ops::{Add, Div, Index, Mul, Rem, Sub};
 
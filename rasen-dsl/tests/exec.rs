@@ -82,3 +82,15 @@ fn test_run_functions() {
     let result = result.read();
     assert_eq!(result, PI);
 }
+
+#[test]
+fn test_run_half() {
+    let vector = Value::of(HVec2([f16::from(3.0f32), f16::from(4.0f32)]));
+    assert_eq!(length(vector).read(), f16::from(5.0f32));
+
+    let HVec2(normal) = normalize(vector).read();
+    assert_eq!(normal, [f16::from(0.6f32), f16::from(0.8f32)]);
+
+    let HVec2(scaled) = (vector * Value::of(f16::from(0.5f32))).read();
+    assert_eq!(scaled, [f16::from(1.5f32), f16::from(2.0f32)]);
+}
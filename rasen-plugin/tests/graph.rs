@@ -19,6 +19,7 @@ fn gen_basic_vert() {
         .build_assembly(Settings {
             mod_type: ShaderType::Vertex,
             uniforms_name: Some(String::from("Uniforms")),
+            ..Settings::default()
         })
         .unwrap();
 
@@ -32,6 +33,7 @@ fn gen_basic_frag() {
         .build_assembly(Settings {
             mod_type: ShaderType::Fragment,
             uniforms_name: Some(String::from("Uniforms")),
+            ..Settings::default()
         })
         .unwrap();
 
@@ -56,6 +58,7 @@ fn gen_functions() {
         .build_assembly(Settings {
             mod_type: ShaderType::Vertex,
             uniforms_name: Some(String::from("Uniforms")),
+            ..Settings::default()
         })
         .unwrap();
 
@@ -17,6 +17,7 @@ fn bench_build_basic_vert(b: &mut Bencher) {
             Settings {
                 mod_type: ShaderType::Vertex,
                 uniforms_name: Some(String::from("Uniforms")),
+                ..Settings::default()
             },
         )
         .unwrap()
@@ -34,6 +35,7 @@ fn bench_build_basic_frag(b: &mut Bencher) {
             Settings {
                 mod_type: ShaderType::Fragment,
                 uniforms_name: Some(String::from("Uniforms")),
+                ..Settings::default()
             },
         )
         .unwrap()
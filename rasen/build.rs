@@ -9,13 +9,20 @@ use std::{collections::HashMap, env, fs::File, io::Write, path::Path, str::FromS
 
 use proc_macro2::{Ident, Span, TokenStream};
 
-static INTS: [(&'static str, &'static str, &'static str); 3] = [
+static INTS: [(&'static str, &'static str, &'static str); 7] = [
     ("Bool", "b", "bool"),
+    ("Int16", "i16", "i16"),
+    ("UInt16", "u16", "u16"),
     ("Int", "i", "i32"),
     ("UInt", "u", "u32"),
+    ("Int64", "i64", "i64"),
+    ("UInt64", "u64", "u64"),
+];
+static FLOATS: [(&'static str, &'static str, &'static str); 3] = [
+    ("Half", "h", "f16"),
+    ("Float", "", "f32"),
+    ("Double", "d", "f64"),
 ];
-static FLOATS: [(&'static str, &'static str, &'static str); 2] =
-    [("Float", "", "f32"), ("Double", "d", "f64")];
 
 static SAMPLERS: [(&'static str, &'static str); 3] = [("", "FLOAT"), ("I", "INT"), ("U", "UINT")];
 static DIMENSIONS: [&'static str; 6] = ["1D", "2D", "3D", "Cube", "Rect", "Buffer"];
@@ -26,6 +33,68 @@ enum ConstType {
     Sampler(Ident, Ident),
 }
 
+/// Get the pattern matching the `TypeName` of a scalar type from its constant name
+fn scalar_pattern(name: &str) -> Option<TokenStream> {
+    Some(match name {
+        "BOOL" => quote! { TypeName::Bool },
+        "INT16" => quote! { TypeName::Int(true, 16) },
+        "UINT16" => quote! { TypeName::Int(false, 16) },
+        "INT" => quote! { TypeName::Int(true, 32) },
+        "UINT" => quote! { TypeName::Int(false, 32) },
+        "INT64" => quote! { TypeName::Int(true, 64) },
+        "UINT64" => quote! { TypeName::Int(false, 64) },
+        "HALF" => quote! { TypeName::Float(16) },
+        "FLOAT" => quote! { TypeName::Float(32) },
+        "DOUBLE" => quote! { TypeName::Float(64) },
+        _ => return None,
+    })
+}
+
+/// Get the expression converting a value of a Rust scalar type to a `Scalar`
+fn to_scalar(ty: &str, value: &Ident) -> TokenStream {
+    match ty {
+        "bool" => quote! { Scalar::Bool(#value) },
+        "i16" | "i32" | "i64" => quote! { Scalar::Int(i64::from(#value)) },
+        "u16" | "u32" | "u64" => quote! { Scalar::UInt(u64::from(#value)) },
+        "f16" => quote! { Scalar::Float(f64::from(#value.to_f32())) },
+        "f32" | "f64" => quote! { Scalar::Float(f64::from(#value)) },
+        _ => unreachable!(),
+    }
+}
+
+/// Get the expression converting a `Scalar` back to a value of a Rust scalar type,
+/// returning `None` from the enclosing function if the kinds don't match
+fn from_scalar(ty: &str, value: &Ident) -> TokenStream {
+    let ty_ident = Ident::new(ty, Span::call_site());
+    match ty {
+        "bool" => quote! {
+            match #value { Scalar::Bool(val) => val, _ => return None }
+        },
+        "i16" | "i32" => quote! {
+            match #value { Scalar::Int(val) => val as #ty_ident, _ => return None }
+        },
+        "i64" => quote! {
+            match #value { Scalar::Int(val) => val, _ => return None }
+        },
+        "u16" | "u32" => quote! {
+            match #value { Scalar::UInt(val) => val as #ty_ident, _ => return None }
+        },
+        "u64" => quote! {
+            match #value { Scalar::UInt(val) => val, _ => return None }
+        },
+        "f16" => quote! {
+            match #value { Scalar::Float(val) => f16::from_f32(val as f32), _ => return None }
+        },
+        "f32" => quote! {
+            match #value { Scalar::Float(val) => val as f32, _ => return None }
+        },
+        "f64" => quote! {
+            match #value { Scalar::Float(val) => val, _ => return None }
+        },
+        _ => unreachable!(),
+    }
+}
+
 fn types(out_dir: &str) {
     let builder = TokenStream::from_str("$builder").unwrap();
     let constant = TokenStream::from_str("$constant").unwrap();
@@ -36,12 +105,27 @@ fn types(out_dir: &str) {
     let mut type_name_arms = Vec::new();
     let mut typed_value_from = Vec::new();
     let mut register_constant_arms = Vec::new();
+    let mut scalar_ptr_consts = Vec::new();
+    let mut scalar_ptr_arms = Vec::new();
+    let mut components_arms = Vec::new();
+    let mut from_components_arms = Vec::new();
 
     for &(name, _, ty) in INTS.iter().chain(FLOATS.iter()) {
         let const_name = Ident::new(&name.to_string().to_uppercase(), Span::call_site());
         let glsl_name = name.to_string().to_lowercase();
         let name = Ident::new(&name, Span::call_site());
 
+        let pattern = scalar_pattern(&const_name.to_string()).unwrap();
+        let ptr_name_uni = Ident::new(&format!("{}_PTR_UNI", const_name), Span::call_site());
+        let ptr_name_fun = Ident::new(&format!("{}_PTR_FUN", const_name), Span::call_site());
+        scalar_ptr_consts.push(quote! {
+            const #ptr_name_uni: &'static Self = &TypeName::_Pointer(Self::#const_name, StorageClass::Uniform);
+            const #ptr_name_fun: &'static Self = &TypeName::_Pointer(Self::#const_name, StorageClass::Function);
+        });
+        scalar_ptr_arms.push(quote! {
+            #pattern => if is_uniform { Self::#ptr_name_uni } else { Self::#ptr_name_fun },
+        });
+
         from_string_arms.push(quote! {
             #glsl_name => Self::#const_name,
         });
@@ -83,20 +167,45 @@ fn types(out_dir: &str) {
                 }
             },
 
-            "i32" | "u32" => quote! {
+            "i16" | "u16" | "i32" | "u32" => quote! {
                 #builder.module.types_global_values.push(
                     Instruction::new(
                         Op::Constant,
                         Some(res_type),
                         Some(res_id),
                         vec![
-                            #[allow(clippy::cast_sign_loss)]
+                            #[allow(clippy::cast_sign_loss, clippy::cast_lossless)]
                             Operand::LiteralInt32(val as u32),
                         ]
                     )
                 );
             },
+            "i64" | "u64" => quote! {
+                #builder.module.types_global_values.push(
+                    Instruction::new(
+                        Op::Constant,
+                        Some(res_type),
+                        Some(res_id),
+                        vec![
+                            #[allow(clippy::cast_sign_loss)]
+                            Operand::LiteralInt64(val as u64),
+                        ]
+                    )
+                );
+            },
 
+            "f16" => quote! {
+                #builder.module.types_global_values.push(
+                    Instruction::new(
+                        Op::Constant,
+                        Some(res_type),
+                        Some(res_id),
+                        vec![
+                            Operand::LiteralInt32(u32::from(val.to_bits())),
+                        ]
+                    )
+                );
+            },
             "f32" => quote! {
                 #builder.module.types_global_values.push(
                     Instruction::new(
@@ -134,6 +243,17 @@ fn types(out_dir: &str) {
             },
         });
 
+        let value = Ident::new("val", Span::call_site());
+        let component = Ident::new("c0", Span::call_site());
+        let to_value = to_scalar(ty, &value);
+        let from_value = from_scalar(ty, &component);
+        components_arms.push(quote! {
+            TypedValue::#name(#value) => vec![ #to_value ],
+        });
+        from_components_arms.push(quote! {
+            (ty, &[#component]) if ty == TypeName::#const_name => TypedValue::#name(#from_value),
+        });
+
         let ty = Ident::new(&ty, Span::call_site());
         typed_variants.push(quote! {
             #name(#ty)
@@ -188,6 +308,27 @@ fn types(out_dir: &str) {
                 .map(|i| Ident::new(&format!("f{}_id", i), Span::call_site()))
                 .collect();
 
+            let value_fields = fields.clone();
+            let to_fields: Vec<_> = fields.iter().map(|field| to_scalar(ty, field)).collect();
+            components_arms.push(quote! {
+                TypedValue::#type_variant(#( #value_fields ),*) => vec![ #( #to_fields ),* ],
+            });
+
+            let components: Vec<_> = (0..size)
+                .map(|i| Ident::new(&format!("c{}", i), Span::call_site()))
+                .collect();
+            let from_fields: Vec<_> = {
+                components
+                    .iter()
+                    .map(|component| from_scalar(ty, component))
+                    .collect()
+            };
+            from_components_arms.push(quote! {
+                (ty, &[#( #components ),*]) if ty == TypeName::#const_name => {
+                    TypedValue::#type_variant(#( #from_fields ),*)
+                },
+            });
+
             let register_fields: Vec<_> = field_ids
                 .iter()
                 .zip(fields.iter())
@@ -291,15 +432,7 @@ fn types(out_dir: &str) {
                         ty
                     } else {
                         let name = name.to_string();
-                        return Ok(match &name as &str {
-                            "BOOL" => quote! { TypeName::Bool },
-                            "INT" => quote! { TypeName::Int(true) },
-                            "UINT" => quote! { TypeName::Int(false) },
-                            "FLOAT" => quote! { TypeName::Float(false) },
-                            "DOUBLE" => quote! { TypeName::Float(true) },
-
-                            other => panic!("{:?}", other),
-                        });
+                        return Ok(scalar_pattern(&name).unwrap_or_else(|| panic!("{:?}", name)));
                     };
 
                     Ok(match *ty {
@@ -367,6 +500,7 @@ fn types(out_dir: &str) {
     let type_name_impl = quote! {
         #[allow(clippy::unseparated_literal_suffix)]
         impl TypeName {
+            #( #scalar_ptr_consts )*
             #( #const_types )*
 
             #[inline]
@@ -380,7 +514,7 @@ fn types(out_dir: &str) {
             #[inline]
             pub(crate) fn as_ptr(&'static self, is_uniform: bool) -> &'static Self {
                 match *self {
-                    TypeName::Float(false) => if is_uniform { Self::FLOAT_PTR_UNI } else { Self::FLOAT_PTR_FUN },
+                    #( #scalar_ptr_arms )*
                     #( #ptr_arms )*
                     ref other => panic!("Missing as_ptr implementation: {:?}", other),
                 }
@@ -390,7 +524,7 @@ fn types(out_dir: &str) {
 
     let typed_value = quote! {
         /// Holder for a GLSL value and a type
-        #[derive(Debug)]
+        #[derive(Clone, Debug, PartialEq)]
         #[allow(clippy::unseparated_literal_suffix)]
         pub enum TypedValue {
             #( #typed_variants ),*
@@ -405,6 +539,24 @@ fn types(out_dir: &str) {
                     #( #type_name_arms ),*
                 }
             }
+
+            /// Get the components of a scalar or vector value, or `None` for matrices
+            pub(crate) fn components(&self) -> Option<Vec<Scalar>> {
+                Some(match *self {
+                    #( #components_arms )*
+                    _ => return None,
+                })
+            }
+
+            /// Build a scalar or vector value from its type and components, or return `None`
+            /// if they don't match
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            pub(crate) fn from_components(ty: &'static TypeName, components: &[Scalar]) -> Option<Self> {
+                Some(match (ty, components) {
+                    #( #from_components_arms )*
+                    _ => return None,
+                })
+            }
         }
 
         #( #typed_value_from )*
@@ -423,7 +575,7 @@ fn types(out_dir: &str) {
     let register_constant = quote! {
         match *#constant {
             #( #register_constant_arms )*
-            _ => Err(ErrorKind::UnsupportedConstant(#constant.to_type_name()))
+            _ => Err(Error::UnsupportedConstant(#constant.to_type_name()))
         }
     };
 
@@ -613,6 +765,21 @@ const NODES: &'static [(&'static str, &'static str, &'static str)] = &[
         "Returns true if the first operand is less than or equal to the second",
         "Takes 2 parameters",
     ),
+    (
+        "EmitVertex",
+        "Emit the current values of the outputs as a vertex (geometry shaders only)",
+        "Takes the previous EmitVertex or EndPrimitive node first if there is one, followed by the Output nodes written for this vertex. The instruction has no operands, the arguments only order the emission after the nodes it depends on, so at least one is required",
+    ),
+    (
+        "EndPrimitive",
+        "Finish the current output primitive (geometry shaders only)",
+        "Takes the last EmitVertex node of the primitive. The instruction has no operands, the argument only orders the end of the primitive after its vertices",
+    ),
+    (
+        "Barrier",
+        "Wait for all the invocations of the patch to reach this point (tessellation control shaders only)",
+        "Takes any number of parameters, evaluated before the barrier (usually the Output nodes it synchronizes)",
+    ),
 ];
 
 fn nodes(out_dir: &str) {
@@ -640,22 +807,34 @@ fn nodes(out_dir: &str) {
 
     let node = quote! {
         /// All the supported operations
-        #[derive(Debug)]
+        #[derive(Clone, Debug, PartialEq)]
         pub enum Node {
-            /// Create an input with a location and a type
+            /// Create an input with a location, a type and interpolation attributes
             ///
             /// Incoming values from other nodes are ignored
-            Input(u32, &'static TypeName, VariableName),
+            Input(u32, &'static TypeName, VariableName, Attributes),
+
+            /// Read an element of an arrayed input with a location, a type and interpolation
+            /// attributes, holding a value for each vertex of the primitive
+            ///
+            /// Takes one parameter: the index of the vertex to read
+            ArrayedInput(u32, &'static TypeName, VariableName, Attributes),
 
             /// Create a uniform with a location and a type
             ///
             /// Incoming values from other nodes are ignored
             Uniform(u32, &'static TypeName, VariableName),
 
-            /// Create an output with a location and a type
+            /// Create an output with a location, a type and interpolation attributes
             ///
             /// Doesn't need to be an output of the graph, but all the outputs should use this type
-            Output(u32, &'static TypeName, VariableName),
+            Output(u32, &'static TypeName, VariableName, Attributes),
+
+            /// Write the element of an arrayed output corresponding to the current invocation,
+            /// with a location, a type and interpolation attributes (tessellation control shaders only)
+            ///
+            /// Takes a single parameter: the value to be written
+            ArrayedOutput(u32, &'static TypeName, VariableName, Attributes),
 
             /// Declare a new constant
             ///
@@ -687,8 +866,10 @@ fn nodes(out_dir: &str) {
             pub fn to_string(&self) -> &'static str {
                 match *self {
                     Node::Input(..) => "Input",
+                    Node::ArrayedInput(..) => "ArrayedInput",
                     Node::Uniform(..) => "Uniform",
                     Node::Output(..) => "Output",
+                    Node::ArrayedOutput(..) => "ArrayedOutput",
                     Node::Constant(..) => "Constant",
                     Node::Construct(..) => "Construct",
                     Node::Extract(..) => "Extract",
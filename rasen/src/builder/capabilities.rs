@@ -0,0 +1,305 @@
+//! Inference of the capabilities and extensions required by a module
+
+use rspirv::mr::{Instruction, Operand};
+use spirv_headers::{ExecutionModel as ShaderType, *};
+
+use super::version::Version;
+
+/// Capabilities and extensions a device needs to support in order to run a module
+#[derive(Clone, Debug, Default)]
+pub struct Requirements {
+    version: Version,
+    capabilities: Vec<Capability>,
+    extensions: Vec<&'static str>,
+}
+
+/// List the capabilities implicitly declared by a capability
+fn implicit(capability: Capability) -> &'static [Capability] {
+    match capability {
+        Capability::Shader => &[Capability::Matrix],
+        Capability::Geometry
+        | Capability::Tessellation
+        | Capability::ClipDistance
+        | Capability::CullDistance
+        | Capability::SampleRateShading
+        | Capability::DerivativeControl
+        | Capability::ImageQuery
+        | Capability::InterpolationFunction
+        | Capability::StorageImageExtendedFormats
+        | Capability::InputAttachment
+        | Capability::SampledRect
+        | Capability::SampledCubeArray
+        | Capability::ImageMSArray
+        | Capability::MinLod
+        | Capability::DrawParameters => &[Capability::Shader],
+        Capability::GeometryPointSize | Capability::GeometryStreams => &[Capability::Geometry],
+        Capability::TessellationPointSize => &[Capability::Tessellation],
+        Capability::Image1D => &[Capability::Sampled1D],
+        Capability::ImageRect => &[Capability::SampledRect],
+        Capability::ImageBuffer => &[Capability::SampledBuffer],
+        Capability::ImageCubeArray => &[Capability::SampledCubeArray],
+        Capability::UniformAndStorageBuffer16BitAccess => {
+            &[Capability::StorageBuffer16BitAccess]
+        }
+        _ => &[],
+    }
+}
+
+/// Check if a capability is implicitly declared by another one, recursively
+fn is_implied_by(capability: Capability, other: Capability) -> bool {
+    implicit(other)
+        .iter()
+        .any(|&cap| cap == capability || is_implied_by(capability, cap))
+}
+
+/// Get the extension needed to use a capability in a given version, if any
+fn extension(capability: Capability, version: Version) -> Option<&'static str> {
+    // Both extensions were promoted to core in SPIR-V 1.3
+    if version >= Version::V1_3 {
+        return None;
+    }
+
+    match capability {
+        Capability::StorageBuffer16BitAccess
+        | Capability::UniformAndStorageBuffer16BitAccess
+        | Capability::StoragePushConstant16
+        | Capability::StorageInputOutput16 => Some("SPV_KHR_16bit_storage"),
+        Capability::DrawParameters => Some("SPV_KHR_shader_draw_parameters"),
+        _ => None,
+    }
+}
+
+fn is_extended_format(format: ImageFormat) -> bool {
+    match format {
+        ImageFormat::Rg32f
+        | ImageFormat::Rg16f
+        | ImageFormat::R11fG11fB10f
+        | ImageFormat::R16f
+        | ImageFormat::Rgba16
+        | ImageFormat::Rgb10A2
+        | ImageFormat::Rg16
+        | ImageFormat::Rg8
+        | ImageFormat::R16
+        | ImageFormat::R8
+        | ImageFormat::Rgba16Snorm
+        | ImageFormat::Rg16Snorm
+        | ImageFormat::Rg8Snorm
+        | ImageFormat::R16Snorm
+        | ImageFormat::R8Snorm
+        | ImageFormat::Rg32i
+        | ImageFormat::Rg16i
+        | ImageFormat::Rg8i
+        | ImageFormat::R16i
+        | ImageFormat::R8i
+        | ImageFormat::Rgb10a2ui
+        | ImageFormat::Rg32ui
+        | ImageFormat::Rg16ui
+        | ImageFormat::Rg8ui
+        | ImageFormat::R16ui
+        | ImageFormat::R8ui => true,
+        _ => false,
+    }
+}
+
+impl Requirements {
+    /// Declare a capability as required, if it wasn't already
+    pub fn add_capability(&mut self, capability: Capability) {
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+    }
+
+    /// Declare an extension as required, if it wasn't already
+    pub fn add_extension(&mut self, name: &'static str) {
+        if !self.extensions.contains(&name) {
+            self.extensions.push(name);
+        }
+    }
+
+    /// Get the minimal set of required capabilities, sorted by value
+    ///
+    /// Capabilities implicitly declared by another capability of the set are omitted
+    pub fn capabilities(&self) -> Vec<Capability> {
+        let mut res: Vec<_> = {
+            self.capabilities
+                .iter()
+                .cloned()
+                .filter(|&cap| {
+                    !self
+                        .capabilities
+                        .iter()
+                        .any(|&other| is_implied_by(cap, other))
+                })
+                .collect()
+        };
+
+        res.sort_by_key(|&cap| cap as u32);
+        res
+    }
+
+    /// Get the set of required extensions, sorted by name
+    pub fn extensions(&self) -> Vec<&'static str> {
+        let mut res = self.extensions.clone();
+        for &cap in &self.capabilities {
+            if let Some(ext) = extension(cap, self.version) {
+                if !res.contains(&ext) {
+                    res.push(ext);
+                }
+            }
+        }
+
+        res.sort();
+        res
+    }
+
+    /// Set the SPIR-V version the module targets
+    pub(crate) fn set_version(&mut self, version: Version) {
+        self.version = version;
+    }
+
+    /// Declare the capability needed by an execution model
+    pub(crate) fn visit_model(&mut self, model: ShaderType) {
+        self.add_capability(match model {
+            ShaderType::Geometry => Capability::Geometry,
+            ShaderType::TessellationControl | ShaderType::TessellationEvaluation => {
+                Capability::Tessellation
+            }
+            ShaderType::Kernel => Capability::Kernel,
+            _ => Capability::Shader,
+        });
+    }
+
+    /// Declare the capabilities needed by an instruction
+    pub(crate) fn visit(&mut self, model: ShaderType, inst: &Instruction) {
+        match (inst.class.opcode, &inst.operands as &[_]) {
+            (Op::TypeInt, &[Operand::LiteralInt32(width), ..]) => match width {
+                8 => self.add_capability(Capability::Int8),
+                16 => self.add_capability(Capability::Int16),
+                64 => self.add_capability(Capability::Int64),
+                _ => {}
+            },
+            (Op::TypeFloat, &[Operand::LiteralInt32(width)]) => match width {
+                16 => self.add_capability(Capability::Float16),
+                64 => self.add_capability(Capability::Float64),
+                _ => {}
+            },
+
+            (
+                Op::TypeImage,
+                &[
+                    _,
+                    Operand::Dim(dim),
+                    _,
+                    Operand::LiteralInt32(arrayed),
+                    Operand::LiteralInt32(multisampled),
+                    Operand::LiteralInt32(sampled),
+                    Operand::ImageFormat(format),
+                    ..
+                ],
+            ) => {
+                // Sampled is 1 for images used with a sampler, 2 for storage images
+                let is_storage = sampled == 2;
+                match dim {
+                    Dim::Dim1D if is_storage => self.add_capability(Capability::Image1D),
+                    Dim::Dim1D => self.add_capability(Capability::Sampled1D),
+                    Dim::DimRect if is_storage => self.add_capability(Capability::ImageRect),
+                    Dim::DimRect => self.add_capability(Capability::SampledRect),
+                    Dim::DimBuffer if is_storage => self.add_capability(Capability::ImageBuffer),
+                    Dim::DimBuffer => self.add_capability(Capability::SampledBuffer),
+                    Dim::DimCube if arrayed == 1 && is_storage => {
+                        self.add_capability(Capability::ImageCubeArray)
+                    }
+                    Dim::DimCube if arrayed == 1 => {
+                        self.add_capability(Capability::SampledCubeArray)
+                    }
+                    Dim::DimSubpassData => self.add_capability(Capability::InputAttachment),
+                    _ => {}
+                }
+
+                if multisampled == 1 && arrayed == 1 && is_storage {
+                    self.add_capability(Capability::ImageMSArray);
+                }
+
+                if is_extended_format(format) {
+                    self.add_capability(Capability::StorageImageExtendedFormats);
+                }
+            }
+
+            (Op::DPdxFine, _)
+            | (Op::DPdyFine, _)
+            | (Op::FwidthFine, _)
+            | (Op::DPdxCoarse, _)
+            | (Op::DPdyCoarse, _)
+            | (Op::FwidthCoarse, _) => self.add_capability(Capability::DerivativeControl),
+
+            (Op::ImageQuerySizeLod, _)
+            | (Op::ImageQuerySize, _)
+            | (Op::ImageQueryLod, _)
+            | (Op::ImageQueryLevels, _)
+            | (Op::ImageQuerySamples, _) => self.add_capability(Capability::ImageQuery),
+
+            (Op::EmitStreamVertex, _) | (Op::EndStreamPrimitive, _) => {
+                self.add_capability(Capability::GeometryStreams)
+            }
+
+            (Op::ExtInst, &[_, Operand::LiteralExtInstInteger(inst), ..])
+                if inst == GLOp::InterpolateAtCentroid as u32
+                    || inst == GLOp::InterpolateAtSample as u32
+                    || inst == GLOp::InterpolateAtOffset as u32 =>
+            {
+                self.add_capability(Capability::InterpolationFunction)
+            }
+
+            (Op::Decorate, &[_, Operand::Decoration(Decoration::Sample), ..]) => {
+                self.add_capability(Capability::SampleRateShading)
+            }
+            (
+                Op::Decorate,
+                &[_, Operand::Decoration(Decoration::BuiltIn), Operand::BuiltIn(built_in)],
+            )
+            | (
+                Op::MemberDecorate,
+                &[_, _, Operand::Decoration(Decoration::BuiltIn), Operand::BuiltIn(built_in)],
+            ) => self.visit_built_in(model, built_in),
+
+            _ => {}
+        }
+
+        // Image operands may be placed at various indices depending on the instruction
+        for operand in &inst.operands {
+            if let Operand::ImageOperands(operands) = *operand {
+                if operands.contains(ImageOperands::MIN_LOD) {
+                    self.add_capability(Capability::MinLod);
+                }
+            }
+        }
+    }
+
+    fn visit_built_in(&mut self, model: ShaderType, built_in: BuiltIn) {
+        match built_in {
+            BuiltIn::ClipDistance => self.add_capability(Capability::ClipDistance),
+            BuiltIn::CullDistance => self.add_capability(Capability::CullDistance),
+            BuiltIn::SampleId | BuiltIn::SamplePosition => {
+                self.add_capability(Capability::SampleRateShading)
+            }
+            BuiltIn::Layer | BuiltIn::ViewportIndex | BuiltIn::PrimitiveId
+                if model == ShaderType::Fragment =>
+            {
+                self.add_capability(Capability::Geometry)
+            }
+            BuiltIn::PointSize if model == ShaderType::Geometry => {
+                self.add_capability(Capability::GeometryPointSize)
+            }
+            BuiltIn::PointSize
+                if model == ShaderType::TessellationControl
+                    || model == ShaderType::TessellationEvaluation =>
+            {
+                self.add_capability(Capability::TessellationPointSize)
+            }
+            BuiltIn::DrawIndex | BuiltIn::BaseVertex | BuiltIn::BaseInstance => {
+                self.add_capability(Capability::DrawParameters)
+            }
+            _ => {}
+        }
+    }
+}
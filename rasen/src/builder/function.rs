@@ -1,22 +1,29 @@
 use std::iter;
 
-use fnv::FnvHashMap as HashMap;
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
 use petgraph::graph::NodeIndex;
 
 use rspirv::mr::{BasicBlock, Function, Instruction, Operand};
 use spirv_headers::*;
 
 use super::{
-    module::{FunctionData, VOID_ID},
-    Builder as BuilderTrait,
+    module::{FunctionData, Settings, VOID_ID},
+    reflection::Declaration,
+    source_map::SourceLocation,
+    Builder as BuilderTrait, VariableKey,
 };
 use errors::*;
-use module::FunctionRef;
+use module::{FunctionRef, GraphRef};
 use types::{TypeName, TypedValue};
 
 pub(crate) struct Builder<'a> {
     module: &'a mut dyn BuilderTrait,
     results: HashMap<NodeIndex<Word>, (&'static TypeName, Word)>,
+    poisoned: HashSet<NodeIndex<Word>>,
+
+    index: FunctionRef,
+    control: FunctionControl,
+    file: Option<Word>,
 
     id: Word,
     args: Vec<(&'static TypeName, Instruction)>,
@@ -25,11 +32,20 @@ pub(crate) struct Builder<'a> {
 }
 
 impl<'a> Builder<'a> {
-    pub fn new(module: &'a mut dyn BuilderTrait) -> Builder<'a> {
+    pub fn new(
+        module: &'a mut dyn BuilderTrait,
+        index: FunctionRef,
+        control: FunctionControl,
+    ) -> Builder<'a> {
         let id = module.get_id();
         Builder {
             module,
             results: HashMap::default(),
+            poisoned: HashSet::default(),
+
+            index,
+            control,
+            file: None,
 
             id,
             args: Vec::new(),
@@ -42,6 +58,7 @@ impl<'a> Builder<'a> {
         let Builder {
             module,
             id,
+            control,
             args,
             instructions,
             res,
@@ -79,10 +96,7 @@ impl<'a> Builder<'a> {
                     Op::Function,
                     Some(result_type),
                     Some(id),
-                    vec![
-                        Operand::FunctionControl(FunctionControl::empty()),
-                        Operand::IdRef(func_type),
-                    ],
+                    vec![Operand::FunctionControl(control), Operand::IdRef(func_type)],
                 )),
                 end: Some(Instruction::new(Op::FunctionEnd, None, None, Vec::new())),
                 parameters,
@@ -117,8 +131,41 @@ impl<'a> BuilderTrait for Builder<'a> {
         self.module.register_constant(constant)
     }
 
-    fn register_uniform(&mut self, location: u32, type_id: &'static TypeName) -> (Word, Word) {
-        self.module.register_uniform(location, type_id)
+    fn register_uniform(
+        &mut self,
+        location: u32,
+        type_id: &'static TypeName,
+        name: Option<String>,
+    ) -> (Word, Word) {
+        self.module.register_uniform(location, type_id, name)
+    }
+
+    fn register_per_vertex(
+        &mut self,
+        built_in: BuiltIn,
+        type_id: &'static TypeName,
+    ) -> Result<(Word, Word)> {
+        self.module.register_per_vertex(built_in, type_id)
+    }
+
+    fn get_variable(&self, key: VariableKey) -> Option<(Word, Word)> {
+        self.module.get_variable(key)
+    }
+
+    fn set_variable(&mut self, key: VariableKey, var: (Word, Word)) {
+        self.module.set_variable(key, var)
+    }
+
+    fn add_capability(&mut self, capability: Capability) {
+        self.module.add_capability(capability)
+    }
+
+    fn get_settings(&self) -> &Settings {
+        self.module.get_settings()
+    }
+
+    fn push_execution_mode(&mut self, mode: ExecutionMode) {
+        self.module.push_execution_mode(mode)
     }
 
     fn push_instruction(&mut self, inst: Instruction) {
@@ -146,6 +193,35 @@ impl<'a> BuilderTrait for Builder<'a> {
         self.module.push_debug(inst)
     }
 
+    fn register_string(&mut self, value: String) -> Word {
+        self.module.register_string(value)
+    }
+
+    fn push_reflection(&mut self, decl: Declaration) {
+        self.module.push_reflection(decl)
+    }
+
+    fn get_graph(&self) -> GraphRef {
+        GraphRef::Function(self.index)
+    }
+
+    fn map_result(&mut self, id: Word, location: SourceLocation) {
+        self.module.map_result(id, location)
+    }
+
+    fn get_debug_file(&mut self) -> Option<Word> {
+        if !self.get_settings().debug_info {
+            return None;
+        }
+
+        if self.file.is_none() {
+            let name = format!("function:{}", self.index.0);
+            self.file = Some(self.module.register_string(name));
+        }
+
+        self.file
+    }
+
     fn push_function(&mut self, func: FunctionData) {
         self.module.push_function(func)
     }
@@ -194,6 +270,18 @@ impl<'a> BuilderTrait for Builder<'a> {
         self.results.insert(index, res);
     }
 
+    fn push_error(&mut self, err: Error) {
+        self.module.push_error(err)
+    }
+
+    fn poison(&mut self, index: NodeIndex<u32>) {
+        self.poisoned.insert(index);
+    }
+
+    fn is_poisoned(&self, index: NodeIndex<u32>) -> bool {
+        self.poisoned.contains(&index)
+    }
+
     fn get_function(
         &self,
         index: FunctionRef,
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use fnv::FnvHashSet as HashSet;
+use petgraph::graph::NodeIndex;
+
+use rspirv::mr::Instruction;
+use spirv_headers::*;
+
+use super::{
+    module::{Builder as ModuleBuilder, FunctionData, Settings},
+    reflection::Declaration,
+    source_map::SourceLocation,
+    Builder as BuilderTrait, VariableKey,
+};
+use errors::*;
+use graph::Graph;
+use module::{FunctionRef, GraphRef};
+use types::{TypeName, TypedValue};
+
+/// Builder running the nodes of a graph to find the type of their results,
+/// discarding all the instructions they emit
+///
+/// The declarations are still registered in a full module builder, as the nodes
+/// check their variables and constants against it to raise the same errors as a build.
+/// Parameters and return values are accepted in any graph
+struct Builder {
+    module: ModuleBuilder,
+}
+
+impl BuilderTrait for Builder {
+    fn get_id(&mut self) -> Word {
+        self.module.get_id()
+    }
+
+    fn import_set(&mut self, name: &'static str) -> Word {
+        self.module.import_set(name)
+    }
+
+    fn register_type(&mut self, type_id: &'static TypeName) -> Word {
+        self.module.register_type(type_id)
+    }
+
+    fn register_constant(&mut self, constant: &TypedValue) -> Result<u32> {
+        self.module.register_constant(constant)
+    }
+
+    fn register_uniform(
+        &mut self,
+        location: u32,
+        type_id: &'static TypeName,
+        name: Option<String>,
+    ) -> (Word, Word) {
+        self.module.register_uniform(location, type_id, name)
+    }
+
+    fn register_per_vertex(
+        &mut self,
+        built_in: BuiltIn,
+        type_id: &'static TypeName,
+    ) -> Result<(Word, Word)> {
+        self.module.register_per_vertex(built_in, type_id)
+    }
+
+    fn get_variable(&self, key: VariableKey) -> Option<(Word, Word)> {
+        self.module.get_variable(key)
+    }
+
+    fn set_variable(&mut self, key: VariableKey, var: (Word, Word)) {
+        self.module.set_variable(key, var)
+    }
+
+    fn add_capability(&mut self, capability: Capability) {
+        self.module.add_capability(capability)
+    }
+
+    fn get_settings(&self) -> &Settings {
+        self.module.get_settings()
+    }
+
+    fn push_execution_mode(&mut self, mode: ExecutionMode) {
+        self.module.push_execution_mode(mode)
+    }
+
+    fn push_instruction(&mut self, _: Instruction) {}
+
+    fn push_declaration(&mut self, inst: Instruction) {
+        self.module.push_declaration(inst)
+    }
+
+    fn push_output(&mut self, id: Word) {
+        self.module.push_output(id)
+    }
+
+    fn push_input(&mut self, id: Word) {
+        self.module.push_input(id)
+    }
+
+    fn push_annotation(&mut self, inst: Instruction) {
+        self.module.push_annotation(inst)
+    }
+
+    fn push_debug(&mut self, inst: Instruction) {
+        self.module.push_debug(inst)
+    }
+
+    fn register_string(&mut self, value: String) -> Word {
+        self.module.register_string(value)
+    }
+
+    fn push_reflection(&mut self, decl: Declaration) {
+        self.module.push_reflection(decl)
+    }
+
+    fn get_graph(&self) -> GraphRef {
+        GraphRef::Main
+    }
+
+    fn map_result(&mut self, _: Word, _: SourceLocation) {}
+
+    fn get_debug_file(&mut self) -> Option<Word> {
+        None
+    }
+
+    fn push_function(&mut self, func: FunctionData) {
+        self.module.push_function(func)
+    }
+
+    fn push_parameter(&mut self, _: Word, _: &'static TypeName, _: Instruction) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_return(&mut self, _: &'static TypeName, _: Instruction) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_result(&self, _: NodeIndex<u32>) -> Option<(&'static TypeName, u32)> {
+        None
+    }
+
+    fn set_result(&mut self, _: NodeIndex<u32>, _: (&'static TypeName, u32)) {}
+
+    fn push_error(&mut self, _: Error) {}
+
+    fn poison(&mut self, _: NodeIndex<u32>) {}
+
+    fn is_poisoned(&self, _: NodeIndex<u32>) -> bool {
+        false
+    }
+
+    fn get_function(
+        &self,
+        index: FunctionRef,
+    ) -> Option<(Word, &[&'static TypeName], Option<&'static TypeName>)> {
+        self.module.get_function(index)
+    }
+}
+
+struct Inference<'a> {
+    graph: &'a Graph,
+    builder: &'a mut Builder,
+    results: &'a mut HashMap<NodeIndex<u32>, Result<&'static TypeName>>,
+    visiting: HashSet<NodeIndex<u32>>,
+}
+
+impl<'a> Inference<'a> {
+    /// Get the type of a node, or `None` if it cannot be typed
+    fn visit(&mut self, index: NodeIndex<u32>) -> Option<&'static TypeName> {
+        if let Some(res) = self.results.get(&index) {
+            return res.as_ref().ok().cloned();
+        }
+
+        self.visiting.insert(index);
+        let res = self.infer(index);
+        self.visiting.remove(&index);
+
+        let ty = res.as_ref().ok().cloned();
+        self.results.insert(index, res);
+        ty
+    }
+
+    fn infer(&mut self, index: NodeIndex<u32>) -> Result<&'static TypeName> {
+        let mut args = Vec::new();
+        for (position, arg) in self.graph.arguments(index)?.into_iter().enumerate() {
+            if self.visiting.contains(&arg) {
+                bail!(Error::CyclicGraph);
+            }
+
+            // The instructions are discarded, so the arguments don't need an ID
+            match self.visit(arg) {
+                Some(ty) => args.push((ty, 0)),
+                None => bail!(Error::UntypedArgument(position)),
+            }
+        }
+
+        let (ty, _) = self.graph[index].get_result(self.builder, args)?;
+        Ok(ty)
+    }
+}
+
+/// The types of the nodes of a graph, kept up to date while the graph is edited
+///
+/// The builder is kept between the updates, so the nodes typed by an update are
+/// checked against the declarations of the nodes typed before them
+pub(crate) struct TypeCache {
+    builder: Builder,
+    results: HashMap<NodeIndex<u32>, Result<&'static TypeName>>,
+}
+
+impl TypeCache {
+    /// Infer the type of every node of a graph
+    pub fn new(graph: &Graph, settings: Settings) -> Self {
+        let mut cache = TypeCache {
+            builder: Builder {
+                module: ModuleBuilder::new(settings),
+            },
+            results: HashMap::new(),
+        };
+
+        cache.update(graph, graph.nodes().map(|(index, _)| index));
+        cache
+    }
+
+    /// Get the type of a node, or the error raised when typing it
+    pub fn get(&self, index: NodeIndex<u32>) -> Option<&Result<&'static TypeName>> {
+        self.results.get(&index)
+    }
+
+    /// Forget the type of a node and of all the nodes using its result,
+    /// returning the nodes that need to be typed again
+    pub fn invalidate(&mut self, graph: &Graph, index: NodeIndex<u32>) -> Vec<NodeIndex<u32>> {
+        let mut invalidated = Vec::new();
+        let mut queue = vec![index];
+        while let Some(index) = queue.pop() {
+            if self.results.remove(&index).is_some() {
+                invalidated.push(index);
+                queue.extend(graph.outputs_of(index).map(|edge| edge.to));
+            }
+        }
+
+        invalidated
+    }
+
+    /// Infer the type of the nodes that are not typed yet, among `nodes` and their arguments
+    pub fn update(&mut self, graph: &Graph, nodes: impl IntoIterator<Item = NodeIndex<u32>>) {
+        let mut inference = Inference {
+            graph,
+            builder: &mut self.builder,
+            results: &mut self.results,
+            visiting: HashSet::default(),
+        };
+
+        for index in nodes {
+            inference.visit(index);
+        }
+    }
+}
+
+/// Find the type of the result of every node of a graph
+pub(crate) fn infer_types(
+    graph: &Graph,
+    settings: Settings,
+) -> HashMap<NodeIndex<u32>, Result<&'static TypeName>> {
+    TypeCache::new(graph, settings).results
+}
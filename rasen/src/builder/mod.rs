@@ -1,17 +1,39 @@
 use petgraph::graph::NodeIndex;
-use rspirv::mr::Instruction;
-use spirv_headers::Word;
+use rspirv::mr::{Instruction, Operand};
+use spirv_headers::{BuiltIn, Capability, Decoration, ExecutionMode, Op, StorageClass, Word};
 
 use errors::*;
 use graph::*;
-use module::FunctionRef;
+use module::{FunctionRef, GraphRef};
+use node::Node;
 use types::{TypeName, TypedValue};
 
+mod capabilities;
 mod function;
+mod infer;
 mod module;
+mod reflection;
+mod source_map;
+mod stages;
+mod version;
 
+pub use self::capabilities::Requirements;
+pub(crate) use self::infer::{infer_types, TypeCache};
 pub(crate) use self::module::FunctionData;
-pub use self::module::{Builder as ModuleBuilder, Settings};
+pub use self::module::{Builder as ModuleBuilder, InlineSettings, OptLevel, Settings, Source};
+pub(crate) use self::reflection::Declaration;
+pub use self::reflection::{
+    EntryPointReflection, InterfaceVariable, Reflection, SamplerVariable, UniformVariable,
+};
+pub use self::source_map::{SourceLocation, SourceMap};
+pub use self::stages::{
+    GeometrySettings, InputPrimitive, OutputPrimitive, Spacing, TessellationPrimitive,
+    TessellationSettings, Winding,
+};
+pub use self::version::{TargetEnv, Version};
+
+/// Identifies an interface variable by storage class, location and builtin
+pub(crate) type VariableKey = (StorageClass, Word, Option<BuiltIn>);
 
 pub(crate) trait Builder {
     /// Acquire a new identifier to be used in the module
@@ -26,7 +48,38 @@ pub(crate) trait Builder {
     /// Add a new constant to the module, returning its ID
     fn register_constant(&mut self, constant: &TypedValue) -> Result<u32>;
 
-    fn register_uniform(&mut self, location: u32, type_id: &'static TypeName) -> (Word, Word);
+    /// Add a member to the uniforms block, returning the ID of the block
+    /// variable and of the index constant of the member in the block
+    ///
+    /// The index is only defined when the module is built, as members are
+    /// numbered by their position among the uniforms used by the module
+    fn register_uniform(
+        &mut self,
+        location: u32,
+        type_id: &'static TypeName,
+        name: Option<String>,
+    ) -> (Word, Word);
+
+    /// Add a builtin to the `gl_PerVertex` output block, returning the ID
+    /// of the block variable and the index of the builtin in the block
+    fn register_per_vertex(
+        &mut self,
+        built_in: BuiltIn,
+        type_id: &'static TypeName,
+    ) -> Result<(Word, Word)>;
+
+    /// Get the ID of the variable declared for an interface slot, along with the ID of its type
+    fn get_variable(&self, key: VariableKey) -> Option<(Word, Word)>;
+    fn set_variable(&mut self, key: VariableKey, var: (Word, Word));
+
+    /// Declare a capability used by the module that can't be inferred from its instructions
+    fn add_capability(&mut self, capability: Capability);
+
+    /// Get the code generation settings of the module
+    fn get_settings(&self) -> &Settings;
+
+    /// Declare an execution mode of the entry point, if it wasn't already
+    fn push_execution_mode(&mut self, mode: ExecutionMode);
 
     fn push_instruction(&mut self, inst: Instruction);
     fn push_declaration(&mut self, inst: Instruction);
@@ -36,6 +89,22 @@ pub(crate) trait Builder {
     fn push_debug(&mut self, inst: Instruction);
     fn push_function(&mut self, func: FunctionData);
 
+    /// Declare a string used by debug instructions, returning its ID
+    fn register_string(&mut self, value: String) -> Word;
+
+    /// Record a variable declared by a node, to be listed in the reflection of the module
+    fn push_reflection(&mut self, decl: Declaration);
+
+    /// Get a reference to the graph being built
+    fn get_graph(&self) -> GraphRef;
+
+    /// Record the node a result was built from in the source map
+    fn map_result(&mut self, id: Word, location: SourceLocation);
+
+    /// Get the ID of the string naming the virtual source file of the graph
+    /// being built, if debug info is enabled
+    fn get_debug_file(&mut self) -> Option<Word>;
+
     fn push_parameter(
         &mut self,
         location: Word,
@@ -47,6 +116,13 @@ pub(crate) trait Builder {
     fn get_result(&self, index: NodeIndex<u32>) -> Option<(&'static TypeName, u32)>;
     fn set_result(&mut self, index: NodeIndex<u32>, res: (&'static TypeName, u32));
 
+    /// Record an error raised by a node, when collecting errors
+    fn push_error(&mut self, err: Error);
+
+    /// Mark a node as failed, so its dependents are not built
+    fn poison(&mut self, index: NodeIndex<u32>);
+    fn is_poisoned(&self, index: NodeIndex<u32>) -> bool;
+
     fn get_function(
         &self,
         index: FunctionRef,
@@ -60,20 +136,162 @@ pub(crate) trait Builder {
             return Ok(res);
         }
 
-        let args: Result<Vec<_>> = {
-            graph
-                .arguments(index)
-                .map(|edge| self.visit(graph, edge))
-                .collect()
+        let collect_errors = self.get_settings().collect_errors;
+        if self.is_poisoned(index) {
+            bail!(Error::PoisonedNode(index));
+        }
+
+        let edges = match graph.arguments(index) {
+            Ok(edges) => edges,
+            Err(err) => return Err(fail(self, index, err)),
         };
 
+        // When collecting errors, all the arguments are visited before
+        // poisoning the node so the errors of every branch are reported
+        let mut args = Vec::with_capacity(edges.len());
+        let mut poisoned = false;
+        for edge in edges {
+            match self.visit(graph, edge) {
+                Ok(arg) => args.push(arg),
+                Err(_) if collect_errors => poisoned = true,
+                Err(err) => return Err(err),
+            }
+        }
+
+        if poisoned {
+            self.poison(index);
+            bail!(Error::PoisonedNode(index));
+        }
+
         let node = &graph[index];
-        let res = {
-            node.get_result(self, args?)
-                .chain_err(|| ErrorKind::BuildError(node.to_string(), index.index()))?
+        let file = self.get_debug_file();
+        if let Some(file_id) = file {
+            push_line(self, node, index, file_id);
+        }
+
+        let location = SourceLocation {
+            graph: self.get_graph(),
+            node: index,
+        };
+
+        let arg_types: Vec<_> = args.iter().map(|&(ty, _)| ty).collect();
+        let res = match node.get_result(self, args) {
+            Ok(res) => res,
+            Err(err) => {
+                let err = Error::BuildError {
+                    location,
+                    name: node.to_string(),
+                    args: arg_types,
+                    path: Vec::new(),
+                    source: Box::new(err),
+                };
+
+                return Err(fail(self, index, err));
+            }
         };
 
+        if self.get_settings().relaxed_precision {
+            decorate_relaxed(self, node, res);
+        }
+
+        if file.is_some() {
+            name_result(self, node, index, res);
+        }
+
+        if has_own_result(node) && res.1 != 0 {
+            self.map_result(res.1, location);
+        }
+
         self.set_result(index, res);
         Ok(res)
     }
 }
+
+/// Handle an error raised while building a node, returning the error to propagate
+///
+/// When collecting errors, the error is recorded and the node is poisoned,
+/// so its dependents are skipped instead of failing again
+fn fail(module: &mut impl Builder, index: NodeIndex<u32>, err: Error) -> Error {
+    if !module.get_settings().collect_errors {
+        return err;
+    }
+
+    module.push_error(err);
+    module.poison(index);
+    Error::PoisonedNode(index)
+}
+
+/// Check if a node creates a new result
+fn has_own_result(node: &Node) -> bool {
+    // Constants are shared, and these nodes forward their argument
+    match *node {
+        Node::Constant(_) | Node::Output(..) | Node::ArrayedOutput(..) | Node::Return => false,
+        _ => true,
+    }
+}
+
+/// Add a `RelaxedPrecision` decoration to the result of a node, if applicable
+fn decorate_relaxed(
+    module: &mut impl Builder,
+    node: &Node,
+    (res_type, res_id): (&'static TypeName, Word),
+) {
+    if !has_own_result(node) {
+        return;
+    }
+
+    if res_type.width() == Some(32) {
+        module.push_annotation(Instruction::new(
+            Op::Decorate,
+            None,
+            None,
+            vec![
+                Operand::IdRef(res_id),
+                Operand::Decoration(Decoration::RelaxedPrecision),
+            ],
+        ));
+    }
+}
+
+/// Add an `OpLine` instruction attributing the following instructions to a node,
+/// using the index of the node as the line number
+#[allow(clippy::cast_possible_truncation)]
+fn push_line(module: &mut impl Builder, node: &Node, index: NodeIndex<u32>, file_id: Word) {
+    // Constants are declared globally and don't emit any instruction in the function
+    if let Node::Constant(_) = *node {
+        return;
+    }
+
+    module.push_instruction(Instruction::new(
+        Op::Line,
+        None,
+        None,
+        vec![
+            Operand::IdRef(file_id),
+            Operand::LiteralInt32(index.index() as u32),
+            Operand::LiteralInt32(0),
+        ],
+    ));
+}
+
+/// Name the result of a node after its type and index
+fn name_result(
+    module: &mut impl Builder,
+    node: &Node,
+    index: NodeIndex<u32>,
+    (res_type, res_id): (&'static TypeName, Word),
+) {
+    if !has_own_result(node) || res_type == TypeName::VOID {
+        return;
+    }
+
+    module.push_debug(Instruction::new(
+        Op::Name,
+        None,
+        None,
+        vec![
+            Operand::IdRef(res_id),
+            Operand::LiteralString(format!("{}_{}", node, index.index())),
+        ],
+    ));
+}
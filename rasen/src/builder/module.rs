@@ -1,10 +1,11 @@
 use std::{
+    collections::VecDeque,
     convert::TryFrom,
     iter, mem,
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use fnv::FnvHashMap as HashMap;
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
 use petgraph::{
     algo::toposort,
     graph::{Graph as PetGraph, NodeIndex},
@@ -16,20 +17,236 @@ use rspirv::{
 };
 use spirv_headers::{ExecutionModel as ShaderType, *};
 
-use super::{function::Builder as FunctionBuilder, Builder as BuilderTrait};
+use super::{
+    capabilities::Requirements,
+    function::Builder as FunctionBuilder,
+    reflection::{
+        Declaration, EntryPointReflection, InterfaceVariable, Reflection, SamplerVariable,
+        UniformVariable,
+    },
+    source_map::{SourceLocation, SourceMap},
+    stages::{GeometrySettings, TessellationSettings},
+    version::{self, TargetEnv, Version},
+    Builder as BuilderTrait, VariableKey,
+};
 use errors::*;
 use graph::*;
-use module::{FunctionRef, Module as RasenModule};
-use node::VariableName;
+use module::{EntryPointRef, FunctionRef, GraphRef, InlineHint, Module as RasenModule};
+use node::{Node, VariableName};
+use passes::{PassManager, PassTiming};
 use types::{TypeName, TypedValue};
+use validate::validate;
+
+/// Description of the source the module was generated from,
+/// emitted in an `OpSource` instruction
+#[derive(Clone, Debug)]
+pub struct Source {
+    /// The language of the source
+    pub language: SourceLanguage,
+    /// The version of the source language
+    pub version: u32,
+    /// The name of the source file
+    pub file: Option<String>,
+}
+
+/// Heuristics of the function inlining pass
+#[derive(Clone, Debug)]
+pub struct InlineSettings {
+    /// Inline the calls to functions before building a module
+    pub enabled: bool,
+    /// Inline the functions with at most this many nodes, including their parameters
+    /// and return node, the functions called only once are always inlined
+    pub max_nodes: usize,
+}
+
+impl Default for InlineSettings {
+    /// Disabled, inlining the functions of up to 8 nodes when enabled
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_nodes: 8,
+        }
+    }
+}
+
+/// The passes run on the graphs before they're built
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OptLevel {
+    /// Build the graphs as they are
+    None,
+    /// Fold the constants, merge the common subexpressions and remove the dead nodes
+    Basic,
+    /// Also inline the functions and simplify the algebraic expressions
+    Full,
+}
+
+impl Default for OptLevel {
+    fn default() -> Self {
+        OptLevel::None
+    }
+}
 
 /// Global code generation settings
 #[derive(Clone, Debug)]
 pub struct Settings {
-    /// The type of the shader module being built
+    /// The type of the shader module being built, or of the main graph
+    /// for modules with several entry points
     pub mod_type: ShaderType,
+    /// The name of the entry point of the module, or of the main graph
+    /// for modules with several entry points
+    pub entry_name: String,
     /// The name of the uniforms block struct
     pub uniforms_name: Option<String>,
+    /// Decorate the 32-bit results of all the nodes with `RelaxedPrecision`,
+    /// allowing the driver to compute them with a lower precision
+    pub relaxed_precision: bool,
+    /// The version of the SPIR-V specification the module conforms to
+    pub version: Version,
+    /// The client API the module will be consumed by
+    pub target_env: TargetEnv,
+    /// Group the `Position`, `PointSize` and `ClipDistance` builtin outputs
+    /// in a `gl_PerVertex` block instead of declaring them as standalone variables
+    pub per_vertex_block: bool,
+    /// The configuration of the module if it's a geometry shader
+    pub geometry: GeometrySettings,
+    /// The configuration of the module if it's a tessellation control or evaluation shader
+    pub tessellation: TessellationSettings,
+    /// The source the module was generated from
+    pub source: Option<Source>,
+    /// The processes the module went through, emitted in `OpModuleProcessed` instructions
+    pub processes: Vec<String>,
+    /// Emit `OpLine` and `OpName` instructions mapping the results back to the nodes
+    /// of the graphs, each graph being declared as a virtual source file
+    pub debug_info: bool,
+    /// Keep building past the nodes failing to compile, skipping their dependents,
+    /// and fail with a `MultipleErrors` error listing all of them
+    pub collect_errors: bool,
+    /// Inline the calls to the small or single-use functions of a module,
+    /// ignored along with `opt_level` if `debug_info` or `collect_errors` is set
+    pub inlining: InlineSettings,
+    /// Allow the simplifications changing the results of floating-point operations,
+    /// like removing the additions of zero or the normalization of normalized vectors
+    pub fast_math: bool,
+    /// The optimization passes run on the graphs before building them, none are run
+    /// if `debug_info` or `collect_errors` is set so the nodes are built as given
+    pub opt_level: OptLevel,
+    /// Check the built module with the structural validator, failing with `InvalidModule`
+    /// if it's malformed. Enabled by default in debug builds only, as the checks are slow
+    /// on large modules
+    pub validate: bool,
+}
+
+impl Default for Settings {
+    /// Default settings for a SPIR-V 1.0 fragment shader targeting Vulkan 1.0
+    fn default() -> Self {
+        Self {
+            mod_type: ShaderType::Fragment,
+            entry_name: String::from("main"),
+            uniforms_name: None,
+            relaxed_precision: false,
+            version: Version::default(),
+            target_env: TargetEnv::default(),
+            per_vertex_block: false,
+            geometry: GeometrySettings::default(),
+            tessellation: TessellationSettings::default(),
+            source: None,
+            processes: Vec::new(),
+            debug_info: false,
+            collect_errors: false,
+            inlining: InlineSettings::default(),
+            fast_math: false,
+            opt_level: OptLevel::default(),
+            validate: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl Settings {
+    /// Check if passes should run on the graphs before building them
+    ///
+    /// The passes are disabled when the results must be mapped back to the nodes,
+    /// as they rewrite the graphs and the locations would refer to the rewritten nodes
+    pub(crate) fn has_passes(&self) -> bool {
+        let keeps_nodes = self.debug_info || self.collect_errors;
+        !keeps_nodes && (self.opt_level != OptLevel::None || self.inlining.enabled)
+    }
+
+    /// Get these settings without the passes, once they ran
+    pub(crate) fn without_passes(self) -> Self {
+        Self {
+            opt_level: OptLevel::None,
+            inlining: InlineSettings {
+                enabled: false,
+                ..self.inlining
+            },
+            ..self
+        }
+    }
+
+    /// Get the length of the arrayed inputs of the module, if the stage has any
+    pub(crate) fn input_vertices(&self) -> Option<u32> {
+        match self.mod_type {
+            ShaderType::Geometry => Some(self.geometry.input.vertex_count()),
+            ShaderType::TessellationControl | ShaderType::TessellationEvaluation => {
+                Some(self.tessellation.patch_vertices)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the length of the arrayed outputs of the module, if the stage has any
+    pub(crate) fn output_vertices(&self) -> Option<u32> {
+        match self.mod_type {
+            ShaderType::TessellationControl => Some(self.tessellation.output_vertices),
+            _ => None,
+        }
+    }
+
+    /// Check the configuration of a stage is within the limits guaranteed by Vulkan
+    fn check(&self, model: ShaderType) -> Result<()> {
+        match model {
+            ShaderType::Geometry => {
+                let geometry = &self.geometry;
+                if geometry.max_vertices == 0 || geometry.max_vertices > 256 {
+                    bail!(Error::InvalidSettings(
+                        "max_vertices must be between 1 and 256"
+                    ));
+                }
+                if geometry.invocations == 0 || geometry.invocations > 32 {
+                    bail!(Error::InvalidSettings(
+                        "invocations must be between 1 and 32"
+                    ));
+                }
+            }
+
+            ShaderType::TessellationControl | ShaderType::TessellationEvaluation => {
+                let tessellation = &self.tessellation;
+                if tessellation.patch_vertices == 0 || tessellation.patch_vertices > 32 {
+                    bail!(Error::InvalidSettings(
+                        "patch_vertices must be between 1 and 32"
+                    ));
+                }
+                if tessellation.output_vertices == 0 || tessellation.output_vertices > 32 {
+                    bail!(Error::InvalidSettings(
+                        "output_vertices must be between 1 and 32"
+                    ));
+                }
+            }
+
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl From<ShaderType> for Settings {
+    fn from(mod_type: ShaderType) -> Self {
+        Self {
+            mod_type,
+            ..Self::default()
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Hash)]
@@ -37,6 +254,9 @@ enum CachedConstant {
     Bool(bool),
     Int(i32),
     UInt(u32),
+    Int64(i64),
+    UInt64(u64),
+    Half(u16),
     Float(bool, i16, u32),
     Double(bool, i16, u64),
 }
@@ -75,6 +295,296 @@ impl CachedConstant {
     }
 }
 
+/// Create an `OpExecutionMode` instruction for an entry point
+fn execution_mode(entry_id: Word, mode: ExecutionMode, literal: Option<u32>) -> Instruction {
+    let mut operands = vec![Operand::IdRef(entry_id), Operand::ExecutionMode(mode)];
+    operands.extend(literal.map(Operand::LiteralInt32));
+    Instruction::new(Op::ExecutionMode, None, None, operands)
+}
+
+/// Build an output of a graph, ignoring the errors already collected by the builder
+fn visit_output(
+    builder: &mut impl BuilderTrait,
+    graph: &Graph,
+    index: NodeIndex<u32>,
+) -> Result<()> {
+    match builder.visit(graph, index) {
+        Err(_) if builder.get_settings().collect_errors => Ok(()),
+        res => res.map(|_| ()),
+    }
+}
+
+/// Fill the call path of an error raised by a node of a function
+fn set_call_path(err: &mut Error, module: &RasenModule, roots: &[(GraphRef, &Graph)]) {
+    if let Error::BuildError {
+        location,
+        ref mut path,
+        ..
+    } = *err
+    {
+        if let GraphRef::Function(function) = location.graph {
+            *path = call_path(module, roots, function);
+        }
+    }
+}
+
+/// Get the functions called by a node
+fn callees(node: &Node) -> Vec<FunctionRef> {
+    match *node {
+        Node::Call(callee) => vec![callee],
+        Node::Loop(cond, body) => vec![cond, body],
+        _ => Vec::new(),
+    }
+}
+
+/// Find the functions called by a graph, directly or through other functions
+fn called_functions(module: &RasenModule, graph: &Graph) -> HashSet<FunctionRef> {
+    let mut res = HashSet::default();
+    let mut stack = vec![graph];
+
+    while let Some(graph) = stack.pop() {
+        for (_, node) in graph.nodes() {
+            for callee in callees(node) {
+                if let Some(callee_graph) = module.functions.get(callee.0) {
+                    if res.insert(callee) {
+                        stack.push(callee_graph);
+                    }
+                }
+            }
+        }
+    }
+
+    res
+}
+
+/// Check if the instructions emitted for a node depend on the stage of the entry point
+fn is_stage_specific(node: &Node) -> bool {
+    match *node {
+        Node::Input(..)
+        | Node::ArrayedInput(..)
+        | Node::Output(..)
+        | Node::ArrayedOutput(..)
+        | Node::EmitVertex
+        | Node::EndPrimitive
+        | Node::Barrier => true,
+        _ => false,
+    }
+}
+
+/// Find the functions that must be built for each entry point calling them:
+/// the ones using stage-specific nodes, and the ones calling them
+fn stage_specific_functions(module: &RasenModule) -> Vec<bool> {
+    let mut res: Vec<_> = {
+        module
+            .functions
+            .iter()
+            .map(|graph| graph.nodes().any(|(_, node)| is_stage_specific(node)))
+            .collect()
+    };
+
+    loop {
+        let mut changed = false;
+        for (index, graph) in module.functions.iter().enumerate() {
+            let calls_specific = graph.nodes().any(|(_, node)| {
+                callees(node)
+                    .into_iter()
+                    .any(|callee| res.get(callee.0).cloned().unwrap_or(false))
+            });
+
+            if calls_specific && !res[index] {
+                res[index] = true;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return res;
+        }
+    }
+}
+
+/// Iterate over the instructions of the body of a function
+fn function_instructions(function: &FunctionData) -> impl Iterator<Item = &Instruction> {
+    let (_, _, _, ref func) = *function;
+    func.basic_blocks
+        .iter()
+        .flat_map(|block| &block.instructions)
+}
+
+/// Find the `Call` and `Loop` nodes leading from one of the root graphs to a function,
+/// going through the shortest chain of calls
+fn call_path(
+    module: &RasenModule,
+    roots: &[(GraphRef, &Graph)],
+    function: FunctionRef,
+) -> Vec<SourceLocation> {
+    let mut callers: HashMap<FunctionRef, SourceLocation> = HashMap::default();
+    let mut queue: VecDeque<_> = roots.iter().cloned().collect();
+
+    while let Some((graph_ref, graph)) = queue.pop_front() {
+        for (node, value) in graph.nodes() {
+            for callee in callees(value) {
+                if callers.contains_key(&callee) {
+                    continue;
+                }
+
+                if let Some(callee_graph) = module.functions.get(callee.0) {
+                    let location = SourceLocation {
+                        graph: graph_ref,
+                        node,
+                    };
+
+                    callers.insert(callee, location);
+                    queue.push_back((GraphRef::Function(callee), callee_graph));
+                }
+            }
+        }
+    }
+
+    let mut path = Vec::new();
+    let mut current = function;
+    while let Some(&location) = callers.get(&current) {
+        path.push(location);
+        match location.graph {
+            GraphRef::Function(caller) => current = caller,
+            _ => break,
+        }
+    }
+
+    path.reverse();
+    path
+}
+
+/// Compute the std140 offsets of the members of a uniform block, in order
+fn std140_offsets<'a>(members: impl Iterator<Item = &'a TypeName>) -> Vec<u32> {
+    let mut offset = 0;
+    members
+        .map(|member| {
+            let alignment = member.std140_alignment().max(1);
+            let start = (offset + alignment - 1) / alignment * alignment;
+            offset = start + member.std140_size();
+            start
+        })
+        .collect()
+}
+
+/// Check if an instruction uses an ID as an operand
+fn references(inst: &Instruction, id: Word) -> bool {
+    inst.operands.iter().any(|op| match *op {
+        Operand::IdRef(other) => other == id,
+        _ => false,
+    })
+}
+
+/// An entry point of the module, with the state specific to its stage
+#[derive(Debug)]
+struct EntryPoint {
+    name: String,
+    model: ShaderType,
+    id: Word,
+    label: Word,
+
+    inputs: Vec<Word>,
+    outputs: Vec<Word>,
+    execution_modes: Vec<Instruction>,
+    instructions: Vec<Instruction>,
+
+    per_vertex: Option<(Word, Word)>,
+    per_vertex_members: Vec<(BuiltIn, Word)>,
+
+    // The functions built for the stage of this entry point, with their index in the builder
+    functions: Vec<(FunctionRef, usize)>,
+
+    file: Option<Word>,
+    graph: GraphRef,
+
+    reflected_inputs: Vec<InterfaceVariable>,
+    reflected_outputs: Vec<InterfaceVariable>,
+}
+
+impl EntryPoint {
+    /// Create a new entry point with the execution modes required by its stage
+    fn new(name: String, graph: GraphRef, settings: &Settings, id: Word, label: Word) -> Self {
+        let execution_modes = match settings.mod_type {
+            ShaderType::Fragment => vec![execution_mode(
+                id,
+                match settings.target_env {
+                    TargetEnv::OpenGL4_5 => ExecutionMode::OriginLowerLeft,
+                    _ => ExecutionMode::OriginUpperLeft,
+                },
+                None,
+            )],
+
+            ShaderType::Geometry => {
+                let geometry = &settings.geometry;
+                vec![
+                    execution_mode(id, geometry.input.execution_mode(), None),
+                    execution_mode(id, ExecutionMode::Invocations, Some(geometry.invocations)),
+                    execution_mode(id, geometry.output.execution_mode(), None),
+                    execution_mode(
+                        id,
+                        ExecutionMode::OutputVertices,
+                        Some(geometry.max_vertices),
+                    ),
+                ]
+            }
+
+            ShaderType::TessellationControl => vec![execution_mode(
+                id,
+                ExecutionMode::OutputVertices,
+                Some(settings.tessellation.output_vertices),
+            )],
+
+            ShaderType::TessellationEvaluation => {
+                let tessellation = &settings.tessellation;
+                let mut res = vec![
+                    execution_mode(id, tessellation.primitive.execution_mode(), None),
+                    execution_mode(id, tessellation.spacing.execution_mode(), None),
+                    execution_mode(id, tessellation.winding.execution_mode(), None),
+                ];
+
+                if tessellation.point_mode {
+                    res.push(execution_mode(id, ExecutionMode::PointMode, None));
+                }
+
+                res
+            }
+
+            _ => Vec::new(),
+        };
+
+        Self {
+            name,
+            model: settings.mod_type,
+            id,
+            label,
+
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            execution_modes,
+            instructions: Vec::new(),
+
+            per_vertex: None,
+            per_vertex_members: Vec::new(),
+
+            functions: Vec::new(),
+
+            file: None,
+            graph,
+
+            reflected_inputs: Vec::new(),
+            reflected_outputs: Vec::new(),
+        }
+    }
+
+    /// Check if a variable is an input or an output of this entry point
+    fn has_interface(&self, id: Word) -> bool {
+        self.inputs.contains(&id)
+            || self.outputs.contains(&id)
+            || self.per_vertex.map_or(false, |(ty_id, _)| ty_id == id)
+    }
+}
+
 /// Builds the dependency graph for a list of Instructions and perform a topological sort
 fn sort_instructions(unsorted: &[Instruction]) -> Result<Vec<Instruction>> {
     let mut decl_graph = PetGraph::new();
@@ -106,7 +616,7 @@ fn sort_instructions(unsorted: &[Instruction]) -> Result<Vec<Instruction>> {
     }
 
     match toposort(&decl_graph, None) {
-        Err(_) => bail!(ErrorKind::CyclicGraph),
+        Err(_) => bail!(Error::CyclicGraph),
         Ok(indices) => Ok(indices
             .into_iter()
             .map(|i| {
@@ -135,21 +645,45 @@ pub struct Builder {
     settings: Settings,
     counter: AtomicUsize,
 
-    inputs: Vec<Word>,
-    outputs: Vec<Word>,
+    entry: EntryPoint,
+    entry_points: Vec<EntryPoint>,
 
     imports: HashMap<&'static str, (Word, Instruction)>,
+    requirements: Requirements,
+    strings: Vec<Instruction>,
+    source_map: SourceMap,
+
+    reflected_uniforms: Vec<UniformVariable>,
+    reflected_samplers: Vec<SamplerVariable>,
 
     pub(crate) module: Module,
-    instructions: Vec<Instruction>,
     pub(crate) functions: Vec<FunctionData>,
+    shared_functions: Vec<(FunctionRef, usize)>,
 
     uniform: Option<(Word, Word)>,
-    uniforms: HashMap<Word, (Word, &'static TypeName)>,
+    uniforms: HashMap<Word, UniformMember>,
+
+    variables: HashMap<VariableKey, (Word, Word)>,
 
     types: HashMap<&'static TypeName, Word>,
     constants: HashMap<CachedConstant, Word>,
     results: HashMap<NodeIndex<Word>, (&'static TypeName, Word)>,
+
+    errors: Vec<Error>,
+    poisoned: HashSet<NodeIndex<Word>>,
+
+    pass_timings: Vec<PassTiming>,
+}
+
+/// A member of the uniforms block, keyed by its location
+#[derive(Debug)]
+struct UniformMember {
+    type_id: Word,
+    type_name: &'static TypeName,
+    /// The ID of the index constant used to access the member, defined
+    /// once the position of the member in the block is known
+    index_id: Word,
+    name: Option<String>,
 }
 
 pub const VOID_ID: Word = 1;
@@ -162,37 +696,25 @@ include!(concat!(env!("OUT_DIR"), "/builder.rs"));
 impl Builder {
     /// Create a new shader builder with some predefined base values
     pub fn new(settings: Settings) -> Self {
-        let execution_modes = match settings.mod_type {
-            ShaderType::Fragment => vec![Instruction::new(
-                Op::ExecutionMode,
-                None,
-                None,
-                vec![
-                    Operand::IdRef(ENTRY_ID),
-                    Operand::ExecutionMode(ExecutionMode::OriginUpperLeft),
-                ],
-            )],
-
-            _ => Vec::new(),
-        };
+        let name = settings.entry_name.clone();
+        let entry = EntryPoint::new(name, GraphRef::Main, &settings, ENTRY_ID, LABEL_ID);
 
         Self {
             settings,
             counter: AtomicUsize::new(5),
 
-            inputs: Vec::new(),
-            outputs: Vec::new(),
+            entry,
+            entry_points: Vec::new(),
 
             imports: HashMap::default(),
+            requirements: Requirements::default(),
+            strings: Vec::new(),
+            source_map: SourceMap::default(),
 
-            module: Module {
-                capabilities: vec![Instruction::new(
-                    Op::Capability,
-                    None,
-                    None,
-                    vec![Operand::Capability(Capability::Shader)],
-                )],
+            reflected_uniforms: Vec::new(),
+            reflected_samplers: Vec::new(),
 
+            module: Module {
                 memory_model: Some(Instruction::new(
                     Op::MemoryModel,
                     None,
@@ -203,8 +725,6 @@ impl Builder {
                     ],
                 )),
 
-                execution_modes,
-
                 types_global_values: vec![
                     Instruction::new(Op::TypeVoid, None, Some(VOID_ID), Vec::new()),
                     Instruction::new(
@@ -218,54 +738,240 @@ impl Builder {
                 ..Module::new()
             },
 
-            instructions: Vec::new(),
             functions: Vec::new(),
+            shared_functions: Vec::new(),
 
             uniform: None,
             uniforms: HashMap::default(),
 
+            variables: HashMap::default(),
+
             types: HashMap::default(),
             constants: HashMap::default(),
             results: HashMap::default(),
+
+            errors: Vec::new(),
+            poisoned: HashSet::default(),
+
+            pass_timings: Vec::new(),
         }
     }
 
+    /// Fail with all the errors collected while building the graphs, if any
+    fn check_errors(&mut self) -> Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+
+        let errors = mem::replace(&mut self.errors, Vec::new());
+        bail!(Error::MultipleErrors(errors))
+    }
+
     /// Create a new Builder and add instructions to it based on a Graph
+    ///
+    /// The passes selected by the settings run on a copy of the graph first,
+    /// their timings are available from `pass_timings`
     pub fn from_graph(graph: &Graph, settings: Settings) -> Result<Self> {
+        if settings.has_passes() {
+            let mut graph = graph.clone();
+            let mut manager = PassManager::from_settings(&settings);
+            manager.run_graph(&mut graph, &settings)?;
+
+            let mut program = Self::from_graph(&graph, settings.without_passes())?;
+            program.pass_timings = manager.timings().to_vec();
+            return Ok(program);
+        }
+
         if graph.has_cycle() {
-            bail!(ErrorKind::CyclicGraph);
+            bail!(Error::CyclicGraph);
         }
 
         let mut program = Self::new(settings);
         for node in graph.outputs() {
-            program.visit(graph, node)?;
+            visit_output(&mut program, graph, node)?;
         }
 
+        program.check_errors()?;
         Ok(program)
     }
 
     /// Create a new Builder and add instructions to it based on a Module
+    ///
+    /// The passes selected by the settings run on a copy of the module first,
+    /// their timings are available from `pass_timings`
     pub fn from_module(module: &RasenModule, settings: Settings) -> Result<Self> {
-        if module.main.has_cycle() {
-            bail!(ErrorKind::CyclicGraph);
+        if settings.has_passes() {
+            let mut module = module.clone();
+            let mut manager = PassManager::from_settings(&settings);
+            manager.run_module(&mut module, &settings)?;
+
+            let mut program = Self::from_module(&module, settings.without_passes())?;
+            program.pass_timings = manager.timings().to_vec();
+            return Ok(program);
         }
 
-        let mut program = Self::new(settings);
-        for function in &module.functions {
-            let mut proxy = FunctionBuilder::new(&mut program);
+        // The main graph is omitted if it's empty and other entry points were declared
+        let main_name = settings.entry_name.clone();
+        let main = if module.main.is_empty() && !module.entry_points.is_empty() {
+            None
+        } else {
+            Some((
+                main_name.as_str(),
+                GraphRef::Main,
+                settings.mod_type,
+                &module.main,
+            ))
+        };
+
+        let entry_points: Vec<_> = {
+            main.into_iter()
+                .chain(
+                    module
+                        .entry_points
+                        .iter()
+                        .enumerate()
+                        .map(|(index, entry)| {
+                            let graph = GraphRef::EntryPoint(EntryPointRef(index));
+                            (entry.name.as_str(), graph, entry.mod_type, &entry.graph)
+                        }),
+                )
+                .collect()
+        };
+
+        if entry_points
+            .iter()
+            .any(|&(_, _, _, graph)| graph.has_cycle())
+        {
+            bail!(Error::CyclicGraph);
+        }
+
+        let (name, graph_ref, mod_type, _) = entry_points[0];
+        let mut program = Self::new(Settings {
+            mod_type,
+            ..settings
+        });
+        program.entry.name = String::from(name);
+        program.entry.graph = graph_ref;
+
+        let roots: Vec<_> = {
+            entry_points
+                .iter()
+                .map(|&(_, graph_ref, _, graph)| (graph_ref, graph))
+                .collect()
+        };
+
+        // The functions only computing values are shared by all the entry points,
+        // the other ones are built for the stage and interface of each caller
+        let specific = stage_specific_functions(module);
+        let called: Vec<_> = {
+            roots
+                .iter()
+                .map(|&(_, graph)| called_functions(module, graph))
+                .collect()
+        };
+
+        for function in (0..module.functions.len()).map(FunctionRef) {
+            if !specific[function.0] {
+                let built = program.build_function(module, function, &roots)?;
+                program.shared_functions.push((function, built));
+            }
+        }
+
+        for (index, &(name, graph_ref, mod_type, graph)) in entry_points.iter().enumerate() {
+            if index > 0 {
+                program.begin_entry_point(name, graph_ref, mod_type)?;
+            }
+
+            // The functions no entry point calls are built for the first one
+            for function in (0..module.functions.len()).map(FunctionRef) {
+                let uncalled = index == 0 && called.iter().all(|set| !set.contains(&function));
+                if specific[function.0] && (uncalled || called[index].contains(&function)) {
+                    let built = program.build_function(module, function, &roots[index..=index])?;
+                    program.entry.functions.push((function, built));
+                }
+            }
+
+            for node in graph.outputs() {
+                visit_output(&mut program, graph, node)?;
+            }
+        }
+
+        program.check_errors()?;
+        Ok(program)
+    }
+
+    /// Build a function of a module, returning its index in the builder
+    ///
+    /// The call path of the errors is searched from the given root graphs
+    fn build_function(
+        &mut self,
+        module: &RasenModule,
+        function: FunctionRef,
+        roots: &[(GraphRef, &Graph)],
+    ) -> Result<usize> {
+        let control = match module.inline_hint(function) {
+            InlineHint::Auto => FunctionControl::empty(),
+            InlineHint::Always => FunctionControl::INLINE,
+            InlineHint::Never => FunctionControl::DONT_INLINE,
+        };
 
-            for node in function.outputs() {
-                proxy.visit(function, node)?;
+        let graph = &module.functions[function.0];
+        let errors = self.errors.len();
+
+        {
+            let mut proxy = FunctionBuilder::new(self, function, control);
+
+            for node in graph.outputs() {
+                visit_output(&mut proxy, graph, node).map_err(|mut err| {
+                    set_call_path(&mut err, module, roots);
+                    err
+                })?;
             }
 
             proxy.build();
         }
 
-        for node in module.main.outputs() {
-            program.visit(&module.main, node)?;
+        for err in &mut self.errors[errors..] {
+            set_call_path(err, module, roots);
         }
 
-        Ok(program)
+        Ok(self.functions.len() - 1)
+    }
+
+    /// Finish the current entry point and start building a new one
+    fn begin_entry_point(
+        &mut self,
+        name: &str,
+        graph: GraphRef,
+        mod_type: ShaderType,
+    ) -> Result<()> {
+        let exists = self
+            .iter_entry_points()
+            .any(|entry| entry.name == name && entry.model == mod_type);
+
+        if exists {
+            bail!(Error::DuplicateEntryPoint(String::from(name)));
+        }
+
+        self.settings.mod_type = mod_type;
+
+        let id = self.get_id();
+        let label = self.get_id();
+        let entry = EntryPoint::new(String::from(name), graph, &self.settings, id, label);
+
+        let prev = mem::replace(&mut self.entry, entry);
+        self.entry_points.push(prev);
+
+        // Interface variables and node results are specific to each graph
+        self.variables.clear();
+        self.results.clear();
+        self.poisoned.clear();
+        Ok(())
+    }
+
+    /// Iterate over the entry points of the module, in declaration order
+    fn iter_entry_points(&self) -> impl Iterator<Item = &EntryPoint> + Clone {
+        self.entry_points.iter().chain(iter::once(&self.entry))
     }
 
     fn get_uniform_block(&mut self) -> (Word, Word) {
@@ -313,6 +1019,58 @@ impl Builder {
         res
     }
 
+    fn get_per_vertex_block(&mut self) -> (Word, Word) {
+        if let Some(res) = self.entry.per_vertex {
+            return res;
+        }
+
+        let ty_id = self.get_id();
+        self.module.annotations.push(Instruction::new(
+            Op::Decorate,
+            None,
+            None,
+            vec![
+                Operand::IdRef(ty_id),
+                Operand::Decoration(Decoration::Block),
+            ],
+        ));
+
+        self.module.debugs.push(Instruction::new(
+            Op::Name,
+            None,
+            None,
+            vec![
+                Operand::IdRef(ty_id),
+                Operand::LiteralString(String::from("gl_PerVertex")),
+            ],
+        ));
+
+        let ptr_id = self.get_id();
+        self.module.types_global_values.push(Instruction::new(
+            Op::TypePointer,
+            None,
+            Some(ptr_id),
+            vec![
+                Operand::StorageClass(StorageClass::Output),
+                Operand::IdRef(ty_id),
+            ],
+        ));
+
+        let var_id = self.get_id();
+        self.module.types_global_values.push(Instruction::new(
+            Op::Variable,
+            Some(ptr_id),
+            Some(var_id),
+            vec![Operand::StorageClass(StorageClass::Output)],
+        ));
+
+        self.entry.outputs.push(var_id);
+
+        let res = (ty_id, var_id);
+        self.entry.per_vertex = Some(res);
+        res
+    }
+
     /// Get the ID bound of this module
     #[inline]
     #[allow(clippy::cast_possible_truncation)]
@@ -320,69 +1078,384 @@ impl Builder {
         self.counter.load(Ordering::SeqCst) as u32
     }
 
+    /// Iterate over the instructions of the functions declared in the module
+    fn iter_functions(&self) -> impl Iterator<Item = &Instruction> {
+        self.functions.iter().flat_map(|&(_, _, _, ref func)| {
+            func.basic_blocks
+                .iter()
+                .flat_map(|block| &block.instructions)
+        })
+    }
+
+    /// Iterate over all the instructions added to the module so far
+    fn iter_instructions(&self) -> impl Iterator<Item = &Instruction> {
+        let entry_points = self.iter_entry_points();
+
+        entry_points
+            .clone()
+            .flat_map(|entry| &entry.execution_modes)
+            .chain(&self.module.debugs)
+            .chain(&self.module.annotations)
+            .chain(&self.module.types_global_values)
+            .chain(entry_points.flat_map(|entry| &entry.instructions))
+            .chain(self.iter_functions())
+    }
+
+    /// Get the capabilities and extensions required by the instructions
+    /// added to the module so far
+    pub fn requirements(&self) -> Requirements {
+        let mut res = self.requirements.clone();
+        res.set_version(self.settings.version);
+
+        for entry in self.iter_entry_points() {
+            res.visit_model(entry.model);
+
+            for inst in entry.execution_modes.iter().chain(&entry.instructions) {
+                res.visit(entry.model, inst);
+            }
+
+            for &(_, index) in &entry.functions {
+                for inst in function_instructions(&self.functions[index]) {
+                    res.visit(entry.model, inst);
+                }
+            }
+        }
+
+        let shared = self
+            .shared_functions
+            .iter()
+            .flat_map(|&(_, index)| function_instructions(&self.functions[index]));
+
+        let globals = self
+            .module
+            .debugs
+            .iter()
+            .chain(&self.module.annotations)
+            .chain(&self.module.types_global_values)
+            .chain(shared);
+
+        for inst in globals {
+            // Builtin decorations depend on the stage of the variable they're applied to
+            let model = match inst.operands.get(0) {
+                Some(&Operand::IdRef(id)) => self
+                    .iter_entry_points()
+                    .find(|entry| entry.has_interface(id))
+                    .map(|entry| entry.model),
+                _ => None,
+            };
+
+            res.visit(model.unwrap_or(self.entry.model), inst);
+        }
+
+        res
+    }
+
+    /// Add the debug instructions describing where the module came from
+    fn push_source_info(&mut self) {
+        let mut header = mem::replace(&mut self.strings, Vec::new());
+
+        if let Some(source) = self.settings.source.clone() {
+            let mut operands = vec![
+                Operand::SourceLanguage(source.language),
+                Operand::LiteralInt32(source.version),
+            ];
+
+            if let Some(file) = source.file {
+                let file_id = self.get_id();
+                header.push(Instruction::new(
+                    Op::String,
+                    None,
+                    Some(file_id),
+                    vec![Operand::LiteralString(file)],
+                ));
+
+                operands.push(Operand::IdRef(file_id));
+            }
+
+            header.push(Instruction::new(Op::Source, None, None, operands));
+        }
+
+        // Sources come before the names, and processes after them
+        let names = mem::replace(&mut self.module.debugs, header);
+        self.module.debugs.extend(names);
+
+        for process in &self.settings.processes {
+            self.module.debugs.push(Instruction::new(
+                Op::ModuleProcessed,
+                None,
+                None,
+                vec![Operand::LiteralString(process.clone())],
+            ));
+        }
+    }
+
+    /// Check the module can be emitted with the targeted version and environment
+    fn check_version(&self) -> Result<()> {
+        let Settings {
+            version,
+            target_env,
+            ..
+        } = self.settings;
+
+        if version > target_env.max_version() {
+            bail!(Error::UnsupportedTarget(version, target_env));
+        }
+
+        for inst in self.iter_instructions() {
+            let op = inst.class.opcode;
+            let required = version::min_version(op);
+            if required > version {
+                bail!(Error::UnsupportedVersion(op, required, version));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Number the members of the uniforms block by their position in the
+    /// block, sorted by location, and define their index constants and
+    /// decorations. Returns the type of each member in order
+    ///
+    /// The members are only numbered once all the nodes are built, as
+    /// uniforms removed from the graph leave no hole in the block
+    #[allow(clippy::cast_possible_truncation)]
+    fn declare_uniform_members(&mut self) -> Vec<Word> {
+        let struct_id = match self.uniform {
+            Some((ty_id, _)) => ty_id,
+            None => return Vec::new(),
+        };
+
+        let mut uniforms: Vec<_> = self.uniforms.drain().collect();
+        uniforms.sort_by_key(|&(location, _)| location);
+
+        let int_id = self.register_type(TypeName::INT);
+        let offsets = std140_offsets(uniforms.iter().map(|&(_, ref member)| member.type_name));
+
+        let mut members = Vec::with_capacity(uniforms.len());
+        for (index, ((_, member), offset)) in uniforms.into_iter().zip(offsets).enumerate() {
+            let index = index as u32;
+
+            self.module.types_global_values.push(Instruction::new(
+                Op::Constant,
+                Some(int_id),
+                Some(member.index_id),
+                vec![Operand::LiteralInt32(index)],
+            ));
+
+            self.module.annotations.push(Instruction::new(
+                Op::MemberDecorate,
+                None,
+                None,
+                vec![
+                    Operand::IdRef(struct_id),
+                    Operand::LiteralInt32(index),
+                    Operand::Decoration(Decoration::Offset),
+                    Operand::LiteralInt32(offset),
+                ],
+            ));
+
+            if let Some(stride) = member.type_name.matrix_stride() {
+                self.module.annotations.push(Instruction::new(
+                    Op::MemberDecorate,
+                    None,
+                    None,
+                    vec![
+                        Operand::IdRef(struct_id),
+                        Operand::LiteralInt32(index),
+                        Operand::Decoration(Decoration::MatrixStride),
+                        Operand::LiteralInt32(stride),
+                    ],
+                ));
+                self.module.annotations.push(Instruction::new(
+                    Op::MemberDecorate,
+                    None,
+                    None,
+                    vec![
+                        Operand::IdRef(struct_id),
+                        Operand::LiteralInt32(index),
+                        Operand::Decoration(Decoration::ColMajor),
+                    ],
+                ));
+            }
+
+            if let Some(name) = member.name {
+                self.module.debugs.push(Instruction::new(
+                    Op::MemberName,
+                    None,
+                    None,
+                    vec![
+                        Operand::IdRef(struct_id),
+                        Operand::LiteralInt32(index),
+                        Operand::LiteralString(name),
+                    ],
+                ));
+            }
+
+            members.push(member.type_id);
+        }
+
+        members
+    }
+
     /// Build the module, returning a list of instructions
+    ///
+    /// The module is checked by the structural validator before being returned
+    /// if `settings.validate` is set
+    #[allow(clippy::cast_possible_truncation)]
     pub fn build(mut self) -> Result<Module> {
-        let mut uniforms: Vec<(Word, Word, &'static TypeName)> = {
-            self.uniforms
-                .iter()
-                .map(|(k, &(a, b))| (*k, a, b))
+        let check_module = self.settings.validate;
+        for entry in self.iter_entry_points() {
+            self.settings.check(entry.model)?;
+        }
+
+        let uniforms = self.declare_uniform_members();
+        self.push_source_info();
+        self.check_version()?;
+        let requirements = self.requirements();
+        let bound = self.bound();
+
+        // Starting with SPIR-V 1.4, the interface lists all the global
+        // variables referenced by the entry point, not only the inputs and outputs
+        let globals: Vec<Vec<Word>> = if self.settings.version >= Version::V1_4 {
+            let variables: Vec<_> = {
+                self.module
+                    .types_global_values
+                    .iter()
+                    .filter_map(|inst| {
+                        match (inst.class.opcode, inst.result_id, inst.operands.get(0)) {
+                            (Op::Variable, Some(id), Some(&Operand::StorageClass(storage)))
+                                if storage != StorageClass::Function =>
+                            {
+                                Some(id)
+                            }
+                            _ => None,
+                        }
+                    })
+                    .collect()
+            };
+
+            // Shared functions may be called from any entry point
+            self.iter_entry_points()
+                .map(|entry| {
+                    let functions: Vec<_> = {
+                        entry
+                            .functions
+                            .iter()
+                            .chain(&self.shared_functions)
+                            .map(|&(_, index)| &self.functions[index])
+                            .collect()
+                    };
+
+                    variables
+                        .iter()
+                        .cloned()
+                        .filter(|&id| {
+                            !entry.has_interface(id)
+                                && (entry.instructions.iter().any(|inst| references(inst, id))
+                                    || functions.iter().any(|function| {
+                                        function_instructions(function)
+                                            .any(|inst| references(inst, id))
+                                    }))
+                        })
+                        .collect()
+                })
                 .collect()
+        } else {
+            Vec::new()
         };
 
-        uniforms.sort_by_key(|&(k, _, _)| k);
+        let mut entry_points = mem::replace(&mut self.entry_points, Vec::new());
+        entry_points.push(self.entry);
 
-        Ok(Module {
+        let module = Module {
             header: Some(ModuleHeader {
                 magic_number: MAGIC_NUMBER,
-                version: (MAJOR_VERSION << 16) | (MAJOR_VERSION << 8),
+                version: self.settings.version.word(),
                 generator: 0xffff_0009,
-                bound: self.bound(),
+                bound,
                 reserved_word: 0,
             }),
 
-            ext_inst_imports: self.imports.into_iter().map(|(_, (_, op))| op).collect(),
+            capabilities: {
+                requirements
+                    .capabilities()
+                    .into_iter()
+                    .map(|cap| {
+                        Instruction::new(Op::Capability, None, None, vec![Operand::Capability(cap)])
+                    })
+                    .collect()
+            },
 
-            entry_points: vec![Instruction::new(Op::EntryPoint, None, None, {
-                let mut res = Vec::with_capacity(self.inputs.len() + self.outputs.len() + 3);
+            extensions: {
+                requirements
+                    .extensions()
+                    .into_iter()
+                    .map(|ext| {
+                        Instruction::new(
+                            Op::Extension,
+                            None,
+                            None,
+                            vec![Operand::LiteralString(ext.into())],
+                        )
+                    })
+                    .collect()
+            },
 
-                res.push(Operand::ExecutionModel(self.settings.mod_type));
-                res.push(Operand::IdRef(ENTRY_ID));
-                res.push(Operand::LiteralString("main".into()));
+            ext_inst_imports: self.imports.into_iter().map(|(_, (_, op))| op).collect(),
 
-                res.extend(self.inputs.into_iter().map(Operand::IdRef));
-                res.extend(self.outputs.into_iter().map(Operand::IdRef));
+            entry_points: {
+                entry_points
+                    .iter()
+                    .enumerate()
+                    .map(|(index, entry)| {
+                        let mut res =
+                            Vec::with_capacity(entry.inputs.len() + entry.outputs.len() + 3);
 
-                res
-            })],
+                        res.push(Operand::ExecutionModel(entry.model));
+                        res.push(Operand::IdRef(entry.id));
+                        res.push(Operand::LiteralString(entry.name.clone()));
 
-            annotations: {
-                let mut res = self.module.annotations;
+                        res.extend(entry.inputs.iter().cloned().map(Operand::IdRef));
+                        res.extend(entry.outputs.iter().cloned().map(Operand::IdRef));
 
-                if let Some((ty_id, _)) = self.uniform {
-                    let mut offset = 0;
-                    for &(location, _, type_id) in &uniforms {
-                        res.push(Instruction::new(
-                            Op::MemberDecorate,
-                            None,
-                            None,
-                            vec![
-                                Operand::IdRef(ty_id),
-                                Operand::LiteralInt32(location),
-                                Operand::Decoration(Decoration::Offset),
-                                Operand::LiteralInt32(offset),
-                            ],
-                        ));
+                        if let Some(used) = globals.get(index) {
+                            res.extend(used.iter().cloned().map(Operand::IdRef));
+                        }
 
-                        offset += type_id.size();
-                    }
-                }
+                        Instruction::new(Op::EntryPoint, None, None, res)
+                    })
+                    .collect()
+            },
 
-                res
+            execution_modes: {
+                entry_points
+                    .iter()
+                    .flat_map(|entry| entry.execution_modes.iter().cloned())
+                    .collect()
             },
 
+            annotations: self.module.annotations,
+
             types_global_values: {
-                let mut declarations =
-                    Vec::with_capacity(self.module.types_global_values.len() + 1);
+                let mut declarations = Vec::with_capacity(
+                    self.module.types_global_values.len() + entry_points.len() + 1,
+                );
+
+                // Per-vertex builtins
+                for entry in &entry_points {
+                    if let Some((ty_id, _)) = entry.per_vertex {
+                        declarations.push(Instruction::new(
+                            Op::TypeStruct,
+                            None,
+                            Some(ty_id),
+                            entry
+                                .per_vertex_members
+                                .iter()
+                                .map(|&(_, member_type)| Operand::IdRef(member_type))
+                                .collect(),
+                        ));
+                    }
+                }
 
                 // Uniforms
                 if let Some((ty_id, _)) = self.uniform {
@@ -390,10 +1463,7 @@ impl Builder {
                         Op::TypeStruct,
                         None,
                         Some(ty_id),
-                        uniforms
-                            .into_iter()
-                            .map(|(_, v, _)| Operand::IdRef(v))
-                            .collect(),
+                        uniforms.into_iter().map(Operand::IdRef).collect(),
                     ));
                 }
 
@@ -404,39 +1474,98 @@ impl Builder {
 
             // Functions
             functions: {
-                iter::once(Function {
-                    def: Some(Instruction::new(
-                        Op::Function,
-                        Some(VOID_ID),
-                        Some(ENTRY_ID),
-                        vec![
-                            Operand::FunctionControl(FunctionControl::empty()),
-                            Operand::IdRef(FUNC_ID),
-                        ],
-                    )),
-                    end: Some(Instruction::new(Op::FunctionEnd, None, None, Vec::new())),
-                    parameters: Vec::new(),
-                    basic_blocks: vec![BasicBlock {
-                        label: Some(Instruction::new(
-                            Op::Label,
-                            None,
-                            Some(LABEL_ID),
-                            Vec::new(),
+                entry_points
+                    .into_iter()
+                    .map(|entry| Function {
+                        def: Some(Instruction::new(
+                            Op::Function,
+                            Some(VOID_ID),
+                            Some(entry.id),
+                            vec![
+                                Operand::FunctionControl(FunctionControl::empty()),
+                                Operand::IdRef(FUNC_ID),
+                            ],
                         )),
-                        instructions: {
-                            let mut res = self.instructions;
-                            res.push(Instruction::new(Op::Return, None, None, Vec::new()));
-
-                            res
-                        },
-                    }],
-                })
-                .chain(self.functions.into_iter().map(|(_, _, _, func)| func))
-                .collect()
+                        end: Some(Instruction::new(Op::FunctionEnd, None, None, Vec::new())),
+                        parameters: Vec::new(),
+                        basic_blocks: vec![BasicBlock {
+                            label: Some(Instruction::new(
+                                Op::Label,
+                                None,
+                                Some(entry.label),
+                                Vec::new(),
+                            )),
+                            instructions: {
+                                let mut res = entry.instructions;
+                                res.push(Instruction::new(Op::Return, None, None, Vec::new()));
+
+                                res
+                            },
+                        }],
+                    })
+                    .chain(self.functions.into_iter().map(|(_, _, _, func)| func))
+                    .collect()
             },
 
             ..self.module
-        })
+        };
+
+        if check_module {
+            let errors = validate(&module);
+            if !errors.is_empty() {
+                bail!(Error::InvalidModule(errors));
+            }
+        }
+
+        Ok(module)
+    }
+
+    /// Get the time spent in each pass run on the graphs by `from_graph` or
+    /// `from_module`, in the order they ran
+    pub fn pass_timings(&self) -> &[PassTiming] {
+        &self.pass_timings
+    }
+
+    /// Describe the interface of the module, as declared so far
+    pub fn reflection(&self) -> Reflection {
+        let mut uniforms = self.reflected_uniforms.clone();
+        uniforms.sort_by_key(|uniform| uniform.location);
+
+        // Offsets are assigned in the same order as the `Offset` decorations of the block
+        let offsets = std140_offsets(uniforms.iter().map(|uniform| uniform.type_name));
+        for (uniform, offset) in uniforms.iter_mut().zip(offsets) {
+            uniform.offset = offset;
+        }
+
+        let mut samplers = self.reflected_samplers.clone();
+        samplers.sort_by_key(|sampler| sampler.location);
+
+        Reflection {
+            entry_points: {
+                self.iter_entry_points()
+                    .map(|entry| EntryPointReflection {
+                        name: entry.name.clone(),
+                        stage: entry.model,
+                        inputs: entry.reflected_inputs.clone(),
+                        outputs: entry.reflected_outputs.clone(),
+                    })
+                    .collect()
+            },
+            uniforms_name: if self.uniform.is_some() {
+                self.settings.uniforms_name.clone()
+            } else {
+                None
+            },
+            uniforms,
+            samplers,
+            push_constants: None,
+        }
+    }
+
+    /// Build the module, also returning the nodes each result was built from
+    pub fn build_with_source_map(mut self) -> Result<(Module, SourceMap)> {
+        let source_map = mem::replace(&mut self.source_map, SourceMap::default());
+        Ok((self.build()?, source_map))
     }
 
     /// Get the instructions of the module in assembly form
@@ -453,8 +1582,10 @@ impl Builder {
     pub fn into_binary(self) -> Result<Vec<u8>> {
         let mut res = self.into_words()?;
         let ptr = res.as_mut_ptr();
-        let len = res.len().checked_mul(4).ok_or("Integer overflow")?;
-        let cap = res.capacity().checked_mul(4).ok_or("Integer overflow")?;
+
+        // An allocation never exceeds `isize::MAX` bytes, so the byte lengths can't overflow
+        let len = res.len() * 4;
+        let cap = res.capacity() * 4;
 
         Ok(unsafe {
             mem::forget(res);
@@ -515,7 +1646,7 @@ impl BuilderTrait for Builder {
 
                 bool_id
             }
-            TypeName::Int(is_signed) => {
+            TypeName::Int(is_signed, width) => {
                 let int_id = self.get_id();
 
                 self.module.types_global_values.push(Instruction::new(
@@ -523,21 +1654,21 @@ impl BuilderTrait for Builder {
                     None,
                     Some(int_id),
                     vec![
-                        Operand::LiteralInt32(32),
+                        Operand::LiteralInt32(width),
                         Operand::LiteralInt32(if is_signed { 1 } else { 0 }),
                     ],
                 ));
 
                 int_id
             }
-            TypeName::Float(is_double) => {
+            TypeName::Float(width) => {
                 let float_id = self.get_id();
 
                 self.module.types_global_values.push(Instruction::new(
                     Op::TypeFloat,
                     None,
                     Some(float_id),
-                    vec![Operand::LiteralInt32(if is_double { 64 } else { 32 })],
+                    vec![Operand::LiteralInt32(width)],
                 ));
 
                 float_id
@@ -623,6 +1754,9 @@ impl BuilderTrait for Builder {
             TypedValue::Bool(v) => Some(CachedConstant::Bool(v)),
             TypedValue::Int(v) => Some(CachedConstant::Int(v)),
             TypedValue::UInt(v) => Some(CachedConstant::UInt(v)),
+            TypedValue::Int64(v) => Some(CachedConstant::Int64(v)),
+            TypedValue::UInt64(v) => Some(CachedConstant::UInt64(v)),
+            TypedValue::Half(v) => Some(CachedConstant::Half(v.to_bits())),
             TypedValue::Float(v) => Some(CachedConstant::from_f32(v)),
             TypedValue::Double(v) => Some(CachedConstant::from_f64(v)),
             _ => None,
@@ -642,17 +1776,135 @@ impl BuilderTrait for Builder {
         Ok(id)
     }
 
-    fn register_uniform(&mut self, location: u32, type_id: &'static TypeName) -> (Word, Word) {
-        let (struct_id, var_id) = self.get_uniform_block();
+    fn register_uniform(
+        &mut self,
+        location: u32,
+        type_id: &'static TypeName,
+        name: Option<String>,
+    ) -> (Word, Word) {
+        let (_, var_id) = self.get_uniform_block();
+        if let Some(member) = self.uniforms.get(&location) {
+            return (var_id, member.index_id);
+        }
+
+        // UniformAndStorageBuffer16BitAccess implicitly declares StorageBuffer16BitAccess
+        if type_id.width() == Some(16) {
+            self.add_capability(Capability::UniformAndStorageBuffer16BitAccess);
+        }
 
         let ty_id = self.register_type(type_id);
-        self.uniforms.insert(location, (ty_id, type_id));
+        let index_id = self.get_id();
+        self.uniforms.insert(
+            location,
+            UniformMember {
+                type_id: ty_id,
+                type_name: type_id,
+                index_id,
+                name,
+            },
+        );
 
-        (struct_id, var_id)
+        (var_id, index_id)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn register_per_vertex(
+        &mut self,
+        built_in: BuiltIn,
+        type_id: &'static TypeName,
+    ) -> Result<(Word, Word)> {
+        let (ty_id, var_id) = self.get_per_vertex_block();
+
+        let index = self
+            .entry
+            .per_vertex_members
+            .iter()
+            .position(|&(member, _)| member == built_in);
+
+        if let Some(index) = index {
+            return Ok((var_id, index as Word));
+        }
+
+        let member_type = self.register_type(type_id);
+        let member_type = if built_in == BuiltIn::ClipDistance {
+            // ClipDistance is an array of floats, declared here with a single element
+            let len_id = self.register_constant(&TypedValue::UInt(1))?;
+            let array_id = self.get_id();
+            self.module.types_global_values.push(Instruction::new(
+                Op::TypeArray,
+                None,
+                Some(array_id),
+                vec![Operand::IdRef(member_type), Operand::IdRef(len_id)],
+            ));
+
+            array_id
+        } else {
+            member_type
+        };
+
+        // The decoration is added right away so the capabilities of the builtin are inferred
+        self.module.annotations.push(Instruction::new(
+            Op::MemberDecorate,
+            None,
+            None,
+            vec![
+                Operand::IdRef(ty_id),
+                Operand::LiteralInt32(self.entry.per_vertex_members.len() as Word),
+                Operand::Decoration(Decoration::BuiltIn),
+                Operand::BuiltIn(built_in),
+            ],
+        ));
+
+        self.entry.per_vertex_members.push((built_in, member_type));
+        self.entry.reflected_outputs.push(InterfaceVariable {
+            location: None,
+            built_in: Some(built_in),
+            type_name: type_id,
+            array_len: if built_in == BuiltIn::ClipDistance {
+                Some(1)
+            } else {
+                None
+            },
+            name: None,
+        });
+
+        Ok((var_id, (self.entry.per_vertex_members.len() - 1) as Word))
+    }
+
+    fn get_variable(&self, key: VariableKey) -> Option<(Word, Word)> {
+        self.variables.get(&key).cloned()
+    }
+
+    fn set_variable(&mut self, key: VariableKey, var: (Word, Word)) {
+        self.variables.insert(key, var);
+    }
+
+    fn add_capability(&mut self, capability: Capability) {
+        self.requirements.add_capability(capability);
+    }
+
+    fn get_settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    fn push_execution_mode(&mut self, mode: ExecutionMode) {
+        let exists = self
+            .entry
+            .execution_modes
+            .iter()
+            .any(|inst| match inst.operands.get(1) {
+                Some(&Operand::ExecutionMode(other)) => other == mode,
+                _ => false,
+            });
+
+        if !exists {
+            let inst = execution_mode(self.entry.id, mode, None);
+            self.entry.execution_modes.push(inst);
+        }
     }
 
     fn push_instruction(&mut self, inst: Instruction) {
-        self.instructions.push(inst);
+        self.entry.instructions.push(inst);
     }
 
     fn push_declaration(&mut self, inst: Instruction) {
@@ -660,23 +1912,96 @@ impl BuilderTrait for Builder {
     }
 
     fn push_output(&mut self, id: Word) {
-        self.outputs.push(id);
+        self.entry.outputs.push(id);
     }
 
     fn push_input(&mut self, id: Word) {
-        self.inputs.push(id);
+        self.entry.inputs.push(id);
     }
 
     fn push_annotation(&mut self, inst: Instruction) {
-        self.module.annotations.push(inst);
+        // Nodes sharing a variable may decorate it several times
+        let exists = self.module.annotations.iter().any(|other| {
+            other.class.opcode == inst.class.opcode && other.operands == inst.operands
+        });
+
+        if !exists {
+            self.module.annotations.push(inst);
+        }
     }
 
     fn push_debug(&mut self, inst: Instruction) {
         self.module.debugs.push(inst);
     }
 
+    fn register_string(&mut self, value: String) -> Word {
+        let id = self.get_id();
+        self.strings.push(Instruction::new(
+            Op::String,
+            None,
+            Some(id),
+            vec![Operand::LiteralString(value)],
+        ));
+
+        id
+    }
+
+    fn push_reflection(&mut self, decl: Declaration) {
+        match decl {
+            Declaration::Interface(StorageClass::Input, variable) => {
+                self.entry.reflected_inputs.push(variable);
+            }
+            Declaration::Interface(_, variable) => {
+                self.entry.reflected_outputs.push(variable);
+            }
+
+            // Uniforms and samplers are shared by all the nodes using the same location
+            Declaration::Uniform(variable) => {
+                let exists = self
+                    .reflected_uniforms
+                    .iter()
+                    .any(|other| other.location == variable.location);
+
+                if !exists {
+                    self.reflected_uniforms.push(variable);
+                }
+            }
+            Declaration::Sampler(variable) => {
+                let exists = self
+                    .reflected_samplers
+                    .iter()
+                    .any(|other| other.location == variable.location);
+
+                if !exists {
+                    self.reflected_samplers.push(variable);
+                }
+            }
+        }
+    }
+
+    fn get_graph(&self) -> GraphRef {
+        self.entry.graph
+    }
+
+    fn map_result(&mut self, id: Word, location: SourceLocation) {
+        self.source_map.insert(id, location);
+    }
+
+    fn get_debug_file(&mut self) -> Option<Word> {
+        if !self.settings.debug_info {
+            return None;
+        }
+
+        if self.entry.file.is_none() {
+            let name = format!("entry:{}", self.entry.name);
+            self.entry.file = Some(self.register_string(name));
+        }
+
+        self.entry.file
+    }
+
     fn push_parameter(&mut self, _: Word, _: &'static TypeName, _: Instruction) -> Result<()> {
-        bail!(ErrorKind::UnsupportedOperation("Parameter"))
+        bail!(Error::UnsupportedOperation("Parameter"))
     }
 
     fn push_function(&mut self, func: FunctionData) {
@@ -684,7 +2009,7 @@ impl BuilderTrait for Builder {
     }
 
     fn set_return(&mut self, _: &'static TypeName, _: Instruction) -> Result<()> {
-        bail!(ErrorKind::UnsupportedOperation("Return"))
+        bail!(Error::UnsupportedOperation("Return"))
     }
 
     fn get_result(&self, index: NodeIndex<u32>) -> Option<(&'static TypeName, u32)> {
@@ -695,12 +2020,28 @@ impl BuilderTrait for Builder {
         self.results.insert(index, res);
     }
 
+    fn push_error(&mut self, err: Error) {
+        self.errors.push(err);
+    }
+
+    fn poison(&mut self, index: NodeIndex<u32>) {
+        self.poisoned.insert(index);
+    }
+
+    fn is_poisoned(&self, index: NodeIndex<u32>) -> bool {
+        self.poisoned.contains(&index)
+    }
+
     fn get_function(
         &self,
         index: FunctionRef,
     ) -> Option<(Word, &[&'static TypeName], Option<&'static TypeName>)> {
-        self.functions
-            .get(index.0)
+        self.entry
+            .functions
+            .iter()
+            .chain(&self.shared_functions)
+            .find(|&&(function, _)| function == index)
+            .and_then(|&(_, built)| self.functions.get(built))
             .map(|&(id, ref args, res, _)| (id, args as &[_], res))
     }
 }
@@ -722,25 +2063,13 @@ impl<'a> TryFrom<(&'a RasenModule, Settings)> for Builder {
 impl<'a> TryFrom<(&'a Graph, ShaderType)> for Builder {
     type Error = Error;
     fn try_from((graph, mod_type): (&'a Graph, ShaderType)) -> Result<Self> {
-        Self::from_graph(
-            graph,
-            Settings {
-                mod_type,
-                uniforms_name: None,
-            },
-        )
+        Self::from_graph(graph, Settings::from(mod_type))
     }
 }
 
 impl<'a> TryFrom<(&'a RasenModule, ShaderType)> for Builder {
     type Error = Error;
     fn try_from((module, mod_type): (&'a RasenModule, ShaderType)) -> Result<Self> {
-        Self::from_module(
-            module,
-            Settings {
-                mod_type,
-                uniforms_name: None,
-            },
-        )
+        Self::from_module(module, Settings::from(mod_type))
     }
 }
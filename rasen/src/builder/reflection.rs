@@ -0,0 +1,115 @@
+//! Description of the interface of a module, as declared by the builder
+
+#[cfg(feature = "serialize")]
+use serde::Serializer;
+#[cfg(feature = "serialize")]
+use std::fmt::Debug;
+use std::ops::Range;
+
+use spirv_headers::{BuiltIn, ExecutionModel as ShaderType, StorageClass};
+
+use types::TypeName;
+
+/// Serialize a SPIR-V enum using its name
+#[cfg(feature = "serialize")]
+fn serialize_debug<T: Debug, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{:?}", value))
+}
+
+/// Serialize an optional SPIR-V enum using its name
+#[cfg(feature = "serialize")]
+fn serialize_debug_opt<T: Debug, S: Serializer>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match *value {
+        Some(ref value) => serializer.serialize_some(&format_args!("{:?}", value)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// An input or output variable of an entry point
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct InterfaceVariable {
+    /// The location of the variable, or `None` for builtins
+    pub location: Option<u32>,
+    /// The builtin this variable is bound to
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "serialize_debug_opt"))]
+    pub built_in: Option<BuiltIn>,
+    /// The type of the variable, or of its elements if it's arrayed
+    pub type_name: &'static TypeName,
+    /// The length of the variable if it's arrayed
+    pub array_len: Option<u32>,
+    /// The name of the variable
+    pub name: Option<String>,
+}
+
+/// A member of the uniform block
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct UniformVariable {
+    /// The location of the uniform node
+    ///
+    /// Members are stored in the block sorted by location, but the index of
+    /// a member is its position among the uniforms used by the module
+    pub location: u32,
+    /// The type of the member
+    pub type_name: &'static TypeName,
+    /// The name of the member
+    pub name: Option<String>,
+    /// The offset of the member from the start of the block, in bytes
+    pub offset: u32,
+}
+
+/// A sampler declared by the module
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct SamplerVariable {
+    /// The location of the sampler
+    pub location: u32,
+    /// The type of the sampler
+    pub type_name: &'static TypeName,
+    /// The name of the sampler
+    pub name: Option<String>,
+}
+
+/// The interface of an entry point
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct EntryPointReflection {
+    /// The name of the entry point
+    pub name: String,
+    /// The stage the entry point is executed in
+    #[cfg_attr(feature = "serialize", serde(serialize_with = "serialize_debug"))]
+    pub stage: ShaderType,
+    /// The input variables of the entry point, in declaration order
+    pub inputs: Vec<InterfaceVariable>,
+    /// The output variables of the entry point, in declaration order
+    pub outputs: Vec<InterfaceVariable>,
+}
+
+/// Description of everything a module declared, to be used when binding resources
+/// to the pipeline
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Reflection {
+    /// The entry points of the module, in declaration order
+    pub entry_points: Vec<EntryPointReflection>,
+    /// The name of the uniform block
+    pub uniforms_name: Option<String>,
+    /// The members of the uniform block, sorted by location
+    pub uniforms: Vec<UniformVariable>,
+    /// The samplers, sorted by location
+    pub samplers: Vec<SamplerVariable>,
+    /// The byte range of the push constant block, always `None` as the builder
+    /// doesn't declare push constants
+    pub push_constants: Option<Range<u32>>,
+}
+
+/// A variable declared by a node, recorded for reflection
+pub(crate) enum Declaration {
+    Interface(StorageClass, InterfaceVariable),
+    Uniform(UniformVariable),
+    Sampler(SamplerVariable),
+}
@@ -0,0 +1,58 @@
+//! Mapping from the results of a module back to the nodes they were built from
+
+use fnv::FnvHashMap as HashMap;
+use petgraph::graph::NodeIndex;
+use spirv_headers::Word;
+
+use module::GraphRef;
+
+/// The node a result was built from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// The graph holding the node
+    pub graph: GraphRef,
+    /// The index of the node in its graph
+    pub node: NodeIndex<u32>,
+}
+
+/// Mapping from the result IDs of a module to the nodes they were built from
+///
+/// Constants are shared between all the nodes declaring them, and are not mapped
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    results: HashMap<Word, SourceLocation>,
+}
+
+impl SourceMap {
+    pub(crate) fn insert(&mut self, id: Word, location: SourceLocation) {
+        self.results.insert(id, location);
+    }
+
+    /// Get the node a result was built from
+    pub fn get(&self, id: Word) -> Option<SourceLocation> {
+        self.results.get(&id).cloned()
+    }
+
+    /// Find the result built from a node
+    pub fn find(&self, location: SourceLocation) -> Option<Word> {
+        self.results
+            .iter()
+            .find(|&(_, &other)| other == location)
+            .map(|(&id, _)| id)
+    }
+
+    /// Iterate over all the mapped results
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (Word, SourceLocation)> + 'a {
+        self.results.iter().map(|(&id, &location)| (id, location))
+    }
+
+    /// Get the number of mapped results
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Check if no result was mapped
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+}
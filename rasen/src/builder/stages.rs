@@ -0,0 +1,163 @@
+//! Settings specific to some shader stages
+
+use spirv_headers::ExecutionMode;
+
+/// Kind of primitive received by a geometry shader
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InputPrimitive {
+    Points,
+    Lines,
+    LinesAdjacency,
+    Triangles,
+    TrianglesAdjacency,
+}
+
+impl InputPrimitive {
+    /// Get the number of vertices in a primitive, and the length of the arrayed inputs
+    pub fn vertex_count(self) -> u32 {
+        match self {
+            InputPrimitive::Points => 1,
+            InputPrimitive::Lines => 2,
+            InputPrimitive::LinesAdjacency => 4,
+            InputPrimitive::Triangles => 3,
+            InputPrimitive::TrianglesAdjacency => 6,
+        }
+    }
+
+    pub(crate) fn execution_mode(self) -> ExecutionMode {
+        match self {
+            InputPrimitive::Points => ExecutionMode::InputPoints,
+            InputPrimitive::Lines => ExecutionMode::InputLines,
+            InputPrimitive::LinesAdjacency => ExecutionMode::InputLinesAdjacency,
+            InputPrimitive::Triangles => ExecutionMode::Triangles,
+            InputPrimitive::TrianglesAdjacency => ExecutionMode::InputTrianglesAdjacency,
+        }
+    }
+}
+
+/// Kind of primitive emitted by a geometry shader
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OutputPrimitive {
+    Points,
+    LineStrip,
+    TriangleStrip,
+}
+
+impl OutputPrimitive {
+    pub(crate) fn execution_mode(self) -> ExecutionMode {
+        match self {
+            OutputPrimitive::Points => ExecutionMode::OutputPoints,
+            OutputPrimitive::LineStrip => ExecutionMode::OutputLineStrip,
+            OutputPrimitive::TriangleStrip => ExecutionMode::OutputTriangleStrip,
+        }
+    }
+}
+
+/// Configuration of a geometry shader
+#[derive(Clone, Debug)]
+pub struct GeometrySettings {
+    /// The primitive the shader operates on
+    pub input: InputPrimitive,
+    /// The primitive the emitted vertices are assembled into
+    pub output: OutputPrimitive,
+    /// The maximum number of vertices emitted by an invocation
+    pub max_vertices: u32,
+    /// The number of times the shader is invoked for each primitive
+    pub invocations: u32,
+}
+
+impl Default for GeometrySettings {
+    /// Default settings for a pass-through triangle shader
+    fn default() -> Self {
+        Self {
+            input: InputPrimitive::Triangles,
+            output: OutputPrimitive::TriangleStrip,
+            max_vertices: 3,
+            invocations: 1,
+        }
+    }
+}
+
+/// Kind of primitive generated by the tessellator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TessellationPrimitive {
+    Triangles,
+    Quads,
+    Isolines,
+}
+
+impl TessellationPrimitive {
+    pub(crate) fn execution_mode(self) -> ExecutionMode {
+        match self {
+            TessellationPrimitive::Triangles => ExecutionMode::Triangles,
+            TessellationPrimitive::Quads => ExecutionMode::Quads,
+            TessellationPrimitive::Isolines => ExecutionMode::Isolines,
+        }
+    }
+}
+
+/// Spacing of the segments generated by the tessellator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Spacing {
+    Equal,
+    FractionalEven,
+    FractionalOdd,
+}
+
+impl Spacing {
+    pub(crate) fn execution_mode(self) -> ExecutionMode {
+        match self {
+            Spacing::Equal => ExecutionMode::SpacingEqual,
+            Spacing::FractionalEven => ExecutionMode::SpacingFractionalEven,
+            Spacing::FractionalOdd => ExecutionMode::SpacingFractionalOdd,
+        }
+    }
+}
+
+/// Winding order of the triangles generated by the tessellator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Winding {
+    pub(crate) fn execution_mode(self) -> ExecutionMode {
+        match self {
+            Winding::Clockwise => ExecutionMode::VertexOrderCw,
+            Winding::CounterClockwise => ExecutionMode::VertexOrderCcw,
+        }
+    }
+}
+
+/// Configuration of the tessellation control and evaluation shaders
+#[derive(Clone, Debug)]
+pub struct TessellationSettings {
+    /// The number of vertices in the input patch, and the length of the arrayed inputs
+    pub patch_vertices: u32,
+    /// The number of vertices in the output patch, and the length of the arrayed outputs
+    /// of the control shader
+    pub output_vertices: u32,
+    /// The primitive generated by the tessellator
+    pub primitive: TessellationPrimitive,
+    /// The spacing of the generated segments
+    pub spacing: Spacing,
+    /// The winding order of the generated triangles
+    pub winding: Winding,
+    /// Generate points instead of lines or triangles
+    pub point_mode: bool,
+}
+
+impl Default for TessellationSettings {
+    /// Default settings for the tessellation of triangle patches
+    fn default() -> Self {
+        Self {
+            patch_vertices: 3,
+            output_vertices: 3,
+            primitive: TessellationPrimitive::Triangles,
+            spacing: Spacing::Equal,
+            winding: Winding::CounterClockwise,
+            point_mode: false,
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! SPIR-V version and target environment selection
+
+use std::fmt;
+
+use spirv_headers::{Op, Word};
+
+/// A version of the SPIR-V specification
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Version {
+    V1_0,
+    V1_1,
+    V1_2,
+    V1_3,
+    V1_4,
+    V1_5,
+    V1_6,
+}
+
+impl Version {
+    /// Get the minor version number
+    #[inline]
+    pub fn minor(self) -> u32 {
+        self as u32
+    }
+
+    /// Get the version number as encoded in the module header
+    #[inline]
+    pub fn word(self) -> Word {
+        (1 << 16) | (self.minor() << 8)
+    }
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V1_0
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "1.{}", self.minor())
+    }
+}
+
+/// The client API the module is built for
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TargetEnv {
+    Vulkan1_0,
+    Vulkan1_1,
+    Vulkan1_2,
+    Vulkan1_3,
+    OpenGL4_5,
+}
+
+impl TargetEnv {
+    /// Get the highest SPIR-V version this environment can consume
+    pub fn max_version(self) -> Version {
+        match self {
+            TargetEnv::Vulkan1_0 | TargetEnv::OpenGL4_5 => Version::V1_0,
+            TargetEnv::Vulkan1_1 => Version::V1_3,
+            TargetEnv::Vulkan1_2 => Version::V1_5,
+            TargetEnv::Vulkan1_3 => Version::V1_6,
+        }
+    }
+}
+
+impl Default for TargetEnv {
+    fn default() -> Self {
+        TargetEnv::Vulkan1_0
+    }
+}
+
+impl fmt::Display for TargetEnv {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TargetEnv::Vulkan1_0 => write!(f, "Vulkan 1.0"),
+            TargetEnv::Vulkan1_1 => write!(f, "Vulkan 1.1"),
+            TargetEnv::Vulkan1_2 => write!(f, "Vulkan 1.2"),
+            TargetEnv::Vulkan1_3 => write!(f, "Vulkan 1.3"),
+            TargetEnv::OpenGL4_5 => write!(f, "OpenGL 4.5"),
+        }
+    }
+}
+
+/// Get the first SPIR-V version an instruction is available in
+pub(crate) fn min_version(op: Op) -> Version {
+    match op {
+        Op::SizeOf
+        | Op::TypePipeStorage
+        | Op::ConstantPipeStorage
+        | Op::CreatePipeFromPipeStorage
+        | Op::GetKernelLocalSizeForSubgroupCount
+        | Op::GetKernelMaxNumSubgroups
+        | Op::TypeNamedBarrier
+        | Op::NamedBarrierInitialize
+        | Op::MemoryNamedBarrier
+        | Op::ModuleProcessed => Version::V1_1,
+
+        Op::ExecutionModeId | Op::DecorateId => Version::V1_2,
+
+        Op::GroupNonUniformElect
+        | Op::GroupNonUniformAll
+        | Op::GroupNonUniformAny
+        | Op::GroupNonUniformAllEqual
+        | Op::GroupNonUniformBroadcast
+        | Op::GroupNonUniformBroadcastFirst
+        | Op::GroupNonUniformBallot
+        | Op::GroupNonUniformInverseBallot
+        | Op::GroupNonUniformBallotBitExtract
+        | Op::GroupNonUniformBallotBitCount
+        | Op::GroupNonUniformBallotFindLSB
+        | Op::GroupNonUniformBallotFindMSB
+        | Op::GroupNonUniformShuffle
+        | Op::GroupNonUniformShuffleXor
+        | Op::GroupNonUniformShuffleUp
+        | Op::GroupNonUniformShuffleDown
+        | Op::GroupNonUniformIAdd
+        | Op::GroupNonUniformFAdd
+        | Op::GroupNonUniformIMul
+        | Op::GroupNonUniformFMul
+        | Op::GroupNonUniformSMin
+        | Op::GroupNonUniformUMin
+        | Op::GroupNonUniformFMin
+        | Op::GroupNonUniformSMax
+        | Op::GroupNonUniformUMax
+        | Op::GroupNonUniformFMax
+        | Op::GroupNonUniformBitwiseAnd
+        | Op::GroupNonUniformBitwiseOr
+        | Op::GroupNonUniformBitwiseXor
+        | Op::GroupNonUniformLogicalAnd
+        | Op::GroupNonUniformLogicalOr
+        | Op::GroupNonUniformLogicalXor
+        | Op::GroupNonUniformQuadBroadcast
+        | Op::GroupNonUniformQuadSwap => Version::V1_3,
+
+        _ => Version::V1_0,
+    }
+}
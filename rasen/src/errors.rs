@@ -1,58 +1,226 @@
-//! Error-related definitions generated by `error_chain`
+//! Error type raised while building a module
+//!
+//! The variants carry the graph, node and port they originate from,
+//! so tools can match on them to point the user at the faulty part of the graph
 
-use types::TypeName;
+use std::{error, fmt, result};
+
+use petgraph::graph::NodeIndex;
+use spirv_headers::Op;
+
+use builder::{SourceLocation, TargetEnv, Version};
 use module::FunctionRef;
+use types::TypeName;
+use validate::ValidationError;
 
-error_chain! {
-    errors {
-        /// The compiler was provided with a cyclic graph, but the data flow graph
-        /// should always be acyclic
-        CyclicGraph {
-            description("graph is cyclic")
-            display("graph is cyclic")
-        }
+/// Return early from a function with an error
+macro_rules! bail {
+    ($err:expr) => {
+        return Err($err.into());
+    };
+}
 
-        /// A call node is referencing an unknown function
-        MissingFunction(index: FunctionRef) {
-            description("missing function")
-            display("missing function {:?}", index)
-        }
+/// An error raised while building a module
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The compiler was provided with a cyclic graph, but the data flow graph
+    /// should always be acyclic
+    CyclicGraph,
 
-        /// A node is not receiving the expected number of values
-        WrongArgumentsCount(actual: usize, expected: usize) {
-            description("wrong number of arguments")
-            display("got {} arguments, expected {}", actual, expected)
-        }
+    /// A call node is referencing an unknown function
+    MissingFunction(FunctionRef),
 
-        /// A composite type was acessed with an invalid index (eg. 2 in a `vec2`)
-        IndexOutOfBound(index: u32, len: u32) {
-            description("index out of bounds")
-            display("index out of bounds ({} >= {})", index, len)
-        }
+    /// Several entry points of a module have the same name and stage
+    DuplicateEntryPoint(String),
 
-        /// A generic arguments error, usually thrown when a node receives a combination of
-        /// types it cannot handle
-        BadArguments(args: Box<[&'static TypeName]>) {
-            description("bad arguments")
-            display("bad arguments: {:?}", args)
-        }
+    /// Several edges are connected to the same port of a node
+    DuplicateEdge(NodeIndex<u32>, u32),
 
-        /// Hopefully temporary error type, thrown when creating a constant with a type not yet supported
-        UnsupportedConstant(ty: &'static TypeName) {
-            description("unsupported constant type")
-            display("unsupported constant type {:?}", ty)
-        }
+    /// The type of an argument of a node couldn't be inferred, with the port of the argument
+    UntypedArgument(usize),
+
+    /// A node is not receiving the expected number of values (actual, expected)
+    WrongArgumentsCount(usize, usize),
+
+    /// A composite type was acessed with an invalid index (eg. 2 in a `vec2`)
+    IndexOutOfBound(u32, u32),
+
+    /// A generic arguments error, usually thrown when a node receives a combination of
+    /// types it cannot handle
+    BadArguments {
+        /// The types of all the arguments received by the node
+        args: Box<[&'static TypeName]>,
+        /// The port of the rejected argument, if a single one is at fault
+        port: Option<usize>,
+    },
+
+    /// An argument of a node doesn't have the type the node requires
+    TypeMismatch {
+        /// The port of the argument
+        port: usize,
+        /// The type the node requires
+        expected: &'static TypeName,
+        /// The type of the argument
+        actual: &'static TypeName,
+    },
+
+    /// Hopefully temporary error type, thrown when creating a constant with a type not yet supported
+    UnsupportedConstant(&'static TypeName),
 
-        /// A node is used in an incompatible context
-        UnsupportedOperation(name: &'static str) {
-            description("unsupported operation")
-            display("unsupported operation {}", name)
+    /// A node is used in an incompatible context
+    UnsupportedOperation(&'static str),
+
+    /// The interpolation attributes of an input or output are invalid
+    InvalidAttributes(&'static str),
+
+    /// The stage configuration in the settings is invalid
+    InvalidSettings(&'static str),
+
+    /// The module contains an instruction not available in the targeted SPIR-V version
+    /// (instruction, required version, targeted version)
+    UnsupportedVersion(Op, Version, Version),
+
+    /// The targeted SPIR-V version cannot be consumed by the targeted environment
+    UnsupportedTarget(Version, TargetEnv),
+
+    /// The emitted module failed the structural validation
+    InvalidModule(Vec<ValidationError>),
+
+    /// Used to wrap another error with metadata about its origin node
+    BuildError {
+        /// The graph holding the node and its index
+        location: SourceLocation,
+        /// The name of the node
+        name: &'static str,
+        /// The types of the arguments received by the node
+        args: Vec<&'static TypeName>,
+        /// The `Call` and `Loop` nodes leading from an entry point to the graph
+        /// of the node, empty if the node is not in a function or the function is never called
+        path: Vec<SourceLocation>,
+        /// The error raised by the node
+        source: Box<Error>,
+    },
+
+    /// Two `Input` or `Uniform` nodes declare different variables for the same slot
+    ConflictingDeclarations(NodeIndex<u32>, NodeIndex<u32>),
+
+    /// A node depends on a node that failed to build, only raised when collecting errors
+    PoisonedNode(NodeIndex<u32>),
+
+    /// All the errors raised by the nodes of the module, when collecting errors
+    MultipleErrors(Vec<Error>),
+}
+
+/// Result type of the fallible operations of the compiler
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::CyclicGraph => write!(f, "graph is cyclic"),
+            Error::MissingFunction(index) => write!(f, "missing function {:?}", index),
+            Error::DuplicateEntryPoint(ref name) => write!(f, "duplicate entry point {:?}", name),
+            Error::DuplicateEdge(node, port) => write!(
+                f,
+                "node {} has several edges connected to port {}",
+                node.index(),
+                port
+            ),
+            Error::UntypedArgument(port) => write!(f, "the type of argument {} is unknown", port),
+            Error::WrongArgumentsCount(actual, expected) => {
+                write!(f, "got {} arguments, expected {}", actual, expected)
+            }
+            Error::IndexOutOfBound(index, len) => {
+                write!(f, "index out of bounds ({} >= {})", index, len)
+            }
+            Error::BadArguments {
+                ref args,
+                port: Some(port),
+            } => write!(f, "bad argument {} in {:?}", port, args),
+            Error::BadArguments {
+                ref args,
+                port: None,
+            } => write!(f, "bad arguments: {:?}", args),
+            Error::TypeMismatch {
+                port,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "argument {} has type {:?}, expected {:?}",
+                port, actual, expected
+            ),
+            Error::UnsupportedConstant(ty) => write!(f, "unsupported constant type {:?}", ty),
+            Error::UnsupportedOperation(name) => write!(f, "unsupported operation {}", name),
+            Error::InvalidAttributes(reason) => write!(f, "invalid attributes: {}", reason),
+            Error::InvalidSettings(reason) => write!(f, "invalid settings: {}", reason),
+            Error::UnsupportedVersion(op, required, target) => write!(
+                f,
+                "Op{:?} requires SPIR-V {}, but the target version is {}",
+                op, required, target
+            ),
+            Error::UnsupportedTarget(version, env) => {
+                write!(f, "SPIR-V {} is not supported by {}", version, env)
+            }
+            Error::InvalidModule(ref errors) => write!(
+                f,
+                "invalid module: {}",
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Error::BuildError {
+                location,
+                name,
+                ref args,
+                ref path,
+                ref source,
+            } => {
+                write!(
+                    f,
+                    "compilation failed at {} node with id {} in {:?}, with arguments {:?}",
+                    name,
+                    location.node.index(),
+                    location.graph,
+                    args
+                )?;
+
+                for call in path.iter().rev() {
+                    write!(
+                        f,
+                        ", called from node {} in {:?}",
+                        call.node.index(),
+                        call.graph
+                    )?;
+                }
+
+                write!(f, ": {}", source)
+            }
+            Error::ConflictingDeclarations(first, second) => write!(
+                f,
+                "nodes {} and {} declare different variables for the same slot",
+                first.index(),
+                second.index()
+            ),
+            Error::PoisonedNode(node) => write!(
+                f,
+                "node {} depends on a node that failed to build",
+                node.index()
+            ),
+            Error::MultipleErrors(ref errors) => {
+                write!(f, "{} nodes failed to build", errors.len())
+            }
         }
+    }
+}
 
-        /// Used to wrap another error with metadata about its origin node
-        BuildError(node: &'static str, id: usize) {
-            description("build error")
-            display("compilation failed at {} node with id {}", node, id)
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::BuildError { ref source, .. } => Some(&**source),
+            _ => None,
         }
     }
 }
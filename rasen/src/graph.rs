@@ -1,22 +1,50 @@
 //! Graph building helpers
 
-use std::ops::Index;
+use std::{
+    collections::HashMap,
+    mem,
+    ops::{Index, IndexMut},
+};
 
-use petgraph::{algo, graph::NodeIndex, visit::EdgeRef, Graph as PetGraph, Incoming, Outgoing};
+use petgraph::{
+    algo,
+    graph::{EdgeIndex, NodeIndex},
+    stable_graph::StableGraph,
+    visit::EdgeRef,
+    Incoming, Outgoing,
+};
 
 use super::node::*;
+use builder::{self, Settings};
+use errors::*;
+use types::TypeName;
 
-/// Convenience wrapper for [`petgraph::Graph`](petgraph::graph::Graph)
-#[derive(Debug)]
+/// A connection from the result of a node to an argument of another node
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Edge {
+    /// The index of the edge in the graph
+    pub index: EdgeIndex<u32>,
+    /// The node producing the value
+    pub from: NodeIndex<u32>,
+    /// The node receiving the value
+    pub to: NodeIndex<u32>,
+    /// The argument of the receiving node the value is passed as
+    pub port: u32,
+}
+
+/// Convenience wrapper for [`petgraph::StableGraph`](petgraph::stable_graph::StableGraph)
+///
+/// The indices of the nodes and edges remain valid when other nodes or edges are removed
+#[derive(Clone, Debug)]
 pub struct Graph {
-    graph: PetGraph<Node, u32>,
+    graph: StableGraph<Node, u32>,
 }
 
 impl Default for Graph {
     /// Create a new empty graph
     fn default() -> Self {
         Self {
-            graph: PetGraph::new(),
+            graph: StableGraph::new(),
         }
     }
 }
@@ -28,34 +56,180 @@ impl Graph {
     }
 
     /// Add an edge between two nodes in the graph, infering the result type of the origin node
-    pub fn add_edge(&mut self, from: NodeIndex<u32>, to: NodeIndex<u32>, index: u32) {
-        self.graph.add_edge(from, to, index);
+    pub fn add_edge(
+        &mut self,
+        from: NodeIndex<u32>,
+        to: NodeIndex<u32>,
+        index: u32,
+    ) -> EdgeIndex<u32> {
+        self.graph.add_edge(from, to, index)
+    }
+
+    /// Remove a node and all its edges from the graph, returning the node
+    /// if it existed
+    pub fn remove_node(&mut self, index: NodeIndex<u32>) -> Option<Node> {
+        self.graph.remove_node(index)
+    }
+
+    /// Remove an edge from the graph, returning the port it was connected to
+    /// if it existed
+    pub fn remove_edge(&mut self, index: EdgeIndex<u32>) -> Option<u32> {
+        self.graph.remove_edge(index)
+    }
+
+    /// Replace a node while keeping its edges, returning the previous node
+    /// if it existed
+    pub fn replace_node(&mut self, index: NodeIndex<u32>, node: Node) -> Option<Node> {
+        self.graph
+            .node_weight_mut(index)
+            .map(|weight| mem::replace(weight, node))
+    }
+
+    /// Check if a node exists in the graph
+    pub fn contains_node(&self, index: NodeIndex<u32>) -> bool {
+        self.graph.contains_node(index)
+    }
+
+    /// Get the number of nodes in the graph
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    /// Get the number of edges in the graph
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    /// Check if the graph doesn't contain any node
+    pub fn is_empty(&self) -> bool {
+        self.graph.node_count() == 0
+    }
+
+    /// Iterate over all the nodes of the graph
+    pub fn nodes<'a>(&'a self) -> impl Iterator<Item = (NodeIndex<u32>, &'a Node)> + 'a {
+        self.graph
+            .node_indices()
+            .map(move |index| (index, &self.graph[index]))
+    }
+
+    /// Iterate over all the edges of the graph
+    pub fn edges<'a>(&'a self) -> impl Iterator<Item = Edge> + 'a {
+        self.graph
+            .node_indices()
+            .flat_map(move |index| self.outputs_of(index))
+    }
+
+    /// List the edges going into a node, sorted by port
+    pub fn inputs_of(&self, index: NodeIndex<u32>) -> Vec<Edge> {
+        let mut res: Vec<_> = {
+            self.graph
+                .edges_directed(index, Incoming)
+                .map(|edge| Edge {
+                    index: edge.id(),
+                    from: edge.source(),
+                    to: edge.target(),
+                    port: *edge.weight(),
+                })
+                .collect()
+        };
+
+        res.sort_by_key(|edge| edge.port);
+        res
+    }
+
+    /// Iterate over the edges going out of a node
+    pub fn outputs_of<'a>(&'a self, index: NodeIndex<u32>) -> impl Iterator<Item = Edge> + 'a {
+        self.graph.edges_directed(index, Outgoing).map(|edge| Edge {
+            index: edge.id(),
+            from: edge.source(),
+            to: edge.target(),
+            port: *edge.weight(),
+        })
+    }
+
+    /// List the ports of a node receiving several edges
+    pub fn duplicate_ports(&self, index: NodeIndex<u32>) -> Vec<u32> {
+        let inputs = self.inputs_of(index);
+        let mut res: Vec<_> = {
+            inputs
+                .windows(2)
+                .filter(|pair| pair[0].port == pair[1].port)
+                .map(|pair| pair[0].port)
+                .collect()
+        };
+
+        res.dedup();
+        res
+    }
+
+    /// Infer the type of the result of every node, using the settings of a fragment shader
+    ///
+    /// Each node is mapped to its type, or to the error building it would raise.
+    /// Nodes with an argument that cannot be typed fail with `UntypedArgument`,
+    /// and the nodes closing a cycle with `CyclicGraph`
+    ///
+    /// The nodes are run through a builder discarding the instructions they emit,
+    /// so the types follow the rules of `build_program` exactly, but inferring the
+    /// types costs about as much as building the graph
+    pub fn infer_types(&self) -> HashMap<NodeIndex<u32>, Result<&'static TypeName>> {
+        self.infer_types_with(Settings::default())
+    }
+
+    /// Infer the type of the result of every node, using the settings of the stage
+    /// the graph will be built for
+    ///
+    /// `Call` and `Loop` nodes cannot be typed, since the graph doesn't hold the functions
+    pub fn infer_types_with(
+        &self,
+        settings: Settings,
+    ) -> HashMap<NodeIndex<u32>, Result<&'static TypeName>> {
+        builder::infer_types(self, settings)
     }
 
     pub(crate) fn has_cycle(&self) -> bool {
         algo::is_cyclic_directed(&self.graph)
     }
 
-    /// List all the outputs of the graph
-    pub(crate) fn outputs<'a>(&'a self) -> impl Iterator<Item = NodeIndex<u32>> + 'a {
-        self.graph
-            .externals(Outgoing)
-            .filter(move |index| match self.graph.node_weight(*index) {
-                Some(&Node::Output(_, _, _)) | Some(&Node::Return) => true,
-                _ => false,
-            })
+    /// Sort the nodes of the graph so that each node comes after its arguments
+    pub(crate) fn toposort(&self) -> Result<Vec<NodeIndex<u32>>> {
+        algo::toposort(&self.graph, None).map_err(|_| Error::CyclicGraph)
     }
 
-    /// List the incoming connections for a node
-    pub(crate) fn arguments<'a>(
-        &'a self,
-        index: NodeIndex<u32>,
-    ) -> impl Iterator<Item = NodeIndex<u32>> + 'a {
-        let mut vec: Vec<_> = self.graph.edges_directed(index, Incoming).collect();
+    /// List all the outputs of the graph, including the side-effecting nodes it ends with
+    pub(crate) fn outputs<'a>(&'a self) -> impl Iterator<Item = NodeIndex<u32>> + 'a {
+        self.graph.node_indices().filter(move |index| {
+            let is_external = {
+                self.graph
+                    .neighbors_directed(*index, Outgoing)
+                    .next()
+                    .is_none()
+            };
 
-        vec.sort_by_key(|e| e.weight());
+            is_external
+                && match self.graph[*index] {
+                    Node::Output(..)
+                    | Node::ArrayedOutput(..)
+                    | Node::Return
+                    | Node::EmitVertex
+                    | Node::EndPrimitive
+                    | Node::Barrier => true,
+                    _ => false,
+                }
+        })
+    }
 
-        vec.into_iter().map(|e| e.source())
+    /// List the incoming connections for a node, failing if several edges
+    /// are connected to the same port
+    pub(crate) fn arguments(&self, index: NodeIndex<u32>) -> Result<Vec<NodeIndex<u32>>> {
+        if let Some(&port) = self.duplicate_ports(index).first() {
+            bail!(Error::DuplicateEdge(index, port));
+        }
+
+        Ok(self
+            .inputs_of(index)
+            .into_iter()
+            .map(|edge| edge.from)
+            .collect())
     }
 }
 
@@ -67,3 +241,10 @@ impl Index<NodeIndex<u32>> for Graph {
         &self.graph[index]
     }
 }
+
+impl IndexMut<NodeIndex<u32>> for Graph {
+    /// Get a mutable reference to a node of the graph
+    fn index_mut(&mut self, index: NodeIndex<u32>) -> &mut Node {
+        &mut self.graph[index]
+    }
+}
@@ -0,0 +1,218 @@
+//! Software implementation of the IEEE 754 half-precision floating-point type
+
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
+};
+
+/// A 16-bit floating-point number, stored as its raw bit pattern
+///
+/// Arithmetic is performed in single precision and rounded back to half precision,
+/// which is enough to run shaders using `Half` values on the CPU
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Default)]
+pub struct f16(u16);
+
+impl f16 {
+    pub const ZERO: Self = f16(0x0000);
+    pub const ONE: Self = f16(0x3c00);
+
+    #[inline]
+    pub fn from_bits(bits: u16) -> Self {
+        f16(bits)
+    }
+
+    #[inline]
+    pub fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Convert a single-precision value to half precision, rounding to the nearest even value
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    pub fn from_f32(value: f32) -> Self {
+        let bits = value.to_bits();
+        let sign = ((bits >> 16) & 0x8000) as u16;
+        let exponent = ((bits >> 23) & 0xff) as i32;
+        let mantissa = bits & 0x7f_ffff;
+
+        // Infinity and NaN
+        if exponent == 0xff {
+            let nan = if mantissa == 0 { 0 } else { 0x200 };
+            return f16(sign | 0x7c00 | nan);
+        }
+
+        let exponent = exponent - 127 + 15;
+        if exponent >= 0x1f {
+            return f16(sign | 0x7c00);
+        }
+
+        let (mantissa, shift) = if exponent <= 0 {
+            // The value is too small to be represented, flush it to zero
+            if exponent < -10 {
+                return f16(sign);
+            }
+
+            // Subnormal values keep their implicit leading bit in the mantissa
+            (mantissa | 0x80_0000, (14 - exponent) as u32)
+        } else {
+            (mantissa, 13)
+        };
+
+        let base = if exponent <= 0 {
+            0
+        } else {
+            (exponent as u32) << 10
+        };
+
+        let result = base | (mantissa >> shift);
+        let round_bit = 1 << (shift - 1);
+        let round_up = (mantissa & round_bit) != 0
+            && ((mantissa & (round_bit - 1)) != 0 || (result & 1) != 0);
+
+        // A carry out of the mantissa correctly bumps the exponent (up to infinity)
+        let result = if round_up { result + 1 } else { result };
+        f16(sign | result as u16)
+    }
+
+    /// Convert this value to single precision (this conversion is lossless)
+    pub fn to_f32(self) -> f32 {
+        let sign = u32::from(self.0 & 0x8000) << 16;
+        let exponent = u32::from((self.0 >> 10) & 0x1f);
+        let mantissa = u32::from(self.0 & 0x3ff);
+
+        let bits = match (exponent, mantissa) {
+            (0, 0) => sign,
+            (0, _) => {
+                // Subnormal values get normalized in single precision
+                let mut exponent = 127 - 15 + 1;
+                let mut mantissa = mantissa;
+                while mantissa & 0x400 == 0 {
+                    mantissa <<= 1;
+                    exponent -= 1;
+                }
+
+                sign | (exponent << 23) | ((mantissa & 0x3ff) << 13)
+            }
+            (0x1f, _) => sign | 0x7f80_0000 | (mantissa << 13),
+            _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+        };
+
+        f32::from_bits(bits)
+    }
+
+    #[inline]
+    pub fn is_nan(self) -> bool {
+        self.0 & 0x7c00 == 0x7c00 && self.0 & 0x3ff != 0
+    }
+
+    #[inline]
+    pub fn powi(self, n: i32) -> Self {
+        f16::from_f32(self.to_f32().powi(n))
+    }
+}
+
+macro_rules! impl_unary_fn {
+    ( $( $name:ident ),* ) => {
+        impl f16 {
+            $(
+                #[inline]
+                pub fn $name(self) -> Self {
+                    f16::from_f32(self.to_f32().$name())
+                }
+            )*
+        }
+    };
+}
+
+impl_unary_fn!(sqrt, floor, ceil, round, sin, cos, tan, ln, abs);
+
+macro_rules! impl_binary_fn {
+    ( $( $name:ident ),* ) => {
+        impl f16 {
+            $(
+                #[inline]
+                pub fn $name(self, rhs: Self) -> Self {
+                    f16::from_f32(self.to_f32().$name(rhs.to_f32()))
+                }
+            )*
+        }
+    };
+}
+
+impl_binary_fn!(powf, min, max);
+
+macro_rules! impl_binary_op {
+    ( $( $trait:ident, $name:ident, $op:tt );* ) => {
+        $(
+            impl $trait for f16 {
+                type Output = Self;
+
+                #[inline]
+                fn $name(self, rhs: Self) -> Self {
+                    f16::from_f32(self.to_f32() $op rhs.to_f32())
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_op!(
+    Add, add, +;
+    Sub, sub, -;
+    Mul, mul, *;
+    Div, div, /;
+    Rem, rem, %
+);
+
+impl Neg for f16 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        f16(self.0 ^ 0x8000)
+    }
+}
+
+impl PartialEq for f16 {
+    #[allow(clippy::float_cmp)]
+    fn eq(&self, other: &Self) -> bool {
+        self.to_f32() == other.to_f32()
+    }
+}
+
+impl PartialOrd for f16 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.to_f32().partial_cmp(&other.to_f32())
+    }
+}
+
+impl From<f32> for f16 {
+    #[inline]
+    fn from(value: f32) -> Self {
+        f16::from_f32(value)
+    }
+}
+
+impl From<f16> for f32 {
+    #[inline]
+    fn from(value: f16) -> Self {
+        value.to_f32()
+    }
+}
+
+impl fmt::Debug for f16 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_f32(), f)
+    }
+}
+
+impl fmt::Display for f16 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_f32(), f)
+    }
+}
@@ -0,0 +1,256 @@
+//! Check that the outputs of a stage match the inputs of the next one in the pipeline,
+//! without having to build the modules
+
+use std::{collections::BTreeMap, fmt};
+
+use builder::{EntryPointReflection, InterfaceVariable};
+use graph::Graph;
+use module::Module;
+use node::{Attributes, Node, VariableName};
+use types::TypeName;
+
+/// A user-defined variable passed from a stage to the next one
+#[derive(Clone, Debug)]
+struct Variable {
+    type_name: &'static TypeName,
+    name: Option<String>,
+    /// The attributes of the variable, unknown if it was collected from a reflection
+    attributes: Option<Attributes>,
+}
+
+/// Get the decorations affecting how a variable is interpolated
+fn interpolation(attributes: &Attributes) -> (bool, bool, bool, bool, bool) {
+    (
+        attributes.flat,
+        attributes.no_perspective,
+        attributes.centroid,
+        attributes.sample,
+        attributes.patch,
+    )
+}
+
+/// Slot of a variable in the interface, as a location and a component
+type Slot = (u32, u32);
+
+/// The user-defined inputs and outputs of a stage, builtins are ignored
+#[derive(Clone, Debug, Default)]
+pub struct StageInterface {
+    inputs: BTreeMap<Slot, Variable>,
+    outputs: BTreeMap<Slot, Variable>,
+}
+
+impl StageInterface {
+    fn add_graph(&mut self, graph: &Graph) {
+        for (_, node) in graph.nodes() {
+            let (list, location, type_name, name, attributes) = match *node {
+                Node::Input(location, type_name, ref name, attributes)
+                | Node::ArrayedInput(location, type_name, ref name, attributes) => {
+                    (&mut self.inputs, location, type_name, name, attributes)
+                }
+                Node::Output(location, type_name, ref name, attributes)
+                | Node::ArrayedOutput(location, type_name, ref name, attributes) => {
+                    (&mut self.outputs, location, type_name, name, attributes)
+                }
+                _ => continue,
+            };
+
+            let name = match *name {
+                VariableName::BuiltIn(_) => continue,
+                VariableName::Named(ref name) => Some(name.clone()),
+                VariableName::None => None,
+            };
+
+            let slot = (location, attributes.component.unwrap_or(0));
+            list.entry(slot).or_insert(Variable {
+                type_name,
+                name,
+                attributes: Some(attributes),
+            });
+        }
+    }
+
+    fn add_reflection(list: &mut BTreeMap<Slot, Variable>, variables: &[InterfaceVariable]) {
+        for variable in variables {
+            if let Some(location) = variable.location {
+                list.entry((location, 0)).or_insert(Variable {
+                    type_name: variable.type_name,
+                    name: variable.name.clone(),
+                    attributes: None,
+                });
+            }
+        }
+    }
+}
+
+impl<'a> From<&'a Graph> for StageInterface {
+    fn from(graph: &'a Graph) -> Self {
+        let mut res = Self::default();
+        res.add_graph(graph);
+        res
+    }
+}
+
+impl<'a> From<&'a Module> for StageInterface {
+    /// Collect the interface of the main graph of the module, and of the functions it calls
+    fn from(module: &'a Module) -> Self {
+        let mut res = Self::from(&module.main);
+        for function in &module.functions {
+            res.add_graph(function);
+        }
+
+        res
+    }
+}
+
+impl<'a> From<&'a EntryPointReflection> for StageInterface {
+    /// Collect the interface of a built entry point
+    ///
+    /// The reflection doesn't hold interpolation decorations, they are only checked
+    /// if both stages are described by their graph
+    fn from(entry: &'a EntryPointReflection) -> Self {
+        let mut res = Self::default();
+        Self::add_reflection(&mut res.inputs, &entry.inputs);
+        Self::add_reflection(&mut res.outputs, &entry.outputs);
+        res
+    }
+}
+
+/// An incompatibility between the outputs of a stage and the inputs of the next one
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    /// An input isn't written by the previous stage
+    MissingOutput {
+        location: u32,
+        component: u32,
+        name: Option<String>,
+    },
+    /// An input is written by the previous stage at a different location
+    Location {
+        name: String,
+        output: u32,
+        input: u32,
+    },
+    /// An output and an input sharing a location have different types
+    Type {
+        location: u32,
+        component: u32,
+        output: &'static TypeName,
+        input: &'static TypeName,
+    },
+    /// An output and an input sharing a location have different interpolation decorations
+    Interpolation {
+        location: u32,
+        component: u32,
+        output: Attributes,
+        input: Attributes,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Mismatch::MissingOutput {
+                location,
+                component,
+                ref name,
+            } => write!(
+                f,
+                "input {:?} at location {} component {} is not written by the previous stage",
+                name, location, component
+            ),
+            Mismatch::Location {
+                ref name,
+                output,
+                input,
+            } => write!(
+                f,
+                "{:?} is written at location {} but read at location {}",
+                name, output, input
+            ),
+            Mismatch::Type {
+                location,
+                component,
+                output,
+                input,
+            } => write!(
+                f,
+                "location {} component {} is written as {:?} but read as {:?}",
+                location, component, output, input
+            ),
+            Mismatch::Interpolation {
+                location,
+                component,
+                ..
+            } => write!(
+                f,
+                "location {} component {} has different interpolation decorations",
+                location, component
+            ),
+        }
+    }
+}
+
+/// Check the inputs of a stage are all written by the previous stage of the pipeline
+/// with a matching type and interpolation, returning all the incompatibilities found
+///
+/// The stages can be described by their `Graph`, their `Module`, or the reflection
+/// of an entry point that was already built
+pub fn check_interface<P, C>(producer: P, consumer: C) -> Vec<Mismatch>
+where
+    P: Into<StageInterface>,
+    C: Into<StageInterface>,
+{
+    let producer = producer.into();
+    let consumer = consumer.into();
+
+    let mut res = Vec::new();
+
+    for (&(location, component), input) in &consumer.inputs {
+        let output = if let Some(output) = producer.outputs.get(&(location, component)) {
+            output
+        } else {
+            // Look for an output with the same name, in case it was moved
+            let moved = producer
+                .outputs
+                .iter()
+                .find(|&(_, output)| output.name.is_some() && output.name == input.name);
+
+            res.push(match (moved, &input.name) {
+                (Some((&(output, _), _)), &Some(ref name)) => Mismatch::Location {
+                    name: name.clone(),
+                    output,
+                    input: location,
+                },
+                _ => Mismatch::MissingOutput {
+                    location,
+                    component,
+                    name: input.name.clone(),
+                },
+            });
+
+            continue;
+        };
+
+        if output.type_name != input.type_name {
+            res.push(Mismatch::Type {
+                location,
+                component,
+                output: output.type_name,
+                input: input.type_name,
+            });
+        }
+
+        if let (Some(output), Some(input)) = (output.attributes, input.attributes) {
+            if interpolation(&output) != interpolation(&input) {
+                res.push(Mismatch::Interpolation {
+                    location,
+                    component,
+                    output,
+                    input,
+                });
+            }
+        }
+    }
+
+    res
+}
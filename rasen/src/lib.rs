@@ -10,7 +10,7 @@
 //!     let mut graph = Graph::default();
 //!
 //!     // A vec3 input at location 0
-//!     let normal = graph.add_node(Node::Input(0, TypeName::VEC3, VariableName::Named(String::from("a_normal"))));
+//!     let normal = graph.add_node(Node::Input(0, TypeName::VEC3, VariableName::Named(String::from("a_normal")), Attributes::default()));
 //!
 //!     // Some ambient light constants
 //!     let min_light = graph.add_node(Node::Constant(TypedValue::Float(0.1)));
@@ -27,7 +27,7 @@
 //!     let multiply = graph.add_node(Node::Multiply);
 //!
 //!     // And a vec4 output at location 0
-//!     let color = graph.add_node(Node::Output(0, TypeName::VEC4, VariableName::Named(String::from("o_color"))));
+//!     let color = graph.add_node(Node::Output(0, TypeName::VEC4, VariableName::Named(String::from("o_color")), Attributes::default()));
 //!
 //!     // Normalize the normal
 //!     graph.add_edge(normal, normalize, 0);
@@ -63,16 +63,25 @@
 extern crate petgraph;
 extern crate rspirv;
 extern crate spirv_headers;
-#[macro_use]
-extern crate error_chain;
 extern crate fnv;
+#[cfg(feature = "serialize")]
+extern crate serde;
+#[cfg(feature = "serialize")]
+#[macro_use]
+extern crate serde_derive;
+
+#[macro_use]
+pub mod errors;
 
 mod builder;
+mod half;
 mod node;
 mod operations;
 mod types;
 
-pub mod errors;
 pub mod graph;
+pub mod interface;
 pub mod module;
+pub mod passes;
 pub mod prelude;
+pub mod validate;
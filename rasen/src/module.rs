@@ -1,18 +1,64 @@
 //! A Module is the advanced entry point for the Rasen compiler. It holds the graph for the main function,
-//! as well as the subgraphs for all the user-defined functions
+//! as well as the subgraphs for all the user-defined functions and additional entry points
 
 use graph::Graph;
-use std::ops::{Index, IndexMut};
+use spirv_headers::ExecutionModel as ShaderType;
+use std::{
+    iter,
+    ops::{Index, IndexMut},
+};
 
 /// An opaque pointer struct to a function
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FunctionRef(pub(crate) usize);
 
-/// A container for complex shader programs with multiple functions
-#[derive(Debug, Default)]
+/// An opaque pointer struct to an entry point
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EntryPointRef(pub(crate) usize);
+
+/// Identifies one of the graphs of a module
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GraphRef {
+    /// The main graph of the module, or the graph passed to `ModuleBuilder::from_graph`
+    Main,
+    /// The graph of an additional entry point
+    EntryPoint(EntryPointRef),
+    /// The graph of a function
+    Function(FunctionRef),
+}
+
+/// A hint on whether the calls to a function should be inlined
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InlineHint {
+    /// Let the inlining pass decide from the size and uses of the function
+    Auto,
+    /// Inline all the calls, emitted with the `Inline` function control
+    Always,
+    /// Never inline the calls, emitted with the `DontInline` function control
+    Never,
+}
+
+impl Default for InlineHint {
+    fn default() -> Self {
+        InlineHint::Auto
+    }
+}
+
+/// A named shader stage of a module
+#[derive(Clone, Debug)]
+pub(crate) struct EntryPoint {
+    pub(crate) name: String,
+    pub(crate) mod_type: ShaderType,
+    pub(crate) graph: Graph,
+}
+
+/// A container for complex shader programs with multiple functions and entry points
+#[derive(Clone, Debug, Default)]
 pub struct Module {
     pub main: Graph,
     pub(crate) functions: Vec<Graph>,
+    pub(crate) inline_hints: Vec<InlineHint>,
+    pub(crate) entry_points: Vec<EntryPoint>,
 }
 
 impl Module {
@@ -20,6 +66,7 @@ impl Module {
     pub fn add_function(&mut self) -> FunctionRef {
         let index = self.functions.len();
         self.functions.push(Graph::default());
+        self.inline_hints.push(InlineHint::default());
         FunctionRef(index)
     }
 
@@ -27,6 +74,52 @@ impl Module {
     pub fn function(&mut self, index: FunctionRef) -> Option<&mut Graph> {
         self.functions.get_mut(index.0)
     }
+
+    /// Set whether the calls to a function should be inlined,
+    /// returning `false` if the module has no such function
+    pub fn set_inline_hint(&mut self, index: FunctionRef, hint: InlineHint) -> bool {
+        match self.inline_hints.get_mut(index.0) {
+            Some(slot) => {
+                *slot = hint;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get whether the calls to a function should be inlined
+    pub fn inline_hint(&self, index: FunctionRef) -> InlineHint {
+        self.inline_hints.get(index.0).cloned().unwrap_or_default()
+    }
+
+    /// Iterate over all the graphs of the module: the main graph, the entry points
+    /// and the functions
+    pub fn graphs_mut<'a>(&'a mut self) -> impl Iterator<Item = &'a mut Graph> + 'a {
+        iter::once(&mut self.main)
+            .chain(self.entry_points.iter_mut().map(|entry| &mut entry.graph))
+            .chain(self.functions.iter_mut())
+    }
+
+    /// Add an entry point to the module, with a name and the stage it's executed in
+    ///
+    /// The main graph is only built if it's not empty when the module has other entry points
+    pub fn add_entry_point(&mut self, name: &str, mod_type: ShaderType) -> EntryPointRef {
+        let index = self.entry_points.len();
+        self.entry_points.push(EntryPoint {
+            name: String::from(name),
+            mod_type,
+            graph: Graph::default(),
+        });
+
+        EntryPointRef(index)
+    }
+
+    /// Get a reference to an entry point's graph from its index
+    pub fn entry_point(&mut self, index: EntryPointRef) -> Option<&mut Graph> {
+        self.entry_points
+            .get_mut(index.0)
+            .map(|entry| &mut entry.graph)
+    }
 }
 
 impl Index<FunctionRef> for Module {
@@ -42,3 +135,17 @@ impl IndexMut<FunctionRef> for Module {
         &mut self.functions[index.0]
     }
 }
+
+impl Index<EntryPointRef> for Module {
+    type Output = Graph;
+
+    fn index(&self, index: EntryPointRef) -> &Graph {
+        &self.entry_points[index.0].graph
+    }
+}
+
+impl IndexMut<EntryPointRef> for Module {
+    fn index_mut(&mut self, index: EntryPointRef) -> &mut Graph {
+        &mut self.entry_points[index.0].graph
+    }
+}
@@ -4,7 +4,9 @@ use rspirv::mr::{Instruction, Operand};
 use spirv_headers::*;
 use std::{fmt, iter};
 
-use builder::Builder;
+use builder::{
+    Builder, Declaration, InterfaceVariable, SamplerVariable, UniformVariable, VariableKey,
+};
 use errors::*;
 use module::FunctionRef;
 use operations;
@@ -25,7 +27,7 @@ impl Node {
         macro_rules! impl_glsl_call {
             ( $function:ident, $argc:expr ) => {{
                 if args.len() != $argc {
-                    bail!(ErrorKind::WrongArgumentsCount(args.len(), $argc));
+                    bail!(Error::WrongArgumentsCount(args.len(), $argc));
                 }
 
                 let ext_id = module.import_set("GLSL.std.450");
@@ -54,52 +56,58 @@ impl Node {
         }
 
         match *self {
-            Node::Output(location, attr_type, ref name) => {
+            Node::Output(location, attr_type, ref name, ref attributes) => {
                 if args.len() != 1 {
-                    bail!(ErrorKind::WrongArgumentsCount(args.len(), 1));
+                    bail!(Error::WrongArgumentsCount(args.len(), 1));
                 }
 
                 let (arg_type, arg_value) = args[0];
                 if arg_type != attr_type {
-                    bail!(ErrorKind::BadArguments(Box::new([arg_type])));
+                    bail!(Error::TypeMismatch {
+                        port: 0,
+                        expected: attr_type,
+                        actual: arg_type,
+                    });
                 }
 
-                let type_id = module.register_type(attr_type);
-                let ptr_type = module.get_id();
-
-                module.push_declaration(Instruction::new(
-                    Op::TypePointer,
-                    None,
-                    Some(ptr_type),
-                    vec![
-                        Operand::StorageClass(StorageClass::Output),
-                        Operand::IdRef(type_id),
-                    ],
-                ));
-
-                let var_id = module.get_id();
-
-                module.push_output(var_id);
+                let model = module.get_settings().mod_type;
+                name.check_type(attr_type)?;
+                attributes.check(model, StorageClass::Output, attr_type)?;
+                check_arrayed(model, StorageClass::Output, name, attributes, false)?;
+
+                if let VariableName::BuiltIn(built_in) = *name {
+                    match built_in {
+                        BuiltIn::TessLevelOuter | BuiltIn::TessLevelInner => {
+                            return store_tess_level(
+                                module, name, attr_type, arg_value, attributes,
+                            );
+                        }
+                        BuiltIn::Position | BuiltIn::PointSize | BuiltIn::ClipDistance
+                            if module.get_settings().per_vertex_block =>
+                        {
+                            return store_per_vertex(module, built_in, attr_type, arg_value);
+                        }
+                        BuiltIn::ClipDistance => {
+                            return store_clip_distance(module, name, attr_type, arg_value);
+                        }
+                        BuiltIn::FragDepth => {
+                            module.push_execution_mode(ExecutionMode::DepthReplacing);
+                        }
+                        _ => {}
+                    }
+                }
 
-                module.push_declaration(Instruction::new(
-                    Op::Variable,
-                    Some(ptr_type),
-                    Some(var_id),
-                    vec![Operand::StorageClass(StorageClass::Output)],
-                ));
+                let type_id = module.register_type(attr_type);
+                declare_16bit_storage(module, attr_type);
 
-                module.push_annotation(Instruction::new(
-                    Op::Decorate,
-                    None,
+                let var_id = declare_interface(
+                    module,
+                    StorageClass::Output,
+                    (location, attr_type, type_id),
                     None,
-                    vec![
-                        Operand::IdRef(var_id),
-                        Operand::Decoration(Decoration::Location),
-                        Operand::LiteralInt32(location),
-                    ],
-                ));
-
-                name.decorate_variable(module, var_id);
+                    name,
+                    attributes,
+                )?;
 
                 module.push_instruction(Instruction::new(
                     Op::Store,
@@ -115,53 +123,96 @@ impl Node {
                 Ok((attr_type, var_id))
             }
 
-            Node::Input(location, attr_type, ref name) => {
-                let type_id = module.register_type(attr_type);
+            Node::Input(location, attr_type, ref name, ref attributes) => {
+                let model = module.get_settings().mod_type;
+                name.check_type(attr_type)?;
+                attributes.check(model, StorageClass::Input, attr_type)?;
+                check_arrayed(model, StorageClass::Input, name, attributes, false)?;
+
+                match *name {
+                    VariableName::BuiltIn(BuiltIn::TessLevelOuter)
+                    | VariableName::BuiltIn(BuiltIn::TessLevelInner) => {
+                        return load_tess_level(module, name, attr_type, attributes);
+                    }
+                    VariableName::BuiltIn(BuiltIn::ClipDistance) => {
+                        return load_clip_distance(module, name, attr_type);
+                    }
+                    _ => {}
+                }
 
-                let ptr_type = module.get_id();
+                let type_id = module.register_type(attr_type);
+                declare_16bit_storage(module, attr_type);
 
-                module.push_declaration(Instruction::new(
-                    Op::TypePointer,
+                let var_id = declare_interface(
+                    module,
+                    StorageClass::Input,
+                    (location, attr_type, type_id),
                     None,
-                    Some(ptr_type),
+                    name,
+                    attributes,
+                )?;
+
+                let res_id = module.get_id();
+
+                module.push_instruction(Instruction::new(
+                    Op::Load,
+                    Some(type_id),
+                    Some(res_id),
                     vec![
-                        Operand::StorageClass(StorageClass::Input),
-                        Operand::IdRef(type_id),
+                        Operand::IdRef(var_id),
+                        Operand::MemoryAccess(MemoryAccess::empty()),
                     ],
                 ));
 
-                let var_id = module.get_id();
+                Ok((attr_type, res_id))
+            }
 
-                module.push_input(var_id);
+            Node::ArrayedInput(location, attr_type, ref name, ref attributes) => {
+                if args.len() != 1 {
+                    bail!(Error::WrongArgumentsCount(args.len(), 1));
+                }
 
-                module.push_declaration(Instruction::new(
-                    Op::Variable,
-                    Some(ptr_type),
-                    Some(var_id),
-                    vec![Operand::StorageClass(StorageClass::Input)],
-                ));
+                let (index_type, index_value) = args[0];
+                if !index_type.is_integer() {
+                    bail!(Error::BadArguments {
+                        args: Box::new([index_type]),
+                        port: Some(0),
+                    });
+                }
 
-                module.push_annotation(Instruction::new(
-                    Op::Decorate,
-                    None,
-                    None,
-                    vec![
-                        Operand::IdRef(var_id),
-                        Operand::Decoration(Decoration::Location),
-                        Operand::LiteralInt32(location),
-                    ],
-                ));
+                let len = if let Some(len) = module.get_settings().input_vertices() {
+                    len
+                } else {
+                    bail!(Error::UnsupportedOperation("ArrayedInput"))
+                };
 
-                name.decorate_variable(module, var_id);
+                let model = module.get_settings().mod_type;
+                name.check_type(attr_type)?;
+                attributes.check(model, StorageClass::Input, attr_type)?;
+                check_arrayed(model, StorageClass::Input, name, attributes, true)?;
 
-                let res_id = module.get_id();
+                let type_id = module.register_type(attr_type);
+                declare_16bit_storage(module, attr_type);
+
+                let var_id = declare_array(
+                    module,
+                    StorageClass::Input,
+                    (location, attr_type, type_id),
+                    len,
+                    name,
+                    attributes,
+                )?;
 
+                let ptr_type = declare_pointer(module, StorageClass::Input, type_id);
+                let ptr_id = access_chain(module, ptr_type, var_id, index_value);
+
+                let res_id = module.get_id();
                 module.push_instruction(Instruction::new(
                     Op::Load,
                     Some(type_id),
                     Some(res_id),
                     vec![
-                        Operand::IdRef(var_id),
+                        Operand::IdRef(ptr_id),
                         Operand::MemoryAccess(MemoryAccess::empty()),
                     ],
                 ));
@@ -169,6 +220,62 @@ impl Node {
                 Ok((attr_type, res_id))
             }
 
+            Node::ArrayedOutput(location, attr_type, ref name, ref attributes) => {
+                if args.len() != 1 {
+                    bail!(Error::WrongArgumentsCount(args.len(), 1));
+                }
+
+                let (arg_type, arg_value) = args[0];
+                if arg_type != attr_type {
+                    bail!(Error::TypeMismatch {
+                        port: 0,
+                        expected: attr_type,
+                        actual: arg_type,
+                    });
+                }
+
+                let len = if let Some(len) = module.get_settings().output_vertices() {
+                    len
+                } else {
+                    bail!(Error::UnsupportedOperation("ArrayedOutput"))
+                };
+
+                let model = module.get_settings().mod_type;
+                name.check_type(attr_type)?;
+                attributes.check(model, StorageClass::Output, attr_type)?;
+                check_arrayed(model, StorageClass::Output, name, attributes, true)?;
+
+                let type_id = module.register_type(attr_type);
+                declare_16bit_storage(module, attr_type);
+
+                let var_id = declare_array(
+                    module,
+                    StorageClass::Output,
+                    (location, attr_type, type_id),
+                    len,
+                    name,
+                    attributes,
+                )?;
+
+                // Each invocation of a control shader may only write its own vertex
+                let index = load_invocation_id(module)?;
+                let ptr_type = declare_pointer(module, StorageClass::Output, type_id);
+                let ptr_id = access_chain(module, ptr_type, var_id, index);
+
+                module.push_instruction(Instruction::new(
+                    Op::Store,
+                    None,
+                    None,
+                    vec![
+                        Operand::IdRef(ptr_id),
+                        Operand::IdRef(arg_value),
+                        Operand::MemoryAccess(MemoryAccess::empty()),
+                    ],
+                ));
+
+                Ok((attr_type, var_id))
+            }
+
             Node::Uniform(location, attr_type, ref name) => {
                 let type_id = module.register_type(attr_type);
                 let ptr_type = module.register_type(attr_type.as_ptr(true));
@@ -195,6 +302,12 @@ impl Node {
 
                     name.decorate_variable(module, var_id);
 
+                    module.push_reflection(Declaration::Sampler(SamplerVariable {
+                        location,
+                        type_name: attr_type,
+                        name: name.reflect_name(),
+                    }));
+
                     let res_id = module.get_id();
                     module.push_instruction(Instruction::new(
                         Op::Load,
@@ -208,34 +321,14 @@ impl Node {
 
                     Ok((attr_type, res_id))
                 } else {
-                    let (struct_id, var_id) = module.register_uniform(location, attr_type);
-                    let index_id = module.register_constant(&TypedValue::Int(location as i32))?;
-
-                    name.decorate_member(module, struct_id, location);
-
-                    if let TypeName::Mat(_, vec) = attr_type {
-                        module.push_annotation(Instruction::new(
-                            Op::MemberDecorate,
-                            None,
-                            None,
-                            vec![
-                                Operand::IdRef(struct_id),
-                                Operand::LiteralInt32(location),
-                                Operand::Decoration(Decoration::MatrixStride),
-                                Operand::LiteralInt32(vec.size()),
-                            ],
-                        ));
-                        module.push_annotation(Instruction::new(
-                            Op::MemberDecorate,
-                            None,
-                            None,
-                            vec![
-                                Operand::IdRef(struct_id),
-                                Operand::LiteralInt32(location),
-                                Operand::Decoration(Decoration::ColMajor),
-                            ],
-                        ));
-                    }
+                    let (var_id, index_id) =
+                        module.register_uniform(location, attr_type, name.reflect_name());
+                    module.push_reflection(Declaration::Uniform(UniformVariable {
+                        location,
+                        type_name: attr_type,
+                        name: name.reflect_name(),
+                        offset: 0,
+                    }));
 
                     let chain_id = module.get_id();
                     module.push_instruction(Instruction::new(
@@ -271,19 +364,25 @@ impl Node {
                         if let Some(res) = module.get_function(index) {
                             res
                         } else {
-                            bail!(ErrorKind::MissingFunction(index))
+                            bail!(Error::MissingFunction(index))
                         };
 
                     (
                         result_type,
                         iter::once(Ok(Operand::IdRef(func_id)))
-                            .chain(args.into_iter().zip(args_type).map(|((val, id), arg)| {
-                                if val == *arg {
-                                    Ok(Operand::IdRef(id))
-                                } else {
-                                    bail!(ErrorKind::BadArguments(Box::new([val])));
-                                }
-                            }))
+                            .chain(args.into_iter().zip(args_type).enumerate().map(
+                                |(port, ((val, id), arg))| {
+                                    if val == *arg {
+                                        Ok(Operand::IdRef(id))
+                                    } else {
+                                        bail!(Error::TypeMismatch {
+                                            port,
+                                            expected: *arg,
+                                            actual: val,
+                                        });
+                                    }
+                                },
+                            ))
                             .collect::<Result<_>>()?,
                     )
                 };
@@ -321,7 +420,7 @@ impl Node {
 
             Node::Return => {
                 if args.len() != 1 {
-                    bail!(ErrorKind::WrongArgumentsCount(args.len(), 1));
+                    bail!(Error::WrongArgumentsCount(args.len(), 1));
                 }
 
                 let (arg_type, arg_value) = args[0];
@@ -347,14 +446,19 @@ impl Node {
                     match *output_type {
                         TypeName::Vec(size, data_type) => {
                             if args.len() != size as usize {
-                                bail!(ErrorKind::WrongArgumentsCount(args.len(), size as usize));
+                                bail!(Error::WrongArgumentsCount(args.len(), size as usize));
                             }
 
                             let res: Result<Vec<_>> = {
                                 args.into_iter()
-                                    .map(|(ty, val)| {
+                                    .enumerate()
+                                    .map(|(port, (ty, val))| {
                                         if ty != data_type {
-                                            bail!(ErrorKind::BadArguments(Box::new([ty])));
+                                            bail!(Error::TypeMismatch {
+                                                port,
+                                                expected: data_type,
+                                                actual: ty,
+                                            });
                                         }
 
                                         Ok(Operand::IdRef(val))
@@ -366,14 +470,19 @@ impl Node {
                         }
                         TypeName::Mat(size, vec_type) => {
                             if args.len() != size as usize {
-                                bail!(ErrorKind::WrongArgumentsCount(args.len(), size as usize));
+                                bail!(Error::WrongArgumentsCount(args.len(), size as usize));
                             }
 
                             let res: Result<Vec<_>> = {
                                 args.into_iter()
-                                    .map(|(ty, val)| {
+                                    .enumerate()
+                                    .map(|(port, (ty, val))| {
                                         if ty != vec_type {
-                                            bail!(ErrorKind::BadArguments(Box::new([ty])));
+                                            bail!(Error::TypeMismatch {
+                                                port,
+                                                expected: vec_type,
+                                                actual: ty,
+                                            });
                                         }
 
                                         Ok(Operand::IdRef(val))
@@ -383,7 +492,10 @@ impl Node {
 
                             res?
                         }
-                        _ => bail!(ErrorKind::BadArguments(Box::new([output_type]))),
+                        _ => bail!(Error::BadArguments {
+                            args: Box::new([output_type]),
+                            port: None
+                        }),
                     },
                 ));
 
@@ -392,14 +504,14 @@ impl Node {
 
             Node::Extract(index) => {
                 if args.len() != 1 {
-                    bail!(ErrorKind::WrongArgumentsCount(args.len(), 1));
+                    bail!(Error::WrongArgumentsCount(args.len(), 1));
                 }
 
                 let (arg_type, arg_value) = args[0];
                 match *arg_type {
                     TypeName::Vec(len, data_ty) => {
                         if index >= len {
-                            bail!(ErrorKind::IndexOutOfBound(index, len));
+                            bail!(Error::IndexOutOfBound(index, len));
                         }
 
                         let type_id = module.register_type(data_ty);
@@ -414,7 +526,10 @@ impl Node {
 
                         Ok((data_ty, res_id))
                     }
-                    _ => bail!(ErrorKind::BadArguments(Box::new([arg_type]))),
+                    _ => bail!(Error::BadArguments {
+                        args: Box::new([arg_type]),
+                        port: Some(0),
+                    }),
                 }
             }
 
@@ -462,20 +577,619 @@ impl Node {
             Node::GreaterEqual => operations::gte(module, &args),
             Node::Less => operations::lt(module, &args),
             Node::LessEqual => operations::lte(module, &args),
+
+            Node::EmitVertex => {
+                if module.get_settings().mod_type != ExecutionModel::Geometry {
+                    bail!(Error::UnsupportedOperation("EmitVertex"));
+                }
+
+                if args.is_empty() {
+                    bail!(Error::WrongArgumentsCount(args.len(), 1));
+                }
+
+                module.push_instruction(Instruction::new(Op::EmitVertex, None, None, Vec::new()));
+                Ok((TypeName::VOID, 0))
+            }
+
+            Node::EndPrimitive => {
+                if module.get_settings().mod_type != ExecutionModel::Geometry {
+                    bail!(Error::UnsupportedOperation("EndPrimitive"));
+                }
+
+                if args.len() != 1 {
+                    bail!(Error::WrongArgumentsCount(args.len(), 1));
+                }
+
+                module.push_instruction(Instruction::new(Op::EndPrimitive, None, None, Vec::new()));
+                Ok((TypeName::VOID, 0))
+            }
+
+            Node::Barrier => {
+                if module.get_settings().mod_type != ExecutionModel::TessellationControl {
+                    bail!(Error::UnsupportedOperation("Barrier"));
+                }
+
+                let execution =
+                    module.register_constant(&TypedValue::UInt(Scope::Workgroup as u32))?;
+                let memory =
+                    module.register_constant(&TypedValue::UInt(Scope::Invocation as u32))?;
+                let semantics =
+                    module.register_constant(&TypedValue::UInt(MemorySemantics::empty().bits()))?;
+
+                module.push_instruction(Instruction::new(
+                    Op::ControlBarrier,
+                    None,
+                    None,
+                    vec![
+                        Operand::IdRef(execution),
+                        Operand::IdRef(memory),
+                        Operand::IdRef(semantics),
+                    ],
+                ));
+
+                Ok((TypeName::VOID, 0))
+            }
         }
     }
 }
 
+/// Declare an input or output variable, or get the one already declared for the same slot
+fn declare_interface(
+    module: &mut impl Builder,
+    storage: StorageClass,
+    (location, attr_type, var_type): (Word, &'static TypeName, Word),
+    array_len: Option<u32>,
+    name: &VariableName,
+    attributes: &Attributes,
+) -> Result<Word> {
+    let key = name.variable_key(storage, location);
+    if let Some((var_id, prev_type)) = module.get_variable(key) {
+        if prev_type != var_type {
+            bail!(Error::BadArguments {
+                args: Box::new([attr_type]),
+                port: None
+            });
+        }
+
+        return Ok(var_id);
+    }
+
+    let ptr_type = module.get_id();
+    module.push_declaration(Instruction::new(
+        Op::TypePointer,
+        None,
+        Some(ptr_type),
+        vec![Operand::StorageClass(storage), Operand::IdRef(var_type)],
+    ));
+
+    let var_id = module.get_id();
+    if storage == StorageClass::Input {
+        module.push_input(var_id);
+    } else {
+        module.push_output(var_id);
+    }
+
+    module.push_declaration(Instruction::new(
+        Op::Variable,
+        Some(ptr_type),
+        Some(var_id),
+        vec![Operand::StorageClass(storage)],
+    ));
+
+    name.decorate_location(module, var_id, location);
+    attributes.decorate_variable(module, var_id);
+    name.decorate_variable(module, var_id);
+
+    let variable = name.reflect(location, attr_type, array_len);
+    module.push_reflection(Declaration::Interface(storage, variable));
+
+    module.set_variable(key, (var_id, var_type));
+    Ok(var_id)
+}
+
+/// Declare an arrayed input or output variable holding a value for each vertex of a primitive,
+/// or get the one already declared for the same slot
+fn declare_array(
+    module: &mut impl Builder,
+    storage: StorageClass,
+    (location, attr_type, type_id): (Word, &'static TypeName, Word),
+    len: u32,
+    name: &VariableName,
+    attributes: &Attributes,
+) -> Result<Word> {
+    let key = name.variable_key(storage, location);
+    let array_type = if let Some((_, array_type)) = module.get_variable(key) {
+        array_type
+    } else {
+        let len_id = module.register_constant(&TypedValue::UInt(len))?;
+        let array_type = module.get_id();
+        module.push_declaration(Instruction::new(
+            Op::TypeArray,
+            None,
+            Some(array_type),
+            vec![Operand::IdRef(type_id), Operand::IdRef(len_id)],
+        ));
+
+        array_type
+    };
+
+    declare_interface(
+        module,
+        storage,
+        (location, attr_type, array_type),
+        Some(len),
+        name,
+        attributes,
+    )
+}
+
+/// Declare a pointer to an element of an arrayed variable
+fn declare_pointer(module: &mut impl Builder, storage: StorageClass, type_id: Word) -> Word {
+    let ptr_type = module.get_id();
+    module.push_declaration(Instruction::new(
+        Op::TypePointer,
+        None,
+        Some(ptr_type),
+        vec![Operand::StorageClass(storage), Operand::IdRef(type_id)],
+    ));
+
+    ptr_type
+}
+
+/// Get a pointer to an element of an arrayed variable
+fn access_chain(module: &mut impl Builder, ptr_type: Word, var_id: Word, index: Word) -> Word {
+    let ptr_id = module.get_id();
+    module.push_instruction(Instruction::new(
+        Op::AccessChain,
+        Some(ptr_type),
+        Some(ptr_id),
+        vec![Operand::IdRef(var_id), Operand::IdRef(index)],
+    ));
+
+    ptr_id
+}
+
+/// Load the index of the current invocation of a tessellation control shader
+fn load_invocation_id(module: &mut impl Builder) -> Result<Word> {
+    let type_id = module.register_type(TypeName::INT);
+    let var_id = declare_interface(
+        module,
+        StorageClass::Input,
+        (0, TypeName::INT, type_id),
+        None,
+        &VariableName::BuiltIn(BuiltIn::InvocationId),
+        &Attributes::default(),
+    )?;
+
+    let res_id = module.get_id();
+    module.push_instruction(Instruction::new(
+        Op::Load,
+        Some(type_id),
+        Some(res_id),
+        vec![
+            Operand::IdRef(var_id),
+            Operand::MemoryAccess(MemoryAccess::empty()),
+        ],
+    ));
+
+    Ok(res_id)
+}
+
+/// Declare the array backing a tessellation level builtin, returning its ID and length
+///
+/// The levels are written by the control shader and read by the evaluation shader
+fn declare_tess_level(
+    module: &mut impl Builder,
+    storage: StorageClass,
+    name: &VariableName,
+    attributes: &Attributes,
+) -> Result<(Word, u32)> {
+    let (node, len) = match *name {
+        VariableName::BuiltIn(BuiltIn::TessLevelOuter) => ("TessLevelOuter", 4),
+        _ => ("TessLevelInner", 2),
+    };
+
+    let expected = if storage == StorageClass::Output {
+        ExecutionModel::TessellationControl
+    } else {
+        ExecutionModel::TessellationEvaluation
+    };
+
+    if module.get_settings().mod_type != expected {
+        bail!(Error::UnsupportedOperation(node));
+    }
+
+    let float_id = module.register_type(TypeName::FLOAT);
+    let var_id = declare_array(
+        module,
+        storage,
+        (0, TypeName::FLOAT, float_id),
+        len,
+        name,
+        attributes,
+    )?;
+
+    Ok((var_id, len))
+}
+
+/// Store the components of a vector in a tessellation level builtin
+#[allow(clippy::cast_possible_wrap)]
+fn store_tess_level(
+    module: &mut impl Builder,
+    name: &VariableName,
+    attr_type: &'static TypeName,
+    value: Word,
+    attributes: &Attributes,
+) -> Result<(&'static TypeName, Word)> {
+    let (var_id, len) = declare_tess_level(module, StorageClass::Output, name, attributes)?;
+
+    let float_id = module.register_type(TypeName::FLOAT);
+    let ptr_type = declare_pointer(module, StorageClass::Output, float_id);
+
+    for index in 0..len {
+        let index_id = module.register_constant(&TypedValue::Int(index as i32))?;
+        let ptr_id = access_chain(module, ptr_type, var_id, index_id);
+
+        let comp_id = module.get_id();
+        module.push_instruction(Instruction::new(
+            Op::CompositeExtract,
+            Some(float_id),
+            Some(comp_id),
+            vec![Operand::IdRef(value), Operand::LiteralInt32(index)],
+        ));
+
+        module.push_instruction(Instruction::new(
+            Op::Store,
+            None,
+            None,
+            vec![
+                Operand::IdRef(ptr_id),
+                Operand::IdRef(comp_id),
+                Operand::MemoryAccess(MemoryAccess::empty()),
+            ],
+        ));
+    }
+
+    Ok((attr_type, var_id))
+}
+
+/// Load the components of a tessellation level builtin into a vector
+#[allow(clippy::cast_possible_wrap)]
+fn load_tess_level(
+    module: &mut impl Builder,
+    name: &VariableName,
+    attr_type: &'static TypeName,
+    attributes: &Attributes,
+) -> Result<(&'static TypeName, Word)> {
+    let (var_id, len) = declare_tess_level(module, StorageClass::Input, name, attributes)?;
+
+    let float_id = module.register_type(TypeName::FLOAT);
+    let ptr_type = declare_pointer(module, StorageClass::Input, float_id);
+
+    let mut components = Vec::with_capacity(len as usize);
+    for index in 0..len {
+        let index_id = module.register_constant(&TypedValue::Int(index as i32))?;
+        let ptr_id = access_chain(module, ptr_type, var_id, index_id);
+
+        let comp_id = module.get_id();
+        module.push_instruction(Instruction::new(
+            Op::Load,
+            Some(float_id),
+            Some(comp_id),
+            vec![
+                Operand::IdRef(ptr_id),
+                Operand::MemoryAccess(MemoryAccess::empty()),
+            ],
+        ));
+
+        components.push(Operand::IdRef(comp_id));
+    }
+
+    let type_id = module.register_type(attr_type);
+    let res_id = module.get_id();
+    module.push_instruction(Instruction::new(
+        Op::CompositeConstruct,
+        Some(type_id),
+        Some(res_id),
+        components,
+    ));
+
+    Ok((attr_type, res_id))
+}
+
+/// Get the `ClipDistance` builtin and a pointer to its first element, the variable
+/// being declared as an array of a single float since the nodes hold a scalar
+fn clip_distance_element(
+    module: &mut impl Builder,
+    storage: StorageClass,
+    name: &VariableName,
+) -> Result<(Word, Word)> {
+    let float_id = module.register_type(TypeName::FLOAT);
+    let var_id = declare_array(
+        module,
+        storage,
+        (0, TypeName::FLOAT, float_id),
+        1,
+        name,
+        &Attributes::default(),
+    )?;
+
+    let ptr_type = declare_pointer(module, storage, float_id);
+    let index_id = module.register_constant(&TypedValue::Int(0))?;
+    Ok((var_id, access_chain(module, ptr_type, var_id, index_id)))
+}
+
+/// Store a value in the standalone `ClipDistance` builtin
+fn store_clip_distance(
+    module: &mut impl Builder,
+    name: &VariableName,
+    attr_type: &'static TypeName,
+    value: Word,
+) -> Result<(&'static TypeName, Word)> {
+    let (var_id, ptr_id) = clip_distance_element(module, StorageClass::Output, name)?;
+    module.push_instruction(Instruction::new(
+        Op::Store,
+        None,
+        None,
+        vec![
+            Operand::IdRef(ptr_id),
+            Operand::IdRef(value),
+            Operand::MemoryAccess(MemoryAccess::empty()),
+        ],
+    ));
+
+    Ok((attr_type, var_id))
+}
+
+/// Load the value of the standalone `ClipDistance` builtin
+fn load_clip_distance(
+    module: &mut impl Builder,
+    name: &VariableName,
+    attr_type: &'static TypeName,
+) -> Result<(&'static TypeName, Word)> {
+    let (_, ptr_id) = clip_distance_element(module, StorageClass::Input, name)?;
+    let type_id = module.register_type(attr_type);
+    let res_id = module.get_id();
+    module.push_instruction(Instruction::new(
+        Op::Load,
+        Some(type_id),
+        Some(res_id),
+        vec![
+            Operand::IdRef(ptr_id),
+            Operand::MemoryAccess(MemoryAccess::empty()),
+        ],
+    ));
+
+    Ok((attr_type, res_id))
+}
+
+/// Per-vertex variables of the tessellation shaders hold a value for each vertex of the patch,
+/// and need to be declared with the arrayed nodes
+fn check_arrayed(
+    model: ExecutionModel,
+    storage: StorageClass,
+    name: &VariableName,
+    attributes: &Attributes,
+    arrayed: bool,
+) -> Result<()> {
+    let per_vertex = match (model, storage) {
+        (ExecutionModel::TessellationControl, _)
+        | (ExecutionModel::TessellationEvaluation, StorageClass::Input) => {
+            !attributes.patch && !name.is_per_patch()
+        }
+        _ => return Ok(()),
+    };
+
+    if per_vertex && !arrayed {
+        bail!(Error::InvalidAttributes(
+            "per-vertex variables of tessellation shaders must be arrayed"
+        ));
+    }
+
+    if !per_vertex && arrayed {
+        bail!(Error::InvalidAttributes(
+            "per-patch variables cannot be arrayed"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Store the value of a builtin output in the `gl_PerVertex` block
+#[allow(clippy::cast_possible_wrap)]
+fn store_per_vertex(
+    module: &mut impl Builder,
+    built_in: BuiltIn,
+    attr_type: &'static TypeName,
+    value: Word,
+) -> Result<(&'static TypeName, Word)> {
+    let (var_id, index) = module.register_per_vertex(built_in, attr_type)?;
+
+    let mut operands = vec![
+        Operand::IdRef(var_id),
+        Operand::IdRef(module.register_constant(&TypedValue::Int(index as i32))?),
+    ];
+
+    // ClipDistance is declared as an array, store the value in its first element
+    if built_in == BuiltIn::ClipDistance {
+        operands.push(Operand::IdRef(
+            module.register_constant(&TypedValue::Int(0))?,
+        ));
+    }
+
+    let type_id = module.register_type(attr_type);
+    let ptr_type = module.get_id();
+    module.push_declaration(Instruction::new(
+        Op::TypePointer,
+        None,
+        Some(ptr_type),
+        vec![
+            Operand::StorageClass(StorageClass::Output),
+            Operand::IdRef(type_id),
+        ],
+    ));
+
+    let ptr_id = module.get_id();
+    module.push_instruction(Instruction::new(
+        Op::AccessChain,
+        Some(ptr_type),
+        Some(ptr_id),
+        operands,
+    ));
+
+    module.push_instruction(Instruction::new(
+        Op::Store,
+        None,
+        None,
+        vec![
+            Operand::IdRef(ptr_id),
+            Operand::IdRef(value),
+            Operand::MemoryAccess(MemoryAccess::empty()),
+        ],
+    ));
+
+    Ok((attr_type, var_id))
+}
+
+/// Input and output variables holding 16-bit values need an additional capability
+fn declare_16bit_storage(module: &mut impl Builder, attr_type: &'static TypeName) {
+    if attr_type.width() == Some(16) {
+        module.add_capability(Capability::StorageInputOutput16);
+    }
+}
+
 impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.to_string())
     }
 }
 
+/// Decorations controlling how an input or output variable is interpolated
+/// and where it's stored
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Attributes {
+    /// Disable interpolation, required for integer fragment inputs
+    pub flat: bool,
+    /// Interpolate linearly in screen space, without perspective correction
+    pub no_perspective: bool,
+    /// Interpolate at the centroid of the covered samples
+    pub centroid: bool,
+    /// Interpolate at each sample, enabling per-sample shading
+    pub sample: bool,
+    /// Offset of the variable within its location, in 32-bit components
+    pub component: Option<u32>,
+    /// Blend equation input of a fragment output, used for dual-source blending
+    pub index: Option<u32>,
+    /// Share the variable between all the vertices of a tessellation patch
+    pub patch: bool,
+}
+
+impl Attributes {
+    fn check(
+        &self,
+        model: ExecutionModel,
+        storage: StorageClass,
+        attr_type: &'static TypeName,
+    ) -> Result<()> {
+        let is_fragment = model == ExecutionModel::Fragment;
+
+        if self.flat && self.no_perspective {
+            bail!(Error::InvalidAttributes(
+                "Flat and NoPerspective are mutually exclusive"
+            ));
+        }
+
+        if self.centroid && self.sample {
+            bail!(Error::InvalidAttributes(
+                "Centroid and Sample are mutually exclusive"
+            ));
+        }
+
+        if let Some(component) = self.component {
+            if component > 3 {
+                bail!(Error::InvalidAttributes("Component must be lower than 4"));
+            }
+        }
+
+        if self.index.is_some() && !(is_fragment && storage == StorageClass::Output) {
+            bail!(Error::InvalidAttributes(
+                "Index can only be used on fragment outputs"
+            ));
+        }
+
+        let is_patch_stage = match (model, storage) {
+            (ExecutionModel::TessellationControl, StorageClass::Output)
+            | (ExecutionModel::TessellationEvaluation, StorageClass::Input) => true,
+            _ => false,
+        };
+
+        if self.patch && !is_patch_stage {
+            bail!(Error::InvalidAttributes(
+                "Patch can only be used on tessellation control outputs and evaluation inputs"
+            ));
+        }
+
+        // Integer and double values cannot be interpolated
+        let needs_flat = match *attr_type.scalar() {
+            TypeName::Int(..) | TypeName::Float(64) => true,
+            _ => false,
+        };
+
+        if is_fragment && storage == StorageClass::Input && needs_flat && !self.flat {
+            bail!(Error::InvalidAttributes(
+                "integer and double fragment inputs must be Flat"
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn decorate_variable(&self, module: &mut impl Builder, var_id: Word) {
+        let flags = [
+            (self.flat, Decoration::Flat),
+            (self.no_perspective, Decoration::NoPerspective),
+            (self.centroid, Decoration::Centroid),
+            (self.sample, Decoration::Sample),
+            (self.patch, Decoration::Patch),
+        ];
+
+        for &(enabled, decoration) in &flags {
+            if enabled {
+                module.push_annotation(Instruction::new(
+                    Op::Decorate,
+                    None,
+                    None,
+                    vec![Operand::IdRef(var_id), Operand::Decoration(decoration)],
+                ));
+            }
+        }
+
+        let values = [
+            (self.component, Decoration::Component),
+            (self.index, Decoration::Index),
+        ];
+
+        for &(value, decoration) in &values {
+            if let Some(value) = value {
+                module.push_annotation(Instruction::new(
+                    Op::Decorate,
+                    None,
+                    None,
+                    vec![
+                        Operand::IdRef(var_id),
+                        Operand::Decoration(decoration),
+                        Operand::LiteralInt32(value),
+                    ],
+                ));
+            }
+        }
+    }
+}
+
 /// Name information for a variable
 /// Can be a well-known builtin, a custom string, or nothing
 /// (the variable is only adressed by its location)
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum VariableName {
     BuiltIn(BuiltIn),
     Named(String),
@@ -483,6 +1197,73 @@ pub enum VariableName {
 }
 
 impl VariableName {
+    /// Builtin variables are identified by their builtin instead of their location
+    pub(crate) fn variable_key(&self, storage: StorageClass, location: Word) -> VariableKey {
+        match *self {
+            VariableName::BuiltIn(built_in) => (storage, 0, Some(built_in)),
+            _ => (storage, location, None),
+        }
+    }
+
+    /// Check if this is a builtin shared by all the vertices of a tessellation patch
+    fn is_per_patch(&self) -> bool {
+        match *self {
+            VariableName::BuiltIn(BuiltIn::InvocationId)
+            | VariableName::BuiltIn(BuiltIn::PrimitiveId)
+            | VariableName::BuiltIn(BuiltIn::PatchVertices)
+            | VariableName::BuiltIn(BuiltIn::TessCoord)
+            | VariableName::BuiltIn(BuiltIn::TessLevelOuter)
+            | VariableName::BuiltIn(BuiltIn::TessLevelInner) => true,
+            _ => false,
+        }
+    }
+
+    /// Check the type of a variable matches the type of its builtin, if it's known
+    fn check_type(&self, attr_type: &'static TypeName) -> Result<()> {
+        let expected = match *self {
+            VariableName::BuiltIn(BuiltIn::Position)
+            | VariableName::BuiltIn(BuiltIn::FragCoord) => TypeName::VEC4,
+            VariableName::BuiltIn(BuiltIn::PointSize)
+            | VariableName::BuiltIn(BuiltIn::ClipDistance)
+            | VariableName::BuiltIn(BuiltIn::FragDepth) => TypeName::FLOAT,
+            VariableName::BuiltIn(BuiltIn::FrontFacing) => TypeName::BOOL,
+            VariableName::BuiltIn(BuiltIn::TessLevelOuter) => TypeName::VEC4,
+            VariableName::BuiltIn(BuiltIn::TessLevelInner) => TypeName::VEC2,
+            VariableName::BuiltIn(BuiltIn::TessCoord) => TypeName::VEC3,
+            VariableName::BuiltIn(BuiltIn::InvocationId)
+            | VariableName::BuiltIn(BuiltIn::PrimitiveId)
+            | VariableName::BuiltIn(BuiltIn::PatchVertices) => TypeName::INT,
+            _ => return Ok(()),
+        };
+
+        if attr_type != expected {
+            bail!(Error::BadArguments {
+                args: Box::new([attr_type]),
+                port: None
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Builtin variables are not bound to a location
+    fn decorate_location(&self, module: &mut impl Builder, var_id: Word, location: Word) {
+        if let VariableName::BuiltIn(_) = *self {
+            return;
+        }
+
+        module.push_annotation(Instruction::new(
+            Op::Decorate,
+            None,
+            None,
+            vec![
+                Operand::IdRef(var_id),
+                Operand::Decoration(Decoration::Location),
+                Operand::LiteralInt32(location),
+            ],
+        ));
+    }
+
     pub(crate) fn decorate_variable(&self, module: &mut impl Builder, var_id: Word) {
         match *self {
             VariableName::BuiltIn(built_in) => {
@@ -511,6 +1292,39 @@ impl VariableName {
         }
     }
 
+    /// Get the name of the variable, if it's not a builtin
+    pub(crate) fn reflect_name(&self) -> Option<String> {
+        match *self {
+            VariableName::Named(ref name) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    /// Describe an interface variable declared with this name
+    pub(crate) fn reflect(
+        &self,
+        location: Word,
+        type_name: &'static TypeName,
+        array_len: Option<u32>,
+    ) -> InterfaceVariable {
+        let built_in = match *self {
+            VariableName::BuiltIn(built_in) => Some(built_in),
+            _ => None,
+        };
+
+        InterfaceVariable {
+            location: if built_in.is_some() {
+                None
+            } else {
+                Some(location)
+            },
+            built_in,
+            type_name,
+            array_len,
+            name: self.reflect_name(),
+        }
+    }
+
     fn decorate_member(&self, module: &mut impl Builder, var_id: Word, offset: Word) {
         match *self {
             VariableName::BuiltIn(built_in) => {
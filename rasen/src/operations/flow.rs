@@ -2,7 +2,7 @@ use rspirv::mr::{Instruction, Operand};
 use spirv_headers::{LoopControl, MemoryAccess, Op, StorageClass};
 
 use builder::Builder;
-use errors::{ErrorKind, Result};
+use errors::{Error, Result};
 use module::FunctionRef;
 use types::TypeName;
 
@@ -13,7 +13,7 @@ pub(crate) fn loop_(
     args: Vec<(&'static TypeName, u32)>,
 ) -> Result<(&'static TypeName, u32)> {
     if args.len() != 1 {
-        bail!(ErrorKind::WrongArgumentsCount(args.len(), 1));
+        bail!(Error::WrongArgumentsCount(args.len(), 1));
     }
 
     let (init_ty, init_id) = args[0];
@@ -21,7 +21,7 @@ pub(crate) fn loop_(
     let (cond_id, cond_args, cond_res) = if let Some(res) = module.get_function(cond) {
         res
     } else {
-        bail!(ErrorKind::MissingFunction(cond))
+        bail!(Error::MissingFunction(cond))
     };
 
     assert_eq!(cond_args, &[init_ty]);
@@ -34,7 +34,7 @@ pub(crate) fn loop_(
     let (body_id, body_args, body_res) = if let Some(res) = module.get_function(body) {
         res
     } else {
-        bail!(ErrorKind::MissingFunction(body))
+        bail!(Error::MissingFunction(body))
     };
 
     assert_eq!(body_args, &[init_ty]);
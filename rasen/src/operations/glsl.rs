@@ -17,14 +17,17 @@ macro_rules! unary_vec {
             use types::TypeName::*;
 
             if args.len() != 1 {
-                bail!(ErrorKind::WrongArgumentsCount(args.len(), 1));
+                bail!(Error::WrongArgumentsCount(args.len(), 1));
             }
 
             let (arg_ty, arg_val) = args[0];
             let (res_type, scalar) = if let Vec(_, scalar) = *arg_ty {
                 (builder.register_type(scalar), scalar)
             } else {
-                bail!(ErrorKind::BadArguments(Box::new([ arg_ty ])));
+                bail!(Error::BadArguments {
+                    args: Box::new([arg_ty]),
+                    port: Some(0),
+                });
             };
 
             let res_id = builder.get_id();
@@ -68,7 +71,7 @@ macro_rules! variadic_any {
                     $name(builder, args[0..n - 1].to_vec())?,
                     args[n - 1],
                 ),
-                n => bail!(ErrorKind::WrongArgumentsCount(n, 2)),
+                n => bail!(Error::WrongArgumentsCount(n, 2)),
             };
 
             let (l_type, l_value) = l_arg;
@@ -84,9 +87,10 @@ macro_rules! variadic_any {
                 _ if l_type == r_type && r_type.is_float() => $fcode,
                 (&Vec(l_len, l_scalar), &Vec(r_len, r_scalar)) if l_len == r_len && l_scalar == r_scalar && r_scalar.is_float() => $fcode,
 
-                _ => bail!(ErrorKind::BadArguments(Box::new([
-                    l_type, r_type
-                ]))),
+                _ => bail!(Error::BadArguments {
+                    args: Box::new([l_type, r_type]),
+                    port: None,
+                }),
             };
 
             let res_type = builder.register_type(l_type);
@@ -123,7 +127,7 @@ macro_rules! trinary_any {
             use types::TypeName::*;
 
             if args.len() != 3 {
-                bail!(ErrorKind::WrongArgumentsCount(args.len(), 3));
+                bail!(Error::WrongArgumentsCount(args.len(), 3));
             }
 
             let (a_type, a_value) = args[0];
@@ -142,9 +146,10 @@ macro_rules! trinary_any {
                 _ if a_type == b_type && b_type == c_type && a_type.is_float() => $fcode,
                 (&Vec(a_len, a_scalar), &Vec(b_len, b_scalar), &Vec(c_len, c_scalar)) if a_len == b_len && b_len == c_len && a_scalar == b_scalar && b_scalar == c_scalar && a_scalar.is_float() => $fcode,
 
-                _ => bail!(ErrorKind::BadArguments(Box::new([
-                    a_type, b_type, c_type
-                ]))),
+                _ => bail!(Error::BadArguments {
+                    args: Box::new([a_type, b_type, c_type]),
+                    port: None,
+                }),
             };
 
             let res_type = builder.register_type(a_type);
@@ -180,7 +185,7 @@ pub(crate) fn distance<B: Builder>(builder: &mut B, args: &[(&'static TypeName,
     use types::TypeName::*;
 
     if args.len() != 2 {
-        bail!(ErrorKind::WrongArgumentsCount(args.len(), 2));
+        bail!(Error::WrongArgumentsCount(args.len(), 2));
     }
 
     let (l_type, l_value) = args[0];
@@ -209,9 +214,10 @@ pub(crate) fn distance<B: Builder>(builder: &mut B, args: &[(&'static TypeName,
 
             Ok((l_scalar, res_id))
         },
-        _ => bail!(ErrorKind::BadArguments(Box::new([
-            l_type, r_type
-        ]))),
+        _ => bail!(Error::BadArguments {
+            args: Box::new([l_type, r_type]),
+            port: None,
+        }),
     }
 }
 
@@ -220,7 +226,7 @@ pub(crate) fn reflect<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u
     use types::TypeName::*;
 
     if args.len() != 2 {
-        bail!(ErrorKind::WrongArgumentsCount(args.len(), 2));
+        bail!(Error::WrongArgumentsCount(args.len(), 2));
     }
 
     let (l_type, l_value) = args[0];
@@ -249,7 +255,10 @@ pub(crate) fn reflect<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u
 
             Ok((l_type, result_id))
         },
-        _ => bail!(ErrorKind::BadArguments(Box::new([ l_type, r_type ]))),
+        _ => bail!(Error::BadArguments {
+            args: Box::new([l_type, r_type]),
+            port: None,
+        }),
     }
 }
 
@@ -258,7 +267,7 @@ pub(crate) fn refract<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u
     use types::TypeName::*;
 
     if args.len() != 3 {
-        bail!(ErrorKind::WrongArgumentsCount(args.len(), 3));
+        bail!(Error::WrongArgumentsCount(args.len(), 3));
     }
 
     let (l_type, l_value) = args[0];
@@ -289,7 +298,10 @@ pub(crate) fn refract<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u
 
             Ok((l_type, res_id))
         },
-        _ => bail!(ErrorKind::BadArguments(Box::new([ l_type, r_type, i_type ]))),
+        _ => bail!(Error::BadArguments {
+            args: Box::new([l_type, r_type, i_type]),
+            port: None,
+        }),
     }
 }
 
@@ -298,7 +310,7 @@ pub(crate) fn sample<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u3
     use types::TypeName::*;
 
     if args.len() < 2 || args.len() > 3 {
-        bail!(ErrorKind::WrongArgumentsCount(args.len(), 2));
+        bail!(Error::WrongArgumentsCount(args.len(), 2));
     }
 
     let (image_type, image_value) = args[0];
@@ -315,11 +327,16 @@ pub(crate) fn sample<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u3
         (&Sampler(sampled_type, Dim::DimBuffer), coords_scalar) |
         (&Sampler(sampled_type, Dim::Dim1D), coords_scalar) if sampled_type.is_num() && coords_scalar.is_float() => {
             let res_type = match *sampled_type {
-                Int(true) => TypeName::IVEC4,
-                Int(false) => TypeName::UVEC4,
-                Float(false) => TypeName::VEC4,
-                Float(true) => TypeName::DVEC4,
-                _ => unreachable!(),
+                Int(true, 32) => TypeName::IVEC4,
+                Int(false, 32) => TypeName::UVEC4,
+                Int(true, 64) => TypeName::I64VEC4,
+                Int(false, 64) => TypeName::U64VEC4,
+                Float(32) => TypeName::VEC4,
+                Float(64) => TypeName::DVEC4,
+                _ => bail!(Error::BadArguments {
+                    args: Box::new([image_type, coords_type]),
+                    port: None,
+                }),
             };
 
             let vec_type = builder.register_type(res_type);
@@ -331,7 +348,10 @@ pub(crate) fn sample<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u3
 
             if let Some(&(bias_type, bias_value)) = args.get(2) {
                 if bias_type != TypeName::FLOAT {
-                    bail!(ErrorKind::BadArguments(Box::new([ image_type, coords_type, bias_type ])));
+                    bail!(Error::BadArguments {
+                        args: Box::new([image_type, coords_type, bias_type]),
+                        port: None,
+                    });
                 }
 
                 operands.push(Operand::ImageOperands(ImageOperands::BIAS));
@@ -351,9 +371,15 @@ pub(crate) fn sample<B: Builder>(builder: &mut B, args: &[(&'static TypeName, u3
         },
         
         _ => if let Some(&(bias_type, _)) = args.get(2) {
-            bail!(ErrorKind::BadArguments(Box::new([ image_type, coords_type, bias_type ])))
+            bail!(Error::BadArguments {
+                args: Box::new([image_type, coords_type, bias_type]),
+                port: None,
+            })
         } else {
-            bail!(ErrorKind::BadArguments(Box::new([ image_type, coords_type ])))
+            bail!(Error::BadArguments {
+                args: Box::new([image_type, coords_type]),
+                port: None,
+            })
         },
     }
 }
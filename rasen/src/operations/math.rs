@@ -72,7 +72,7 @@ pub(crate) fn multiply<B: Builder>(
     let (l_arg, r_arg) = match args.len() {
         2 => (args[0], args[1]),
         n if n > 2 => (multiply(builder, &args[0..n - 1])?, args[n - 1]),
-        n => bail!(ErrorKind::WrongArgumentsCount(n, 2)),
+        n => bail!(Error::WrongArgumentsCount(n, 2)),
     };
 
     let (l_type, l_value) = l_arg;
@@ -164,7 +164,10 @@ pub(crate) fn multiply<B: Builder>(
             l_type
         }
 
-        _ => bail!(ErrorKind::BadArguments(Box::new([l_type, r_type]))),
+        _ => bail!(Error::BadArguments {
+            args: Box::new([l_type, r_type]),
+            port: None,
+        }),
     };
 
     Ok((res_type, res_id))
@@ -178,7 +181,7 @@ pub(crate) fn dot<B: Builder>(
     use types::TypeName::*;
 
     if args.len() != 2 {
-        bail!(ErrorKind::WrongArgumentsCount(args.len(), 2));
+        bail!(Error::WrongArgumentsCount(args.len(), 2));
     }
 
     let (l_type, l_value) = args[0];
@@ -200,7 +203,10 @@ pub(crate) fn dot<B: Builder>(
 
             Ok((l_scalar, result_id))
         }
-        _ => bail!(ErrorKind::BadArguments(Box::new([l_type, r_type]))),
+        _ => bail!(Error::BadArguments {
+            args: Box::new([l_type, r_type]),
+            port: None,
+        }),
     }
 }
 
@@ -219,7 +225,7 @@ macro_rules! impl_math_op {
                     $name(builder, args[0..n - 1].to_vec())?,
                     args[n - 1],
                 ),
-                n => bail!(ErrorKind::WrongArgumentsCount(n, 2)),
+                n => bail!(Error::WrongArgumentsCount(n, 2)),
             };
 
             let result_id = builder.get_id();
@@ -320,7 +326,10 @@ macro_rules! impl_math_op {
                             l_type
                         },
 
-                        _ => bail!(ErrorKind::BadArguments(Box::new([ l_type, r_type ]))),
+                        _ => bail!(Error::BadArguments {
+                            args: Box::new([l_type, r_type]),
+                            port: None,
+                        }),
                     }
                 };
                 ( $iopcode:ident, $fopcode:ident ) => {
@@ -346,8 +355,8 @@ macro_rules! impl_math_op {
                                 Op::$iopcode,
                                 Some(res_type), Some(result_id),
                                 vec![
-                                    Operand::IdRef(r_value),
                                     Operand::IdRef(l_value),
+                                    Operand::IdRef(r_value),
                                 ]
                             ));
 
@@ -383,7 +392,10 @@ macro_rules! impl_math_op {
                             l_type
                         },
 
-                        _ => bail!(ErrorKind::BadArguments(Box::new([ l_type, r_type ]))),
+                        _ => bail!(Error::BadArguments {
+                            args: Box::new([l_type, r_type]),
+                            port: None,
+                        }),
                     }
                 };
             }
@@ -410,7 +422,7 @@ macro_rules! impl_logical_op {
             let (l_arg, r_arg) = if args.len() == 2 {
                 (args[0], args[1])
             } else {
-                bail!(ErrorKind::WrongArgumentsCount(args.len(), 2))
+                bail!(Error::WrongArgumentsCount(args.len(), 2))
             };
 
             let result_id = builder.get_id();
@@ -478,7 +490,10 @@ macro_rules! impl_logical_op {
                     ));
                 }
 
-                _ => bail!(ErrorKind::BadArguments(Box::new([l_type, r_type]))),
+                _ => bail!(Error::BadArguments {
+                    args: Box::new([l_type, r_type]),
+                    port: None,
+                }),
             }
 
             Ok((TypeName::BOOL, result_id))
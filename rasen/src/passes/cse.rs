@@ -0,0 +1,177 @@
+//! Merging of the nodes computing the same value
+
+use std::{
+    collections::hash_map::Entry,
+    mem::{self, Discriminant},
+};
+
+use fnv::FnvHashMap as HashMap;
+use petgraph::graph::NodeIndex;
+use spirv_headers::BuiltIn;
+
+use errors::*;
+use graph::Graph;
+use node::{Attributes, Node, VariableName};
+use types::{Scalar, TypeName};
+
+/// The variable declared by an `Input` or `Uniform` node
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Slot {
+    Input(u32),
+    BuiltIn(BuiltIn),
+    Uniform(u32),
+    Sampler(u32),
+}
+
+/// The type, name and attributes of a declared variable
+type Description<'a> = (
+    &'static TypeName,
+    Option<&'a VariableName>,
+    Option<Attributes>,
+);
+
+/// Check if a node computes a value only depending on its arguments
+fn is_pure(node: &Node) -> bool {
+    match *node {
+        Node::Output(..)
+        | Node::ArrayedOutput(..)
+        | Node::Return
+        | Node::Call(..)
+        | Node::Loop(..)
+        | Node::EmitVertex
+        | Node::EndPrimitive
+        | Node::Barrier => false,
+        _ => true,
+    }
+}
+
+/// Get the variable declared by a node, along with a description of its
+/// type, name and attributes
+fn declaration(node: &Node) -> Option<(Slot, Description)> {
+    match *node {
+        Node::Input(_, ty, VariableName::BuiltIn(built_in), attributes) => {
+            Some((Slot::BuiltIn(built_in), (ty, None, Some(attributes))))
+        }
+        Node::Input(location, ty, ref name, attributes) => {
+            Some((Slot::Input(location), (ty, Some(name), Some(attributes))))
+        }
+        Node::Uniform(location, ty @ &TypeName::Sampler(..), ref name) => {
+            Some((Slot::Sampler(location), (ty, Some(name), None)))
+        }
+        Node::Uniform(location, ty, ref name) => {
+            Some((Slot::Uniform(location), (ty, Some(name), None)))
+        }
+        _ => None,
+    }
+}
+
+/// Check if two scalars are equal, comparing the floats on their bits
+fn same_scalar(a: Scalar, b: Scalar) -> bool {
+    match (a, b) {
+        (Scalar::Float(a), Scalar::Float(b)) => a.to_bits() == b.to_bits(),
+        (a, b) => a == b,
+    }
+}
+
+/// Check if two nodes have the same payload
+///
+/// The constants are compared on the bits of their components, so the values
+/// the builder emits differently like `0.0` and `-0.0` are kept apart
+fn same_payload(a: &Node, b: &Node) -> bool {
+    match (a, b) {
+        (&Node::Constant(ref a), &Node::Constant(ref b)) => {
+            match (a.components(), b.components()) {
+                (Some(ref left), Some(ref right)) => {
+                    a.to_type_name() == b.to_type_name()
+                        && left.len() == right.len()
+                        && left
+                            .iter()
+                            .zip(right)
+                            .all(|(&left, &right)| same_scalar(left, right))
+                }
+                // Matrices have no components and are never merged
+                _ => false,
+            }
+        }
+        _ => a == b,
+    }
+}
+
+/// Merge the pure nodes having the same payload and arguments, and the `Input` and
+/// `Uniform` nodes declaring the same variable, returning the number of removed nodes
+///
+/// Two `Input` or `Uniform` nodes with the same location (or builtin) but a different
+/// type, name or attributes are rejected with `ConflictingDeclarations`, before the
+/// graph is modified. Samplers and members of the uniform block are different variables,
+/// even at the same location
+pub fn eliminate_common_subexpressions(graph: &mut Graph) -> Result<usize> {
+    let order = graph.toposort()?;
+
+    // All the declarations are checked before merging any node
+    let mut declarations: HashMap<Slot, NodeIndex<u32>> = HashMap::default();
+    {
+        let mut descriptions: HashMap<Slot, Description> = HashMap::default();
+        for &index in &order {
+            if let Some((slot, desc)) = declaration(&graph[index]) {
+                match descriptions.entry(slot) {
+                    Entry::Occupied(entry) => {
+                        if *entry.get() != desc {
+                            bail!(Error::ConflictingDeclarations(declarations[&slot], index));
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(desc);
+                        declarations.insert(slot, index);
+                    }
+                }
+            }
+        }
+    }
+
+    // The candidates are grouped by variant and arguments, then compared on their payload
+    let mut nodes: HashMap<(Discriminant<Node>, Vec<NodeIndex<u32>>), Vec<NodeIndex<u32>>> =
+        HashMap::default();
+    let mut count = 0;
+
+    // The arguments of each node are merged before the node itself
+    for index in order {
+        let previous = {
+            let node = &graph[index];
+            if let Some((slot, _)) = declaration(node) {
+                let previous = declarations[&slot];
+                if previous == index {
+                    None
+                } else {
+                    Some(previous)
+                }
+            } else if is_pure(node) {
+                let key = (mem::discriminant(node), graph.arguments(index)?);
+                let candidates = nodes.entry(key).or_insert_with(Vec::new);
+                let previous = candidates
+                    .iter()
+                    .cloned()
+                    .find(|&candidate| same_payload(&graph[candidate], node));
+
+                if previous.is_none() {
+                    candidates.push(index);
+                }
+
+                previous
+            } else {
+                None
+            }
+        };
+
+        if let Some(previous) = previous {
+            let outputs: Vec<_> = graph.outputs_of(index).collect();
+            for edge in outputs {
+                graph.add_edge(previous, edge.to, edge.port);
+            }
+
+            graph.remove_node(index);
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
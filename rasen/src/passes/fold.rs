@@ -0,0 +1,480 @@
+//! Evaluation of the nodes whose arguments are all known at compile time
+
+use fnv::FnvHashMap as HashMap;
+use petgraph::graph::NodeIndex;
+
+use graph::Graph;
+use node::Node;
+use types::{f16, Scalar, TypeName, TypedValue};
+
+/// The value of a node known at compile time
+#[derive(Clone, Debug)]
+struct Constant {
+    /// A scalar or vector type
+    ty: &'static TypeName,
+    /// The components of the value, a single one for scalars
+    components: Vec<Scalar>,
+}
+
+/// A binary arithmetic operation
+#[derive(Copy, Clone, Debug)]
+enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Arith {
+    /// Apply the operation to two components of the scalar type `ty`
+    ///
+    /// Integers wrap around, and integer divisions by zero are not evaluated
+    fn apply(self, ty: &'static TypeName, lhs: Scalar, rhs: Scalar) -> Option<Scalar> {
+        let res = match (lhs, rhs) {
+            (Scalar::Int(lhs), Scalar::Int(rhs)) => Scalar::Int(match self {
+                Arith::Add => lhs.wrapping_add(rhs),
+                Arith::Sub => lhs.wrapping_sub(rhs),
+                Arith::Mul => lhs.wrapping_mul(rhs),
+                Arith::Div => lhs.checked_div(rhs)?,
+            }),
+            (Scalar::UInt(lhs), Scalar::UInt(rhs)) => Scalar::UInt(match self {
+                Arith::Add => lhs.wrapping_add(rhs),
+                Arith::Sub => lhs.wrapping_sub(rhs),
+                Arith::Mul => lhs.wrapping_mul(rhs),
+                Arith::Div => lhs.checked_div(rhs)?,
+            }),
+            (Scalar::Float(lhs), Scalar::Float(rhs)) => Scalar::Float(match self {
+                Arith::Add => lhs + rhs,
+                Arith::Sub => lhs - rhs,
+                Arith::Mul => lhs * rhs,
+                Arith::Div => lhs / rhs,
+            }),
+            _ => return None,
+        };
+
+        Some(narrow(ty, res))
+    }
+}
+
+/// Round a float to the precision of its type, or wrap an integer to the width of its type
+///
+/// Rounding the result of each operation computed in double precision gives
+/// the same value as the operation computed in the precision of the type
+#[allow(clippy::cast_possible_truncation)]
+fn narrow(ty: &'static TypeName, value: Scalar) -> Scalar {
+    match (ty, value) {
+        (&TypeName::Float(16), Scalar::Float(val)) => {
+            Scalar::Float(f64::from(f16::from_f32(val as f32).to_f32()))
+        }
+        (&TypeName::Float(32), Scalar::Float(val)) => Scalar::Float(f64::from(val as f32)),
+        (&TypeName::Int(true, width), Scalar::Int(val)) if width < 64 => {
+            let shift = 64 - width;
+            Scalar::Int((val << shift) >> shift)
+        }
+        (&TypeName::Int(false, width), Scalar::UInt(val)) if width < 64 => {
+            Scalar::UInt(val & ((1 << width) - 1))
+        }
+        _ => value,
+    }
+}
+
+/// Apply a floating-point function to a component of the scalar type `ty`
+fn float_op(ty: &'static TypeName, value: Scalar, func: impl Fn(f64) -> f64) -> Option<Scalar> {
+    match value {
+        Scalar::Float(val) => Some(narrow(ty, Scalar::Float(func(val)))),
+        _ => None,
+    }
+}
+
+fn min(lhs: Scalar, rhs: Scalar) -> Option<Scalar> {
+    Some(match (lhs, rhs) {
+        (Scalar::Int(lhs), Scalar::Int(rhs)) => Scalar::Int(lhs.min(rhs)),
+        (Scalar::UInt(lhs), Scalar::UInt(rhs)) => Scalar::UInt(lhs.min(rhs)),
+        (Scalar::Float(lhs), Scalar::Float(rhs)) => Scalar::Float(lhs.min(rhs)),
+        _ => return None,
+    })
+}
+
+fn max(lhs: Scalar, rhs: Scalar) -> Option<Scalar> {
+    Some(match (lhs, rhs) {
+        (Scalar::Int(lhs), Scalar::Int(rhs)) => Scalar::Int(lhs.max(rhs)),
+        (Scalar::UInt(lhs), Scalar::UInt(rhs)) => Scalar::UInt(lhs.max(rhs)),
+        (Scalar::Float(lhs), Scalar::Float(rhs)) => Scalar::Float(lhs.max(rhs)),
+        _ => return None,
+    })
+}
+
+impl Constant {
+    fn from_value(value: &TypedValue) -> Option<Self> {
+        Some(Constant {
+            ty: value.to_type_name(),
+            components: value.components()?,
+        })
+    }
+
+    fn scalar(scalar: &'static TypeName, value: Scalar) -> Self {
+        Constant {
+            ty: scalar,
+            components: vec![value],
+        }
+    }
+
+    fn is_float(&self) -> bool {
+        self.ty.scalar().is_float()
+    }
+
+    fn is_vector(&self) -> bool {
+        match *self.ty {
+            TypeName::Vec(..) => true,
+            _ => false,
+        }
+    }
+
+    /// Apply a function to each component
+    fn map(&self, func: impl Fn(&'static TypeName, Scalar) -> Option<Scalar>) -> Option<Self> {
+        let scalar = self.ty.scalar();
+        let components: Option<_> = {
+            self.components
+                .iter()
+                .map(|&val| func(scalar, val))
+                .collect()
+        };
+
+        Some(Constant {
+            ty: self.ty,
+            components: components?,
+        })
+    }
+
+    /// Apply a function to each pair of components of two values of the same type
+    fn zip(
+        &self,
+        other: &Self,
+        func: impl Fn(&'static TypeName, Scalar, Scalar) -> Option<Scalar>,
+    ) -> Option<Self> {
+        if self.ty != other.ty {
+            return None;
+        }
+
+        let scalar = self.ty.scalar();
+        let components: Option<_> = {
+            self.components
+                .iter()
+                .zip(&other.components)
+                .map(|(&lhs, &rhs)| func(scalar, lhs, rhs))
+                .collect()
+        };
+
+        Some(Constant {
+            ty: self.ty,
+            components: components?,
+        })
+    }
+
+    /// Apply an arithmetic operation component-wise
+    fn arith(&self, op: Arith, other: &Self) -> Option<Self> {
+        self.zip(other, |ty, lhs, rhs| op.apply(ty, lhs, rhs))
+    }
+
+    /// Multiply each component by a scalar
+    fn scale(&self, factor: Scalar) -> Option<Self> {
+        self.map(|ty, val| Arith::Mul.apply(ty, factor, val))
+    }
+
+    /// Dot product of two floating-point vectors, summing the products from the first component
+    fn dot(&self, other: &Self) -> Option<Self> {
+        if !self.is_vector() || !self.is_float() || self.ty != other.ty {
+            return None;
+        }
+
+        let scalar = self.ty.scalar();
+        let mut products = {
+            self.components
+                .iter()
+                .zip(&other.components)
+                .map(|(&lhs, &rhs)| Arith::Mul.apply(scalar, rhs, lhs))
+        };
+
+        let first = products.next()??;
+        let sum = products.try_fold(first, |sum, product| {
+            Arith::Add.apply(scalar, sum, product?)
+        })?;
+
+        Some(Constant::scalar(scalar, sum))
+    }
+
+    fn length(&self) -> Option<Self> {
+        self.dot(self)?.map(|ty, val| float_op(ty, val, f64::sqrt))
+    }
+}
+
+/// Fold the arguments of a variadic node from the left
+fn variadic(
+    args: &[Constant],
+    func: impl Fn(&Constant, &Constant) -> Option<Constant>,
+) -> Option<Constant> {
+    if args.len() < 2 {
+        return None;
+    }
+
+    let (first, rest) = args.split_first()?;
+    rest.iter()
+        .try_fold(first.clone(), |lhs, rhs| func(&lhs, rhs))
+}
+
+/// Apply a function to each component of a single floating-point argument
+fn unary(args: &[Constant], func: fn(f64) -> f64) -> Option<Constant> {
+    match *args {
+        [ref arg] if arg.is_float() => arg.map(|ty, val| float_op(ty, val, func)),
+        _ => None,
+    }
+}
+
+fn multiply(lhs: &Constant, rhs: &Constant) -> Option<Constant> {
+    if lhs.ty == rhs.ty {
+        return lhs.arith(Arith::Mul, rhs);
+    }
+
+    match (lhs.is_vector(), rhs.is_vector()) {
+        (true, false) if lhs.is_float() && lhs.ty.scalar() == rhs.ty => {
+            lhs.scale(rhs.components[0])
+        }
+        (false, true) if rhs.is_float() && rhs.ty.scalar() == lhs.ty => {
+            rhs.scale(lhs.components[0])
+        }
+        _ => None,
+    }
+}
+
+fn normalize(vector: &Constant) -> Option<Constant> {
+    let length = vector.length()?.components[0];
+    vector.map(|ty, val| Arith::Div.apply(ty, val, length))
+}
+
+fn cross(lhs: &Constant, rhs: &Constant) -> Option<Constant> {
+    match *lhs.ty {
+        TypeName::Vec(3, scalar) if scalar.is_float() && lhs.ty == rhs.ty => {}
+        _ => return None,
+    }
+
+    let scalar = lhs.ty.scalar();
+    let (l, r) = (&lhs.components, &rhs.components);
+    let component = |a: usize, b: usize| {
+        let first = Arith::Mul.apply(scalar, l[a], r[b])?;
+        let second = Arith::Mul.apply(scalar, r[a], l[b])?;
+        Arith::Sub.apply(scalar, first, second)
+    };
+
+    Some(Constant {
+        ty: lhs.ty,
+        components: vec![component(1, 2)?, component(2, 0)?, component(0, 1)?],
+    })
+}
+
+fn reflect(incident: &Constant, normal: &Constant) -> Option<Constant> {
+    let scalar = incident.ty.scalar();
+    let dot = normal.dot(incident)?.components[0];
+    let factor = Arith::Add.apply(scalar, dot, dot)?;
+    incident.arith(Arith::Sub, &normal.scale(factor)?)
+}
+
+fn clamp(value: &Constant, low: &Constant, high: &Constant) -> Option<Constant> {
+    value
+        .zip(low, |_, val, low| max(val, low))?
+        .zip(high, |_, val, high| min(val, high))
+}
+
+fn mix(x: &Constant, y: &Constant, alpha: &Constant) -> Option<Constant> {
+    if !x.is_float() {
+        return None;
+    }
+
+    let inverse = alpha.map(|ty, val| Arith::Sub.apply(ty, Scalar::Float(1.0), val))?;
+    let lhs = x.arith(Arith::Mul, &inverse)?;
+    let rhs = y.arith(Arith::Mul, alpha)?;
+    lhs.arith(Arith::Add, &rhs)
+}
+
+fn smoothstep(edge0: &Constant, edge1: &Constant, x: &Constant) -> Option<Constant> {
+    if !x.is_float() {
+        return None;
+    }
+
+    let range = edge1.arith(Arith::Sub, edge0)?;
+    let t = x.arith(Arith::Sub, edge0)?.arith(Arith::Div, &range)?;
+    let t = t.map(|_, val| max(val, Scalar::Float(0.0)))?;
+    let t = t.map(|_, val| min(val, Scalar::Float(1.0)))?;
+
+    t.map(|ty, t| {
+        let square = Arith::Mul.apply(ty, t, t)?;
+        let double = Arith::Mul.apply(ty, Scalar::Float(2.0), t)?;
+        let rest = Arith::Sub.apply(ty, Scalar::Float(3.0), double)?;
+        Arith::Mul.apply(ty, square, rest)
+    })
+}
+
+/// Evaluate a node from the values of its arguments, or return `None` if
+/// the node cannot be evaluated at compile time
+fn fold(node: &Node, args: &[Constant]) -> Option<Constant> {
+    match (node, args) {
+        (&Node::Add, _) => variadic(args, |lhs, rhs| lhs.arith(Arith::Add, rhs)),
+        (&Node::Subtract, _) => variadic(args, |lhs, rhs| lhs.arith(Arith::Sub, rhs)),
+        (&Node::Multiply, _) => variadic(args, multiply),
+        (&Node::Divide, _) => variadic(args, |lhs, rhs| lhs.arith(Arith::Div, rhs)),
+
+        (&Node::Min, _) => variadic(args, |lhs, rhs| lhs.zip(rhs, |_, lhs, rhs| min(lhs, rhs))),
+        (&Node::Max, _) => variadic(args, |lhs, rhs| lhs.zip(rhs, |_, lhs, rhs| max(lhs, rhs))),
+        (&Node::Clamp, &[ref value, ref low, ref high]) => clamp(value, low, high),
+        (&Node::Mix, &[ref x, ref y, ref alpha]) => mix(x, y, alpha),
+        (&Node::Smoothstep, &[ref edge0, ref edge1, ref x]) => smoothstep(edge0, edge1, x),
+
+        (&Node::Floor, _) => unary(args, f64::floor),
+        (&Node::Ceil, _) => unary(args, f64::ceil),
+        (&Node::Round, _) => unary(args, f64::round),
+        (&Node::Abs, _) => unary(args, f64::abs),
+        (&Node::Sqrt, _) => unary(args, f64::sqrt),
+        (&Node::Log, _) => unary(args, f64::ln),
+        (&Node::Pow, &[ref x, ref y]) if x.is_float() => x.zip(y, |ty, x, y| match y {
+            Scalar::Float(y) => float_op(ty, x, |x| x.powf(y)),
+            _ => None,
+        }),
+
+        (&Node::Normalize, &[ref vector]) => normalize(vector),
+        (&Node::Length, &[ref vector]) => vector.length(),
+        (&Node::Dot, &[ref lhs, ref rhs]) => lhs.dot(rhs),
+        (&Node::Distance, &[ref lhs, ref rhs]) => lhs.arith(Arith::Sub, rhs)?.length(),
+        (&Node::Cross, &[ref lhs, ref rhs]) => cross(lhs, rhs),
+        (&Node::Reflect, &[ref incident, ref normal]) => reflect(incident, normal),
+
+        (&Node::Extract(index), &[ref vector]) if vector.is_vector() => {
+            let component = *vector.components.get(index as usize)?;
+            Some(Constant::scalar(vector.ty.scalar(), component))
+        }
+        (&Node::Construct(ty), _) => {
+            let size = match *ty {
+                TypeName::Vec(size, _) => size as usize,
+                _ => return None,
+            };
+            if args.len() != size || args.iter().any(|arg| arg.ty != ty.scalar()) {
+                return None;
+            }
+
+            Some(Constant {
+                ty,
+                components: args.iter().map(|arg| arg.components[0]).collect(),
+            })
+        }
+
+        _ => None,
+    }
+}
+
+/// Memoized evaluation of the nodes of a graph
+struct Folder<'a> {
+    graph: &'a Graph,
+    values: HashMap<NodeIndex<u32>, Option<Constant>>,
+}
+
+impl<'a> Folder<'a> {
+    fn visit(&mut self, index: NodeIndex<u32>) -> Option<Constant> {
+        if let Some(value) = self.values.get(&index) {
+            return value.clone();
+        }
+
+        // Nodes being visited are not constant, so the cycles are never folded
+        self.values.insert(index, None);
+        let value = self.evaluate(index);
+        self.values.insert(index, value.clone());
+        value
+    }
+
+    fn evaluate(&mut self, index: NodeIndex<u32>) -> Option<Constant> {
+        let graph = self.graph;
+        let node = &graph[index];
+        if let Node::Constant(ref value) = *node {
+            return Constant::from_value(value);
+        }
+
+        let args: Option<Vec<_>> = {
+            graph
+                .arguments(index)
+                .ok()?
+                .into_iter()
+                .map(|arg| self.visit(arg))
+                .collect()
+        };
+
+        fold(node, &args?)
+    }
+}
+
+/// Replace the nodes whose arguments are all constant with the value they evaluate to,
+/// returning the number of replaced nodes
+///
+/// The nodes are evaluated with the semantics of the `Execute` context of `rasen-dsl`:
+/// floating-point results are rounded to the precision of their type after each operation
+/// and integer results wrap around. Operations that would be undefined at runtime, like
+/// integer divisions by zero, are not folded.
+///
+/// The arguments that were only used by the replaced nodes are removed from the graph,
+/// the indices of the other nodes remain valid
+pub fn fold_constants(graph: &mut Graph) -> usize {
+    let values = {
+        let mut folder = Folder {
+            graph,
+            values: HashMap::default(),
+        };
+
+        for (index, _) in graph.nodes() {
+            folder.visit(index);
+        }
+
+        folder.values
+    };
+
+    // Only the outermost nodes of the constant subtrees are replaced
+    let folded: Vec<_> = {
+        values
+            .iter()
+            .filter_map(|(&index, value)| {
+                if let Node::Constant(_) = graph[index] {
+                    return None;
+                }
+
+                let is_used = graph
+                    .outputs_of(index)
+                    .any(|edge| values[&edge.to].is_none());
+                if !is_used {
+                    return None;
+                }
+
+                let value = value.as_ref()?;
+                TypedValue::from_components(value.ty, &value.components).map(|value| (index, value))
+            })
+            .collect()
+    };
+
+    let count = folded.len();
+    let mut unused = Vec::new();
+    for (index, value) in folded {
+        for edge in graph.inputs_of(index) {
+            graph.remove_edge(edge.index);
+            unused.push(edge.from);
+        }
+
+        graph.replace_node(index, Node::Constant(value));
+    }
+
+    // The arguments of the replaced nodes are constant, and can be removed
+    // once they are not used by any node
+    while let Some(index) = unused.pop() {
+        if !graph.contains_node(index) || graph.outputs_of(index).next().is_some() {
+            continue;
+        }
+
+        unused.extend(graph.inputs_of(index).into_iter().map(|edge| edge.from));
+        graph.remove_node(index);
+    }
+
+    count
+}
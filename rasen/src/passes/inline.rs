@@ -0,0 +1,244 @@
+//! Inlining of the calls to the small or single-use functions of a module
+
+use std::collections::VecDeque;
+
+use fnv::FnvHashMap as HashMap;
+use petgraph::graph::NodeIndex;
+
+use builder::{Settings, TypeCache};
+use graph::Graph;
+use module::{FunctionRef, InlineHint, Module};
+use node::Node;
+
+/// Check if the body of a function can be copied into its callers:
+/// it must compute a single value without side effects
+fn is_inlinable(function: &Graph) -> bool {
+    let mut returns = 0;
+    for (_, node) in function.nodes() {
+        match *node {
+            Node::Return => returns += 1,
+            Node::Output(..)
+            | Node::ArrayedOutput(..)
+            | Node::EmitVertex
+            | Node::EndPrimitive
+            | Node::Barrier => return false,
+            _ => {}
+        }
+    }
+
+    returns == 1
+}
+
+/// Count the `Call` nodes referencing each function in all the graphs of a module
+fn count_calls(module: &Module) -> HashMap<FunctionRef, usize> {
+    let graphs = {
+        Some(&module.main)
+            .into_iter()
+            .chain(module.entry_points.iter().map(|entry| &entry.graph))
+            .chain(module.functions.iter())
+    };
+
+    let mut uses = HashMap::default();
+    for graph in graphs {
+        for (_, node) in graph.nodes() {
+            if let Node::Call(function) = *node {
+                *uses.entry(function).or_insert(0) += 1;
+            }
+        }
+    }
+
+    uses
+}
+
+/// Replace a `Call` node with a copy of the body of the function, returning the
+/// `Call` nodes of the copy in topological order, or `None` if the call cannot be inlined
+///
+/// Only the copied nodes and the nodes using the result of the call are typed again
+fn inline_call(
+    graph: &mut Graph,
+    types: &mut TypeCache,
+    call: NodeIndex<u32>,
+    function: &Graph,
+) -> Option<Vec<NodeIndex<u32>>> {
+    let args = graph.arguments(call).ok()?;
+
+    // The parameters are replaced by the arguments, which must have the declared types
+    let mut mapping = HashMap::default();
+    let mut ret = None;
+    for (index, node) in function.nodes() {
+        match *node {
+            Node::Parameter(port, ty) => {
+                let arg = *args.get(port as usize)?;
+                match types.get(arg) {
+                    Some(&Ok(arg_ty)) if arg_ty == ty => {}
+                    _ => return None,
+                }
+
+                mapping.insert(index, arg);
+            }
+            Node::Return => ret = Some(index),
+            _ => {}
+        }
+    }
+
+    let ret = ret?;
+    let value = function.inputs_of(ret).first().map(|edge| edge.from);
+    if value.is_none() && graph.outputs_of(call).next().is_some() {
+        return None;
+    }
+
+    let mut calls = Vec::new();
+    for index in function.toposort().ok()? {
+        let node = &function[index];
+        match *node {
+            Node::Parameter(..) | Node::Return => continue,
+            Node::Call(_) => {
+                let copy = graph.add_node(node.clone());
+                calls.push(copy);
+                mapping.insert(index, copy);
+            }
+            _ => {
+                let copy = graph.add_node(node.clone());
+                mapping.insert(index, copy);
+            }
+        }
+    }
+
+    for edge in function.edges() {
+        match function[edge.to] {
+            Node::Parameter(..) | Node::Return => continue,
+            _ => {
+                graph.add_edge(mapping[&edge.from], mapping[&edge.to], edge.port);
+            }
+        }
+    }
+
+    if let Some(value) = value {
+        let value = mapping[&value];
+        let outputs: Vec<_> = graph.outputs_of(call).collect();
+        for edge in outputs {
+            graph.add_edge(value, edge.to, edge.port);
+        }
+    }
+
+    // The nodes using the result of the call could not be typed before
+    let invalidated = types.invalidate(graph, call);
+    graph.remove_node(call);
+
+    let copies: Vec<_> = mapping.values().cloned().collect();
+    types.update(
+        graph,
+        copies
+            .into_iter()
+            .chain(invalidated)
+            .filter(|&index| index != call),
+    );
+
+    Some(calls)
+}
+
+/// Inline the calls of a graph to the selected functions, including the calls
+/// copied from their bodies unless they are recursive, typing the graph with `settings`
+fn inline_graph(
+    graph: &mut Graph,
+    functions: &[Graph],
+    selected: &[bool],
+    caller: Option<FunctionRef>,
+    settings: &Settings,
+) -> usize {
+    let order = match graph.toposort() {
+        Ok(order) => order,
+        Err(_) => return 0,
+    };
+
+    // The types are only inferred once, then updated as the calls are inlined
+    let mut types = TypeCache::new(graph, settings.clone());
+
+    // The arguments of a call must be inlined before it, so they can be typed
+    let mut queue: VecDeque<_> = {
+        order
+            .into_iter()
+            .filter(|&index| match graph[index] {
+                Node::Call(_) => true,
+                _ => false,
+            })
+            .map(|index| (index, caller.into_iter().collect::<Vec<_>>()))
+            .collect()
+    };
+
+    let mut count = 0;
+    while let Some((index, path)) = queue.pop_front() {
+        let function = match graph[index] {
+            Node::Call(function) => function,
+            _ => continue,
+        };
+
+        if path.contains(&function) || !selected.get(function.0).cloned().unwrap_or(false) {
+            continue;
+        }
+
+        if let Some(calls) = inline_call(graph, &mut types, index, &functions[function.0]) {
+            let mut path = path;
+            path.push(function);
+            for call in calls.into_iter().rev() {
+                queue.push_front((call, path.clone()));
+            }
+
+            count += 1;
+        }
+    }
+
+    count
+}
+
+/// Replace the `Call` nodes of all the graphs of a module with the body of the
+/// function they call, returning the number of inlined calls
+///
+/// A function is inlined if its hint is `Always`, or if its hint is `Auto` and it is
+/// either called once or smaller than `settings.inlining.max_nodes`. Only the functions
+/// returning a value without side effects are inlined, and the calls with arguments
+/// not matching the parameters of the function are left for the builder to report.
+/// The functions are kept in the module even if they are not called anymore
+///
+/// The arguments are typed for the stage of the entry point holding the call,
+/// `settings.mod_type` being used for the main graph and the functions
+pub fn inline_functions(module: &mut Module, settings: &Settings) -> usize {
+    let functions = module.functions.clone();
+    let uses = count_calls(module);
+
+    let selected: Vec<_> = {
+        functions
+            .iter()
+            .enumerate()
+            .map(|(index, function)| {
+                let reference = FunctionRef(index);
+                is_inlinable(function)
+                    && match module.inline_hint(reference) {
+                        InlineHint::Never => false,
+                        InlineHint::Always => true,
+                        InlineHint::Auto => {
+                            uses.get(&reference) == Some(&1)
+                                || function.node_count() <= settings.inlining.max_nodes
+                        }
+                    }
+            })
+            .collect()
+    };
+
+    let mut count = inline_graph(&mut module.main, &functions, &selected, None, settings);
+    for entry in &mut module.entry_points {
+        let settings = Settings {
+            mod_type: entry.mod_type,
+            ..settings.clone()
+        };
+
+        count += inline_graph(&mut entry.graph, &functions, &selected, None, &settings);
+    }
+
+    for (index, graph) in module.functions.iter_mut().enumerate() {
+        let caller = Some(FunctionRef(index));
+        count += inline_graph(graph, &functions, &selected, caller, settings);
+    }
+
+    count
+}
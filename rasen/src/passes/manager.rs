@@ -0,0 +1,305 @@
+//! Scheduling of the passes run on the graphs and on the built module
+
+use std::time::{Duration, Instant};
+
+use rspirv::mr::Module as SpirvModule;
+
+use super::{
+    eliminate_common_subexpressions, fold_constants, inline_functions, prune,
+    side_effecting_functions, simplify, RewriteTable,
+};
+use builder::{ModuleBuilder, OptLevel, Settings};
+use errors::*;
+use graph::Graph;
+use module::{FunctionRef, Module};
+use node::Node;
+
+/// A pass transforming the graphs of a module before they're built
+pub trait GraphPass {
+    /// The name of the pass, as reported in the timings
+    fn name(&self) -> &'static str;
+
+    /// Run the pass on a graph, returning whether it changed
+    fn run_graph(&mut self, graph: &mut Graph, settings: &Settings) -> Result<bool>;
+
+    /// Run the pass on a module, returning whether it changed
+    ///
+    /// By default, the pass runs on each graph of the module. The graphs of the
+    /// additional entry points get the settings of their own stage, the main graph
+    /// and the functions get `settings` unchanged
+    fn run_module(&mut self, module: &mut Module, settings: &Settings) -> Result<bool> {
+        let mut changed = self.run_graph(&mut module.main, settings)?;
+        for entry in &mut module.entry_points {
+            let settings = Settings {
+                mod_type: entry.mod_type,
+                ..settings.clone()
+            };
+
+            changed |= self.run_graph(&mut entry.graph, &settings)?;
+        }
+
+        for graph in &mut module.functions {
+            changed |= self.run_graph(graph, settings)?;
+        }
+
+        Ok(changed)
+    }
+}
+
+/// A pass transforming a built SPIR-V module
+pub trait SpirvPass {
+    /// The name of the pass, as reported in the timings
+    fn name(&self) -> &'static str;
+
+    /// Run the pass on a module, returning whether it changed
+    fn run(&mut self, module: &mut SpirvModule, settings: &Settings) -> Result<bool>;
+}
+
+/// Evaluate the nodes whose arguments are all constant
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FoldConstants;
+
+impl GraphPass for FoldConstants {
+    fn name(&self) -> &'static str {
+        "fold-constants"
+    }
+
+    fn run_graph(&mut self, graph: &mut Graph, _: &Settings) -> Result<bool> {
+        Ok(fold_constants(graph) > 0)
+    }
+}
+
+/// Merge the nodes computing the same value
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EliminateCommonSubexpressions;
+
+impl GraphPass for EliminateCommonSubexpressions {
+    fn name(&self) -> &'static str {
+        "eliminate-common-subexpressions"
+    }
+
+    fn run_graph(&mut self, graph: &mut Graph, _: &Settings) -> Result<bool> {
+        Ok(eliminate_common_subexpressions(graph)? > 0)
+    }
+}
+
+/// Remove the nodes that don't contribute to any output of their graph
+///
+/// The uniforms only read by the removed nodes are dropped from the block,
+/// and the offsets of the other members must be read from the reflection
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EliminateDeadCode;
+
+impl GraphPass for EliminateDeadCode {
+    fn name(&self) -> &'static str {
+        "eliminate-dead-code"
+    }
+
+    fn run_graph(&mut self, graph: &mut Graph, _: &Settings) -> Result<bool> {
+        Ok(eliminate_dead_code(graph, &[]))
+    }
+
+    fn run_module(&mut self, module: &mut Module, _: &Settings) -> Result<bool> {
+        let effectful = side_effecting_functions(module);
+
+        let mut changed = false;
+        for graph in module.graphs_mut() {
+            changed |= eliminate_dead_code(graph, &effectful);
+        }
+
+        Ok(changed)
+    }
+}
+
+/// Prune a graph, keeping all its outputs and the calls to the `effectful` functions
+fn eliminate_dead_code(graph: &mut Graph, effectful: &[FunctionRef]) -> bool {
+    let outputs: Vec<_> = {
+        graph
+            .nodes()
+            .filter_map(|(_, node)| match *node {
+                Node::Output(location, ..) | Node::ArrayedOutput(location, ..) => Some(location),
+                _ => None,
+            })
+            .collect()
+    };
+
+    !prune(graph, &outputs, effectful).is_empty()
+}
+
+/// Inline the calls to the small or single-use functions, using the inlining settings
+///
+/// Functions are only available in modules, so this pass does nothing on a single graph
+#[derive(Copy, Clone, Debug, Default)]
+pub struct InlineFunctions;
+
+impl GraphPass for InlineFunctions {
+    fn name(&self) -> &'static str {
+        "inline-functions"
+    }
+
+    fn run_graph(&mut self, _: &mut Graph, _: &Settings) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn run_module(&mut self, module: &mut Module, settings: &Settings) -> Result<bool> {
+        Ok(inline_functions(module, settings) > 0)
+    }
+}
+
+/// Apply the rules of a rewrite table to the nodes
+#[derive(Default)]
+pub struct Simplify {
+    /// The rules applied to the nodes, the builtin ones by default
+    pub table: RewriteTable,
+}
+
+impl GraphPass for Simplify {
+    fn name(&self) -> &'static str {
+        "simplify"
+    }
+
+    fn run_graph(&mut self, graph: &mut Graph, settings: &Settings) -> Result<bool> {
+        Ok(simplify(graph, &self.table, settings)? > 0)
+    }
+}
+
+/// The time spent running a pass
+#[derive(Clone, Debug)]
+pub struct PassTiming {
+    /// The name of the pass
+    pub name: &'static str,
+    /// The duration of the run
+    pub duration: Duration,
+    /// Whether the pass changed the graphs or the module
+    pub changed: bool,
+}
+
+/// Run a pass and record the time it took
+fn timed(
+    timings: &mut Vec<PassTiming>,
+    name: &'static str,
+    run: impl FnOnce() -> Result<bool>,
+) -> Result<()> {
+    let start = Instant::now();
+    let changed = run()?;
+    timings.push(PassTiming {
+        name,
+        duration: start.elapsed(),
+        changed,
+    });
+
+    Ok(())
+}
+
+/// An ordered list of passes, run on the graphs before building them
+/// and on the module once it's built
+#[derive(Default)]
+pub struct PassManager {
+    graph_passes: Vec<Box<dyn GraphPass>>,
+    spirv_passes: Vec<Box<dyn SpirvPass>>,
+    timings: Vec<PassTiming>,
+}
+
+impl PassManager {
+    /// Create a manager without any pass
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a manager running the passes selected by the optimization level
+    /// and the inlining settings
+    pub fn from_settings(settings: &Settings) -> Self {
+        let mut manager = Self::new();
+        if settings.inlining.enabled || settings.opt_level == OptLevel::Full {
+            manager.add_graph_pass(InlineFunctions);
+        }
+
+        if settings.opt_level == OptLevel::Full {
+            manager.add_graph_pass(Simplify::default());
+        }
+
+        if settings.opt_level != OptLevel::None {
+            manager.add_graph_pass(FoldConstants);
+            manager.add_graph_pass(EliminateCommonSubexpressions);
+            manager.add_graph_pass(EliminateDeadCode);
+        }
+
+        manager
+    }
+
+    /// Add a pass run on the graphs, after the graph passes already added
+    pub fn add_graph_pass(&mut self, pass: impl GraphPass + 'static) {
+        self.graph_passes.push(Box::new(pass));
+    }
+
+    /// Add a pass run on the built module, after the SPIR-V passes already added
+    pub fn add_spirv_pass(&mut self, pass: impl SpirvPass + 'static) {
+        self.spirv_passes.push(Box::new(pass));
+    }
+
+    /// Get the time spent in each pass, in the order they ran
+    pub fn timings(&self) -> &[PassTiming] {
+        &self.timings
+    }
+
+    /// Run the graph passes on a graph
+    pub fn run_graph(&mut self, graph: &mut Graph, settings: &Settings) -> Result<()> {
+        for pass in &mut self.graph_passes {
+            timed(&mut self.timings, pass.name(), || {
+                pass.run_graph(graph, settings)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the graph passes on all the graphs of a module
+    pub fn run_module(&mut self, module: &mut Module, settings: &Settings) -> Result<()> {
+        for pass in &mut self.graph_passes {
+            timed(&mut self.timings, pass.name(), || {
+                pass.run_module(module, settings)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the SPIR-V passes on a built module
+    pub fn run_spirv(&mut self, module: &mut SpirvModule, settings: &Settings) -> Result<()> {
+        for pass in &mut self.spirv_passes {
+            timed(&mut self.timings, pass.name(), || {
+                pass.run(module, settings)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Run the passes on a copy of a graph and build it
+    ///
+    /// The optimization level and inlining settings are ignored, only the passes
+    /// of this manager are run
+    pub fn build_graph(&mut self, graph: &Graph, settings: Settings) -> Result<SpirvModule> {
+        let mut graph = graph.clone();
+        self.run_graph(&mut graph, &settings)?;
+
+        let settings = settings.without_passes();
+        let mut module = ModuleBuilder::from_graph(&graph, settings.clone())?.build()?;
+        self.run_spirv(&mut module, &settings)?;
+        Ok(module)
+    }
+
+    /// Run the passes on a copy of a module and build it
+    ///
+    /// The optimization level and inlining settings are ignored, only the passes
+    /// of this manager are run
+    pub fn build_module(&mut self, module: &Module, settings: Settings) -> Result<SpirvModule> {
+        let mut module = module.clone();
+        self.run_module(&mut module, &settings)?;
+
+        let settings = settings.without_passes();
+        let mut spirv = ModuleBuilder::from_module(&module, settings.clone())?.build()?;
+        self.run_spirv(&mut spirv, &settings)?;
+        Ok(spirv)
+    }
+}
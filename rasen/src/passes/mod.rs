@@ -0,0 +1,18 @@
+//! Optimization passes transforming a graph before it's built
+
+mod cse;
+mod fold;
+mod inline;
+mod manager;
+mod prune;
+mod simplify;
+
+pub use self::cse::eliminate_common_subexpressions;
+pub use self::fold::fold_constants;
+pub use self::inline::inline_functions;
+pub use self::manager::{
+    EliminateCommonSubexpressions, EliminateDeadCode, FoldConstants, GraphPass, InlineFunctions,
+    PassManager, PassTiming, Simplify, SpirvPass,
+};
+pub use self::prune::{prune, side_effecting_functions, PrunedInterface, PrunedUniform};
+pub use self::simplify::{simplify, Rewrite, RewriteTable, Rule};
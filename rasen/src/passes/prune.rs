@@ -0,0 +1,237 @@
+//! Removal of the nodes not contributing to the required outputs of a graph
+
+use fnv::FnvHashSet as HashSet;
+use spirv_headers::StorageClass;
+
+use builder::{InterfaceVariable, SamplerVariable, VariableKey};
+use graph::Graph;
+use module::{FunctionRef, Module};
+use node::{Node, VariableName};
+use types::TypeName;
+
+/// A member removed from the uniform block
+///
+/// Unlike the reflected members, it has no offset as the layout of the block
+/// is only known once the pruned graph is built: the remaining members are
+/// renumbered and packed, so their offsets must be read from the reflection
+/// of the pruned module
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrunedUniform {
+    /// The location of the uniform node
+    pub location: u32,
+    /// The type of the member
+    pub type_name: &'static TypeName,
+    /// The name of the member
+    pub name: Option<String>,
+}
+
+/// The variables declared only by the nodes removed from a graph,
+/// described with the types of the module reflection
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PrunedInterface {
+    /// The number of removed nodes
+    pub nodes: usize,
+    /// The inputs no longer read by the graph, the length of arrayed inputs is unknown
+    pub inputs: Vec<InterfaceVariable>,
+    /// The outputs that were not required, the length of arrayed outputs is unknown
+    pub outputs: Vec<InterfaceVariable>,
+    /// The members removed from the uniform block
+    pub uniforms: Vec<PrunedUniform>,
+    /// The samplers no longer read by the graph
+    pub samplers: Vec<SamplerVariable>,
+}
+
+impl PrunedInterface {
+    /// Check if nothing was removed from the graph
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    fn add_variable(&mut self, node: &Node) {
+        match *node {
+            Node::Input(location, type_name, ref name, _)
+            | Node::ArrayedInput(location, type_name, ref name, _) => {
+                self.inputs.push(name.reflect(location, type_name, None));
+            }
+            Node::Output(location, type_name, ref name, _)
+            | Node::ArrayedOutput(location, type_name, ref name, _) => {
+                self.outputs.push(name.reflect(location, type_name, None));
+            }
+            Node::Uniform(location, type_name @ &TypeName::Sampler(..), ref name) => {
+                self.samplers.push(SamplerVariable {
+                    location,
+                    type_name,
+                    name: name.reflect_name(),
+                });
+            }
+            Node::Uniform(location, type_name, ref name) => {
+                self.uniforms.push(PrunedUniform {
+                    location,
+                    type_name,
+                    name: name.reflect_name(),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Get the variable declared by a node
+fn variable(node: &Node) -> Option<VariableKey> {
+    match *node {
+        Node::Input(location, _, ref name, _) | Node::ArrayedInput(location, _, ref name, _) => {
+            Some(name.variable_key(StorageClass::Input, location))
+        }
+        Node::Output(location, _, ref name, _) | Node::ArrayedOutput(location, _, ref name, _) => {
+            Some(name.variable_key(StorageClass::Output, location))
+        }
+        Node::Uniform(location, &TypeName::Sampler(..), _) => {
+            Some((StorageClass::UniformConstant, location, None))
+        }
+        Node::Uniform(location, _, _) => Some((StorageClass::Uniform, location, None)),
+        _ => None,
+    }
+}
+
+/// Check if a node writes to a variable or synchronizes the invocations
+fn has_side_effects(node: &Node) -> bool {
+    match *node {
+        Node::Output(..)
+        | Node::ArrayedOutput(..)
+        | Node::EmitVertex
+        | Node::EndPrimitive
+        | Node::Barrier => true,
+        _ => false,
+    }
+}
+
+/// Get the functions invoked by a node
+fn callees(node: &Node) -> Vec<FunctionRef> {
+    match *node {
+        Node::Call(callee) => vec![callee],
+        Node::Loop(cond, body) => vec![cond, body],
+        _ => Vec::new(),
+    }
+}
+
+/// Find the functions of a module having side effects: the ones using
+/// side-effecting nodes, and the ones calling them
+pub fn side_effecting_functions(module: &Module) -> Vec<FunctionRef> {
+    let mut res: Vec<_> = {
+        module
+            .functions
+            .iter()
+            .map(|graph| graph.nodes().any(|(_, node)| has_side_effects(node)))
+            .collect()
+    };
+
+    loop {
+        let mut changed = false;
+        for (index, graph) in module.functions.iter().enumerate() {
+            let calls_effectful = graph.nodes().any(|(_, node)| {
+                callees(node)
+                    .into_iter()
+                    .any(|callee| res.get(callee.0).cloned().unwrap_or(false))
+            });
+
+            if calls_effectful && !res[index] {
+                res[index] = true;
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    (0..res.len())
+        .map(FunctionRef)
+        .filter(|function| res[function.0])
+        .collect()
+}
+
+/// Check if a node must be kept even if its result is not used
+fn is_root(node: &Node, required_outputs: &[u32], effectful: &[FunctionRef]) -> bool {
+    match *node {
+        Node::Output(location, _, ref name, _) | Node::ArrayedOutput(location, _, ref name, _) => {
+            match *name {
+                VariableName::BuiltIn(_) => true,
+                _ => required_outputs.contains(&location),
+            }
+        }
+        Node::Call(..) | Node::Loop(..) => callees(node)
+            .iter()
+            .any(|callee| effectful.contains(callee)),
+        Node::Parameter(..)
+        | Node::Return
+        | Node::EmitVertex
+        | Node::EndPrimitive
+        | Node::Barrier => true,
+        _ => false,
+    }
+}
+
+/// Remove the nodes that don't contribute to the required outputs of a graph,
+/// returning the interface that was only declared by the removed nodes
+///
+/// The outputs are identified by their location, the builtin outputs are always
+/// required. The side-effecting nodes, the calls to the `effectful` functions (as
+/// found by `side_effecting_functions`) and the parameters of function graphs are kept,
+/// along with everything they depend on. Building the pruned graph declares
+/// none of the variables listed in the returned interface
+pub fn prune(
+    graph: &mut Graph,
+    required_outputs: &[u32],
+    effectful: &[FunctionRef],
+) -> PrunedInterface {
+    let mut live = HashSet::default();
+    let mut stack: Vec<_> = {
+        graph
+            .nodes()
+            .filter(|&(_, node)| is_root(node, required_outputs, effectful))
+            .map(|(index, _)| index)
+            .collect()
+    };
+
+    while let Some(index) = stack.pop() {
+        if live.insert(index) {
+            stack.extend(graph.inputs_of(index).into_iter().map(|edge| edge.from));
+        }
+    }
+
+    let dead: Vec<_> = {
+        graph
+            .nodes()
+            .map(|(index, _)| index)
+            .filter(|index| !live.contains(index))
+            .collect()
+    };
+
+    let removed: Vec<_> = {
+        dead.into_iter()
+            .filter_map(|index| graph.remove_node(index))
+            .collect()
+    };
+
+    // A variable is only pruned if no remaining node declares it
+    let mut declared: HashSet<_> = graph
+        .nodes()
+        .filter_map(|(_, node)| variable(node))
+        .collect();
+
+    let mut pruned = PrunedInterface {
+        nodes: removed.len(),
+        ..PrunedInterface::default()
+    };
+
+    for node in &removed {
+        if let Some(key) = variable(node) {
+            if declared.insert(key) {
+                pruned.add_variable(node);
+            }
+        }
+    }
+
+    pruned
+}
@@ -0,0 +1,337 @@
+//! Peephole rewrites of the nodes computing the same value as a simpler expression
+
+use std::convert::TryFrom;
+
+use petgraph::graph::NodeIndex;
+
+use builder::Settings;
+use errors::*;
+use graph::Graph;
+use node::Node;
+use types::{Scalar, TypeName};
+
+/// How a rule simplifies a node
+#[derive(Debug)]
+pub enum Rewrite {
+    /// Replace the uses of the node with another node of the graph, and remove it
+    Forward(NodeIndex<u32>),
+    /// Replace the node with a new node, taking the given arguments in port order
+    Replace(Node, Vec<NodeIndex<u32>>),
+}
+
+/// A rewrite rule, returning how to simplify a node if it applies to it
+pub type Rule = Box<dyn Fn(&Graph, NodeIndex<u32>, &Settings) -> Option<Rewrite>>;
+
+/// An ordered list of rewrite rules, the first rule applying to a node is used
+pub struct RewriteTable {
+    rules: Vec<Rule>,
+}
+
+impl RewriteTable {
+    /// Create a table holding the builtin rules
+    ///
+    /// The rules never change the results of floating-point operations, unless
+    /// `fast_math` is set in the settings: `x * 1`, `x / 1`, `x - 0`, `x + -0`,
+    /// `pow(x, 1)` are replaced with `x`, `pow(x, 2)` with `x * x`, `x * 2`
+    /// with `x + x`, and integer multiplications by zero with zero. With
+    /// `fast_math`, `x + 0` and `normalize(normalize(x))` are also simplified,
+    /// and `length(x) * length(x)` is replaced with `dot(x, x)`
+    pub fn new() -> Self {
+        let mut table = Self::empty();
+        table.add_rule(multiply_by_one);
+        table.add_rule(multiply_by_zero);
+        table.add_rule(multiply_by_two);
+        table.add_rule(divide_by_one);
+        table.add_rule(add_zero);
+        table.add_rule(subtract_zero);
+        table.add_rule(small_power);
+        table.add_rule(normalize_twice);
+        table.add_rule(squared_length);
+        table
+    }
+
+    /// Create a table without any rule
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Add a rule to the table, tried after the rules already in it
+    pub fn add_rule<F>(&mut self, rule: F)
+    where
+        F: Fn(&Graph, NodeIndex<u32>, &Settings) -> Option<Rewrite> + 'static,
+    {
+        self.rules.push(Box::new(rule));
+    }
+
+    fn rewrite(
+        &self,
+        graph: &Graph,
+        index: NodeIndex<u32>,
+        settings: &Settings,
+    ) -> Option<Rewrite> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule(graph, index, settings))
+            .next()
+    }
+}
+
+impl Default for RewriteTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the value of a scalar constant node
+fn scalar_constant(graph: &Graph, index: NodeIndex<u32>) -> Option<Scalar> {
+    match graph[index] {
+        Node::Constant(ref value) => match *value.components()?.as_slice() {
+            [scalar] => Some(scalar),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Check if a scalar is a number equal to `value`, positive zero for floats
+fn is_value(scalar: Scalar, value: i8) -> bool {
+    match scalar {
+        Scalar::Int(val) => val == i64::from(value),
+        Scalar::UInt(val) => u64::try_from(value).ok() == Some(val),
+        Scalar::Float(val) => val.to_bits() == f64::from(value).to_bits(),
+        Scalar::Bool(_) => false,
+    }
+}
+
+fn is_negative_zero(scalar: Scalar) -> bool {
+    match scalar {
+        Scalar::Float(val) => val.to_bits() == (-0f64).to_bits(),
+        _ => false,
+    }
+}
+
+fn is_integer(scalar: Scalar) -> bool {
+    match scalar {
+        Scalar::Int(_) | Scalar::UInt(_) => true,
+        _ => false,
+    }
+}
+
+/// Get the two arguments of a binary node
+fn binary(graph: &Graph, index: NodeIndex<u32>) -> Option<(NodeIndex<u32>, NodeIndex<u32>)> {
+    match *graph.arguments(index).ok()?.as_slice() {
+        [lhs, rhs] => Some((lhs, rhs)),
+        _ => None,
+    }
+}
+
+/// Find an argument of a commutative binary node that is a scalar constant
+/// matching `pred`, returning the other argument along with the constant
+fn commuted(
+    graph: &Graph,
+    index: NodeIndex<u32>,
+    pred: impl Fn(Scalar) -> bool,
+) -> Option<(NodeIndex<u32>, NodeIndex<u32>)> {
+    let (lhs, rhs) = binary(graph, index)?;
+    let matches = |index| scalar_constant(graph, index).map_or(false, &pred);
+
+    if matches(rhs) {
+        Some((lhs, rhs))
+    } else if matches(lhs) {
+        Some((rhs, lhs))
+    } else {
+        None
+    }
+}
+
+/// Get the right argument of a binary node and its value if it's a scalar constant
+fn constant_rhs(graph: &Graph, index: NodeIndex<u32>) -> Option<(NodeIndex<u32>, Scalar)> {
+    let (lhs, rhs) = binary(graph, index)?;
+    Some((lhs, scalar_constant(graph, rhs)?))
+}
+
+fn multiply_by_one(graph: &Graph, index: NodeIndex<u32>, _: &Settings) -> Option<Rewrite> {
+    match graph[index] {
+        Node::Multiply => {
+            let (value, _) = commuted(graph, index, |val| is_value(val, 1))?;
+            Some(Rewrite::Forward(value))
+        }
+        _ => None,
+    }
+}
+
+/// Integer multiplications require both arguments to have the same type
+fn multiply_by_zero(graph: &Graph, index: NodeIndex<u32>, _: &Settings) -> Option<Rewrite> {
+    match graph[index] {
+        Node::Multiply => {
+            let (_, zero) = commuted(graph, index, |val| is_integer(val) && is_value(val, 0))?;
+            Some(Rewrite::Forward(zero))
+        }
+        _ => None,
+    }
+}
+
+fn multiply_by_two(graph: &Graph, index: NodeIndex<u32>, _: &Settings) -> Option<Rewrite> {
+    match graph[index] {
+        Node::Multiply => {
+            let (value, _) = commuted(graph, index, |val| is_value(val, 2))?;
+            Some(Rewrite::Replace(Node::Add, vec![value, value]))
+        }
+        _ => None,
+    }
+}
+
+fn divide_by_one(graph: &Graph, index: NodeIndex<u32>, _: &Settings) -> Option<Rewrite> {
+    match graph[index] {
+        Node::Divide => match constant_rhs(graph, index)? {
+            (value, rhs) if is_value(rhs, 1) => Some(Rewrite::Forward(value)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `-0.0 + 0.0` is `0.0`, so only the additions of negative zeros are exact
+fn add_zero(graph: &Graph, index: NodeIndex<u32>, settings: &Settings) -> Option<Rewrite> {
+    match graph[index] {
+        Node::Add => {
+            let (value, _) = commuted(graph, index, |val| {
+                is_negative_zero(val)
+                    || (is_value(val, 0) && (is_integer(val) || settings.fast_math))
+            })?;
+            Some(Rewrite::Forward(value))
+        }
+        _ => None,
+    }
+}
+
+fn subtract_zero(graph: &Graph, index: NodeIndex<u32>, settings: &Settings) -> Option<Rewrite> {
+    match graph[index] {
+        Node::Subtract => match constant_rhs(graph, index)? {
+            (value, rhs) if is_value(rhs, 0) || (is_negative_zero(rhs) && settings.fast_math) => {
+                Some(Rewrite::Forward(value))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `pow` is undefined for negative bases, the rewrites are defined for all of them
+fn small_power(graph: &Graph, index: NodeIndex<u32>, _: &Settings) -> Option<Rewrite> {
+    match graph[index] {
+        Node::Pow => match constant_rhs(graph, index)? {
+            (value, exp) if is_value(exp, 1) => Some(Rewrite::Forward(value)),
+            (value, exp) if is_value(exp, 2) => {
+                Some(Rewrite::Replace(Node::Multiply, vec![value, value]))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn normalize_twice(graph: &Graph, index: NodeIndex<u32>, settings: &Settings) -> Option<Rewrite> {
+    if !settings.fast_math {
+        return None;
+    }
+
+    match graph[index] {
+        Node::Normalize => match *graph.arguments(index).ok()?.as_slice() {
+            [inner] => match graph[inner] {
+                Node::Normalize => Some(Rewrite::Forward(inner)),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn squared_length(graph: &Graph, index: NodeIndex<u32>, settings: &Settings) -> Option<Rewrite> {
+    if !settings.fast_math {
+        return None;
+    }
+
+    match graph[index] {
+        Node::Multiply => {}
+        _ => return None,
+    }
+
+    let length_arg = |index| match graph[index] {
+        Node::Length => match *graph.arguments(index).ok()?.as_slice() {
+            [arg] => Some(arg),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let (lhs, rhs) = binary(graph, index)?;
+    let value = length_arg(lhs)?;
+    if length_arg(rhs)? != value {
+        return None;
+    }
+
+    // The length of a scalar is its absolute value
+    let types = graph.infer_types_with(settings.clone());
+    match types.get(&value) {
+        Some(&Ok(&TypeName::Vec(..))) => Some(Rewrite::Replace(Node::Dot, vec![value, value])),
+        Some(&Ok(ty)) if ty.is_float() => {
+            Some(Rewrite::Replace(Node::Multiply, vec![value, value]))
+        }
+        _ => None,
+    }
+}
+
+fn apply(graph: &mut Graph, index: NodeIndex<u32>, rewrite: Rewrite) {
+    match rewrite {
+        Rewrite::Forward(value) => {
+            let outputs: Vec<_> = graph.outputs_of(index).collect();
+            for edge in outputs {
+                graph.add_edge(value, edge.to, edge.port);
+            }
+
+            graph.remove_node(index);
+        }
+        Rewrite::Replace(node, args) => {
+            for edge in graph.inputs_of(index) {
+                graph.remove_edge(edge.index);
+            }
+
+            graph.replace_node(index, node);
+            for (port, arg) in (0..).zip(args) {
+                graph.add_edge(arg, index, port);
+            }
+        }
+    }
+}
+
+/// Apply the rules of a table to the nodes of a graph until none of them
+/// applies anymore, returning the number of rewritten nodes
+///
+/// The nodes are visited after their arguments, so a rewrite may enable a rule on
+/// the nodes using the rewritten node. The rules must simplify the graph, a rule
+/// forwarding a node to itself or undoing another rule would never terminate.
+/// The arguments only used by the rewritten nodes are left in the graph, and can
+/// be removed with `prune`
+pub fn simplify(graph: &mut Graph, table: &RewriteTable, settings: &Settings) -> Result<usize> {
+    let mut count = 0;
+    loop {
+        let mut changed = false;
+        for index in graph.toposort()? {
+            if !graph.contains_node(index) {
+                continue;
+            }
+
+            if let Some(rewrite) = table.rewrite(graph, index, settings) {
+                apply(graph, index, rewrite);
+                changed = true;
+                count += 1;
+            }
+        }
+
+        if !changed {
+            return Ok(count);
+        }
+    }
+}
@@ -3,14 +3,15 @@
 use std::convert::TryFrom;
 
 pub use builder::*;
-pub use graph::Graph;
+pub use graph::{Edge, Graph};
 pub use module::Module;
 pub use node::*;
 pub use types::*;
 
-pub use petgraph::graph::NodeIndex;
-pub use spirv_headers::{BuiltIn, ExecutionModel as ShaderType};
+pub use petgraph::graph::{EdgeIndex, NodeIndex};
+pub use spirv_headers::{BuiltIn, Capability, ExecutionModel as ShaderType, SourceLanguage};
 
+use builder::Reflection;
 use errors::{Error, Result};
 
 /// Transform a node graph to SPIR-V bytecode
@@ -30,3 +31,16 @@ where
     let program = ModuleBuilder::try_from((graph, settings))?;
     program.into_assembly()
 }
+
+/// Transform a node graph to SPIR-V bytecode, along with a description of its interface
+pub fn build_program_with_reflection<'a, I, S>(
+    graph: &'a I,
+    settings: S,
+) -> Result<(Vec<u8>, Reflection)>
+where
+    ModuleBuilder: TryFrom<(&'a I, S), Error = Error>,
+{
+    let program = ModuleBuilder::try_from((graph, settings))?;
+    let reflection = program.reflection();
+    Ok((program.into_binary()?, reflection))
+}
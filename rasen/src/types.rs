@@ -1,3 +1,6 @@
+pub use half::f16;
+#[cfg(feature = "serialize")]
+use serde::{Serialize, Serializer};
 pub use spirv_headers::Dim;
 use spirv_headers::StorageClass;
 use std::fmt;
@@ -8,10 +11,10 @@ pub enum TypeName {
     Void,
     /// Basic boolean type
     Bool,
-    /// Integer type, signed or not
-    Int(bool /* is_signed */),
-    /// Floating-point type, with single or double precision
-    Float(bool /* is_double */),
+    /// Integer type, signed or not, with a width of 16, 32 or 64 bits
+    Int(bool /* is_signed */, u32 /* width */),
+    /// Floating-point type, with half, single or double precision (16, 32 or 64 bits)
+    Float(u32 /* width */),
     /// Vector type of n components of given scalar type
     Vec(
         u32,               /* component_count */
@@ -32,32 +35,41 @@ pub enum TypeName {
     _Pointer(&'static TypeName, StorageClass),
 }
 
+/// A component of a constant value, stored with enough precision for every width
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Scalar {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+}
+
 include!(concat!(env!("OUT_DIR"), "/types.rs"));
 
 impl TypeName {
     pub const VOID: &'static Self = &TypeName::Void;
     pub const BOOL: &'static Self = &TypeName::Bool;
-    pub const INT: &'static Self = &TypeName::Int(true);
-    pub const UINT: &'static Self = &TypeName::Int(false);
-    pub const FLOAT: &'static Self = &TypeName::Float(false);
-    pub const DOUBLE: &'static Self = &TypeName::Float(true);
-
-    pub(crate) const FLOAT_PTR_UNI: &'static Self =
-        &TypeName::_Pointer(Self::FLOAT, StorageClass::Uniform);
-    pub(crate) const FLOAT_PTR_FUN: &'static Self =
-        &TypeName::_Pointer(Self::FLOAT, StorageClass::Function);
+    pub const INT16: &'static Self = &TypeName::Int(true, 16);
+    pub const UINT16: &'static Self = &TypeName::Int(false, 16);
+    pub const INT: &'static Self = &TypeName::Int(true, 32);
+    pub const UINT: &'static Self = &TypeName::Int(false, 32);
+    pub const INT64: &'static Self = &TypeName::Int(true, 64);
+    pub const UINT64: &'static Self = &TypeName::Int(false, 64);
+    pub const HALF: &'static Self = &TypeName::Float(16);
+    pub const FLOAT: &'static Self = &TypeName::Float(32);
+    pub const DOUBLE: &'static Self = &TypeName::Float(64);
 
     #[inline]
     pub(crate) fn is_integer(&self) -> bool {
         match *self {
-            TypeName::Int(_) => true,
+            TypeName::Int(..) => true,
             _ => false,
         }
     }
     #[inline]
     pub(crate) fn is_signed(&self) -> bool {
         match *self {
-            TypeName::Int(true) => true,
+            TypeName::Int(true, _) => true,
             _ => false,
         }
     }
@@ -75,27 +87,92 @@ impl TypeName {
         self.is_integer() || self.is_float()
     }
 
+    /// Get the scalar type of a vector or matrix, or the type itself otherwise
+    #[inline]
+    pub(crate) fn scalar(&'static self) -> &'static Self {
+        match *self {
+            TypeName::Vec(_, ty) | TypeName::Mat(_, ty) => ty.scalar(),
+            _ => self,
+        }
+    }
+
+    /// Get the width in bits of a numerical scalar type, or the width of the components
+    /// of a vector or matrix
+    #[inline]
+    pub(crate) fn width(&'static self) -> Option<u32> {
+        match *self.scalar() {
+            TypeName::Int(_, width) | TypeName::Float(width) => Some(width),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub(crate) fn size(&self) -> u32 {
         match *self {
             TypeName::Void | TypeName::Sampler(..) | TypeName::_Pointer(..) => 0,
 
-            TypeName::Bool | TypeName::Int(_) | TypeName::Float(false) => 4,
+            TypeName::Bool => 4,
 
-            TypeName::Float(true) => 8,
+            TypeName::Int(_, width) | TypeName::Float(width) => width / 8,
 
             TypeName::Vec(len, ty) | TypeName::Mat(len, ty) => len * ty.size(),
         }
     }
+
+    /// Get the alignment of a member of this type in a std140 uniform block
+    #[inline]
+    pub(crate) fn std140_alignment(&self) -> u32 {
+        match *self {
+            TypeName::Vec(2, ty) => 2 * ty.size(),
+            TypeName::Vec(_, ty) => 4 * ty.size(),
+            TypeName::Mat(_, column) => column_stride(column),
+            _ => self.size(),
+        }
+    }
+
+    /// Get the size of a member of this type in a std140 uniform block
+    #[inline]
+    pub(crate) fn std140_size(&self) -> u32 {
+        match *self {
+            TypeName::Mat(columns, column) => columns * column_stride(column),
+            _ => self.size(),
+        }
+    }
+
+    /// Get the stride between the columns of a matrix in a std140 uniform block
+    #[inline]
+    pub(crate) fn matrix_stride(&self) -> Option<u32> {
+        match *self {
+            TypeName::Mat(_, column) => Some(column_stride(column)),
+            _ => None,
+        }
+    }
+}
+
+/// Matrices are laid out as arrays of columns, each aligned like a `vec4`
+fn column_stride(column: &TypeName) -> u32 {
+    let alignment = column.std140_alignment();
+    (alignment + 15) / 16 * 16
+}
+
+/// Types are serialized using their GLSL name
+#[cfg(feature = "serialize")]
+impl Serialize for TypeName {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{:?}", self))
+    }
 }
 
 fn print_type_prefix(f: &mut fmt::Formatter, ty: &TypeName) -> fmt::Result {
     match *ty {
         TypeName::Bool => write!(f, "b"),
-        TypeName::Int(true) => write!(f, "i"),
-        TypeName::Int(false) => write!(f, "u"),
-        TypeName::Float(false) => Ok(()),
-        TypeName::Float(true) => write!(f, "d"),
+        TypeName::Int(true, 32) => write!(f, "i"),
+        TypeName::Int(false, 32) => write!(f, "u"),
+        TypeName::Int(true, width) => write!(f, "i{}", width),
+        TypeName::Int(false, width) => write!(f, "u{}", width),
+        TypeName::Float(16) => write!(f, "h"),
+        TypeName::Float(32) => Ok(()),
+        TypeName::Float(64) => write!(f, "d"),
         _ => Err(fmt::Error),
     }
 }
@@ -105,10 +182,14 @@ impl fmt::Debug for TypeName {
         match *self {
             TypeName::Void => write!(f, "void"),
             TypeName::Bool => write!(f, "bool"),
-            TypeName::Int(true) => write!(f, "int"),
-            TypeName::Int(false) => write!(f, "uint"),
-            TypeName::Float(false) => write!(f, "float"),
-            TypeName::Float(true) => write!(f, "double"),
+            TypeName::Int(true, 32) => write!(f, "int"),
+            TypeName::Int(false, 32) => write!(f, "uint"),
+            TypeName::Int(true, width) => write!(f, "int{}_t", width),
+            TypeName::Int(false, width) => write!(f, "uint{}_t", width),
+            TypeName::Float(16) => write!(f, "half"),
+            TypeName::Float(32) => write!(f, "float"),
+            TypeName::Float(64) => write!(f, "double"),
+            TypeName::Float(width) => write!(f, "float{}_t", width),
 
             TypeName::Vec(len, scalar) => {
                 print_type_prefix(f, scalar)?;
@@ -116,12 +197,10 @@ impl fmt::Debug for TypeName {
             }
 
             TypeName::Mat(columns, vec) => match *vec {
-                TypeName::Vec(rows, &TypeName::Float(false)) if columns == rows => {
+                TypeName::Vec(rows, scalar @ &TypeName::Float(_)) if columns == rows => {
+                    print_type_prefix(f, scalar)?;
                     write!(f, "mat{}", rows)
                 }
-                TypeName::Vec(rows, &TypeName::Float(true)) if columns == rows => {
-                    write!(f, "dmat{}", rows)
-                }
                 _ => Err(fmt::Error),
             },
 
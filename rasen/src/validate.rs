@@ -0,0 +1,895 @@
+//! Structural validation of the modules emitted by the builder
+//!
+//! This is not a full SPIR-V validator, it only checks the rules the builder
+//! is most likely to break: ID definitions, operand types, block termination,
+//! the layout of `Block` structs and the interface of the entry points
+
+use std::fmt;
+
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
+use rspirv::mr::{Function, Instruction, Module, Operand};
+use spirv_headers::{Decoration, ExecutionModel, Op, StorageClass, Word};
+
+/// A rule of the specification broken by a module
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// The module has no header
+    MissingHeader,
+    /// The version word of the header is not a valid SPIR-V version
+    InvalidVersion(Word),
+    /// An ID is greater than the bound declared in the header
+    IdOutOfBound(Word),
+    /// An ID is the result of several instructions
+    DuplicateId(Word),
+    /// An instruction uses an ID that's never defined
+    UndefinedId(Op, Word),
+    /// An instruction uses an ID before its definition
+    UseBeforeDefinition(Op, Word),
+    /// The result type of an instruction is not a type
+    NotAType(Op, Word),
+    /// The type of an operand doesn't match the one expected by the instruction
+    TypeMismatch(Op, Word),
+    /// A block doesn't end with a termination instruction
+    UnterminatedBlock(Word),
+    /// An instruction of a function is not in a block
+    InstructionOutsideBlock(Op),
+    /// A member of a block struct has no offset
+    MissingOffset(Word, u32),
+    /// The offset of a member of a block struct doesn't respect its alignment
+    MisalignedMember(Word, u32),
+    /// A member of a block struct overlaps with the previous one
+    OverlappingMembers(Word, u32),
+    /// A matrix member of a block struct has no stride or majorness
+    MissingMatrixLayout(Word, u32),
+    /// A variable used by an entry point is not listed in its interface
+    MissingInterface(Word, Word),
+    /// An integer or double fragment input is not decorated with `Flat`
+    MissingFlat(Word),
+    /// The same decoration is applied several times to an ID
+    DuplicateDecoration(Word, Decoration),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ValidationError::MissingHeader => write!(f, "missing module header"),
+            ValidationError::InvalidVersion(word) => write!(f, "invalid version {:#010x}", word),
+            ValidationError::IdOutOfBound(id) => write!(f, "%{} is out of the ID bound", id),
+            ValidationError::DuplicateId(id) => write!(f, "%{} is defined several times", id),
+            ValidationError::UndefinedId(op, id) => {
+                write!(f, "Op{:?} uses undefined ID %{}", op, id)
+            }
+            ValidationError::UseBeforeDefinition(op, id) => {
+                write!(f, "Op{:?} uses %{} before its definition", op, id)
+            }
+            ValidationError::NotAType(op, id) => {
+                write!(f, "the result type %{} of Op{:?} is not a type", id, op)
+            }
+            ValidationError::TypeMismatch(op, id) => {
+                write!(f, "%{} has the wrong type for Op{:?}", id, op)
+            }
+            ValidationError::UnterminatedBlock(id) => {
+                write!(f, "block %{} is not terminated", id)
+            }
+            ValidationError::InstructionOutsideBlock(op) => {
+                write!(f, "Op{:?} is outside of a block", op)
+            }
+            ValidationError::MissingOffset(id, member) => {
+                write!(f, "member {} of block %{} has no offset", member, id)
+            }
+            ValidationError::MisalignedMember(id, member) => {
+                write!(f, "member {} of block %{} is misaligned", member, id)
+            }
+            ValidationError::OverlappingMembers(id, member) => write!(
+                f,
+                "member {} of block %{} overlaps with the previous member",
+                member, id
+            ),
+            ValidationError::MissingMatrixLayout(id, member) => write!(
+                f,
+                "matrix member {} of block %{} has no stride or majorness",
+                member, id
+            ),
+            ValidationError::MissingInterface(entry, id) => write!(
+                f,
+                "%{} is used by entry point %{} but missing from its interface",
+                id, entry
+            ),
+            ValidationError::MissingFlat(id) => {
+                write!(f, "fragment input %{} must be decorated with Flat", id)
+            }
+            ValidationError::DuplicateDecoration(id, decoration) => {
+                write!(
+                    f,
+                    "%{} is decorated with {:?} several times",
+                    id, decoration
+                )
+            }
+        }
+    }
+}
+
+/// Get the ID operand at an index
+fn id_operand(inst: &Instruction, index: usize) -> Option<Word> {
+    match inst.operands.get(index) {
+        Some(&Operand::IdRef(id)) => Some(id),
+        _ => None,
+    }
+}
+
+/// Get the literal operand at an index
+fn literal_operand(inst: &Instruction, index: usize) -> Option<u32> {
+    match inst.operands.get(index) {
+        Some(&Operand::LiteralInt32(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// Iterate over the IDs used by an instruction
+fn used_ids<'a>(inst: &'a Instruction) -> impl Iterator<Item = Word> + 'a {
+    inst.result_type
+        .into_iter()
+        .chain(inst.operands.iter().filter_map(|op| match *op {
+            Operand::IdRef(id) => Some(id),
+            _ => None,
+        }))
+}
+
+/// Iterate over all the instructions of a function, in order
+fn function_instructions<'a>(func: &'a Function) -> impl Iterator<Item = &'a Instruction> + 'a {
+    func.def
+        .iter()
+        .chain(&func.parameters)
+        .chain(
+            func.basic_blocks
+                .iter()
+                .flat_map(|block| block.label.iter().chain(&block.instructions)),
+        )
+        .chain(&func.end)
+}
+
+/// Iterate over all the instructions of a module, in order
+fn module_instructions<'a>(module: &'a Module) -> impl Iterator<Item = &'a Instruction> + 'a {
+    module
+        .capabilities
+        .iter()
+        .chain(&module.extensions)
+        .chain(&module.ext_inst_imports)
+        .chain(&module.memory_model)
+        .chain(&module.entry_points)
+        .chain(&module.execution_modes)
+        .chain(&module.debugs)
+        .chain(&module.annotations)
+        .chain(&module.types_global_values)
+        .chain(module.functions.iter().flat_map(function_instructions))
+}
+
+fn is_type(op: Op) -> bool {
+    match op {
+        Op::TypeVoid
+        | Op::TypeBool
+        | Op::TypeInt
+        | Op::TypeFloat
+        | Op::TypeVector
+        | Op::TypeMatrix
+        | Op::TypeImage
+        | Op::TypeSampler
+        | Op::TypeSampledImage
+        | Op::TypeArray
+        | Op::TypeRuntimeArray
+        | Op::TypeStruct
+        | Op::TypeOpaque
+        | Op::TypePointer
+        | Op::TypeFunction => true,
+        _ => false,
+    }
+}
+
+fn is_terminator(op: Op) -> bool {
+    match op {
+        Op::Branch
+        | Op::BranchConditional
+        | Op::Switch
+        | Op::Return
+        | Op::ReturnValue
+        | Op::Kill
+        | Op::Unreachable => true,
+        _ => false,
+    }
+}
+
+/// Arithmetic instructions whose operands have the same type as the result,
+/// and whether the signedness of integers may differ
+fn arithmetic(op: Op) -> Option<bool> {
+    match op {
+        Op::IAdd | Op::ISub | Op::IMul | Op::UDiv | Op::SDiv => Some(true),
+        Op::FAdd | Op::FSub | Op::FMul | Op::FDiv => Some(false),
+        _ => None,
+    }
+}
+
+fn round_up(value: u32, alignment: u32) -> u32 {
+    (value + alignment - 1) / alignment * alignment
+}
+
+struct Validator<'a> {
+    module: &'a Module,
+    definitions: HashMap<Word, &'a Instruction>,
+    errors: Vec<ValidationError>,
+}
+
+impl<'a> Validator<'a> {
+    fn new(module: &'a Module) -> Self {
+        Validator {
+            module,
+            definitions: HashMap::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn check_header(&mut self) -> Option<Word> {
+        let module = self.module;
+        let header = if let Some(ref header) = module.header {
+            header
+        } else {
+            self.errors.push(ValidationError::MissingHeader);
+            return None;
+        };
+
+        let major = (header.version >> 16) & 0xff;
+        let minor = (header.version >> 8) & 0xff;
+        if header.version & 0xff00_00ff != 0 || major != 1 || minor > 6 {
+            self.errors
+                .push(ValidationError::InvalidVersion(header.version));
+        }
+
+        Some(header.bound)
+    }
+
+    fn collect_definitions(&mut self, bound: Option<Word>) {
+        for inst in module_instructions(self.module) {
+            if let Some(id) = inst.result_id {
+                if bound.map_or(false, |bound| id >= bound) {
+                    self.errors.push(ValidationError::IdOutOfBound(id));
+                }
+
+                if self.definitions.insert(id, inst).is_some() {
+                    self.errors.push(ValidationError::DuplicateId(id));
+                }
+            }
+        }
+    }
+
+    /// Check the IDs used by an instruction are defined in the set
+    fn check_uses(&mut self, inst: &Instruction, defined: &HashSet<Word>) {
+        for id in used_ids(inst) {
+            if defined.contains(&id) {
+                continue;
+            }
+
+            let op = inst.class.opcode;
+            self.errors.push(if self.definitions.contains_key(&id) {
+                ValidationError::UseBeforeDefinition(op, id)
+            } else {
+                ValidationError::UndefinedId(op, id)
+            });
+        }
+    }
+
+    fn check_definitions(&mut self) {
+        let module = self.module;
+
+        // Debug instructions, annotations and entry points may use IDs defined later
+        let forward = module
+            .entry_points
+            .iter()
+            .chain(&module.execution_modes)
+            .chain(&module.debugs)
+            .chain(&module.annotations);
+
+        for inst in forward {
+            for id in used_ids(inst) {
+                if !self.definitions.contains_key(&id) {
+                    self.errors
+                        .push(ValidationError::UndefinedId(inst.class.opcode, id));
+                }
+            }
+        }
+
+        let mut globals: HashSet<Word> = {
+            module
+                .ext_inst_imports
+                .iter()
+                .chain(&module.debugs)
+                .filter_map(|inst| inst.result_id)
+                .collect()
+        };
+
+        for inst in &module.types_global_values {
+            self.check_uses(inst, &globals);
+            globals.extend(inst.result_id);
+        }
+
+        // Functions may be called before their definition
+        globals.extend(
+            module
+                .functions
+                .iter()
+                .filter_map(|func| func.def.as_ref().and_then(|def| def.result_id)),
+        );
+
+        for func in &module.functions {
+            // Blocks may be branched to before their definition
+            let mut defined = globals.clone();
+            defined.extend(
+                func.basic_blocks
+                    .iter()
+                    .filter_map(|block| block.label.as_ref().and_then(|label| label.result_id)),
+            );
+
+            for inst in function_instructions(func) {
+                // Phi instructions reference values coming from the predecessor blocks
+                if inst.class.opcode != Op::Phi {
+                    self.check_uses(inst, &defined);
+                }
+
+                defined.extend(inst.result_id);
+            }
+        }
+    }
+
+    fn check_result_types(&mut self) {
+        for inst in module_instructions(self.module) {
+            if let Some(ty) = inst.result_type {
+                let is_type = self
+                    .definitions
+                    .get(&ty)
+                    .map_or(true, |def| is_type(def.class.opcode));
+
+                if !is_type {
+                    self.errors
+                        .push(ValidationError::NotAType(inst.class.opcode, ty));
+                }
+            }
+        }
+    }
+
+    fn type_of(&self, id: Word) -> Option<Word> {
+        self.definitions.get(&id).and_then(|inst| inst.result_type)
+    }
+
+    fn pointee(&self, ptr_type: Word) -> Option<Word> {
+        match self.definitions.get(&ptr_type) {
+            Some(inst) if inst.class.opcode == Op::TypePointer => id_operand(inst, 1),
+            _ => None,
+        }
+    }
+
+    /// Check if two types are structurally equivalent, optionally ignoring
+    /// the signedness of integers
+    fn same_type(&self, lhs: Word, rhs: Word, any_sign: bool) -> bool {
+        if lhs == rhs {
+            return true;
+        }
+
+        let (lhs, rhs) = match (self.definitions.get(&lhs), self.definitions.get(&rhs)) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            _ => return false,
+        };
+
+        if lhs.class.opcode != rhs.class.opcode || lhs.operands.len() != rhs.operands.len() {
+            return false;
+        }
+
+        lhs.operands
+            .iter()
+            .zip(&rhs.operands)
+            .enumerate()
+            .all(|(index, pair)| match pair {
+                (&Operand::IdRef(left), &Operand::IdRef(right)) => {
+                    self.same_type(left, right, any_sign)
+                }
+                // Integer operations accept operands of any signedness
+                _ if any_sign && lhs.class.opcode == Op::TypeInt && index == 1 => true,
+                (left, right) => left == right,
+            })
+    }
+
+    /// Check an operand has the expected type
+    fn expect_type(&mut self, op: Op, id: Word, expected: Option<Word>, any_sign: bool) {
+        if let (Some(actual), Some(expected)) = (self.type_of(id), expected) {
+            if !self.same_type(actual, expected, any_sign) {
+                self.errors.push(ValidationError::TypeMismatch(op, id));
+            }
+        }
+    }
+
+    fn check_call(&mut self, inst: &Instruction) {
+        let op = inst.class.opcode;
+        let callee = match id_operand(inst, 0).and_then(|id| self.definitions.get(&id).cloned()) {
+            Some(def) if def.class.opcode == Op::Function => def,
+            _ => return,
+        };
+        let callee_id = callee.result_id.unwrap_or(0);
+
+        if let (Some(actual), Some(expected)) = (inst.result_type, callee.result_type) {
+            if !self.same_type(actual, expected, false) {
+                self.errors
+                    .push(ValidationError::TypeMismatch(op, callee_id));
+            }
+        }
+
+        let func_type = id_operand(callee, 1).and_then(|id| self.definitions.get(&id).cloned());
+        let params: Vec<_> = match func_type {
+            Some(func_type) => func_type.operands.iter().skip(1).collect(),
+            None => return,
+        };
+
+        if params.len() + 1 != inst.operands.len() {
+            self.errors
+                .push(ValidationError::TypeMismatch(op, callee_id));
+            return;
+        }
+
+        for (index, param) in params.into_iter().enumerate() {
+            if let (Some(arg), &Operand::IdRef(param)) = (id_operand(inst, index + 1), param) {
+                self.expect_type(op, arg, Some(param), false);
+            }
+        }
+    }
+
+    fn check_operand_types(&mut self) {
+        let module = self.module;
+        for func in &module.functions {
+            let ret_type = func.def.as_ref().and_then(|def| def.result_type);
+
+            let instructions = func
+                .basic_blocks
+                .iter()
+                .flat_map(|block| &block.instructions);
+            for inst in instructions {
+                let op = inst.class.opcode;
+                match op {
+                    Op::Load => {
+                        if let Some(ptr) = id_operand(inst, 0) {
+                            let pointee = self.type_of(ptr).and_then(|ty| self.pointee(ty));
+                            if let (Some(pointee), Some(res_type)) = (pointee, inst.result_type) {
+                                if !self.same_type(pointee, res_type, false) {
+                                    self.errors.push(ValidationError::TypeMismatch(op, ptr));
+                                }
+                            }
+                        }
+                    }
+                    Op::Store => {
+                        if let (Some(ptr), Some(value)) = (id_operand(inst, 0), id_operand(inst, 1))
+                        {
+                            let pointee = self.type_of(ptr).and_then(|ty| self.pointee(ty));
+                            self.expect_type(op, value, pointee, false);
+                        }
+                    }
+                    Op::ReturnValue => {
+                        if let Some(value) = id_operand(inst, 0) {
+                            self.expect_type(op, value, ret_type, false);
+                        }
+                    }
+                    Op::FunctionCall => self.check_call(inst),
+                    _ => {
+                        if let Some(any_sign) = arithmetic(op) {
+                            for index in 0..2 {
+                                if let Some(id) = id_operand(inst, index) {
+                                    self.expect_type(op, id, inst.result_type, any_sign);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_blocks(&mut self) {
+        let module = self.module;
+        for func in &module.functions {
+            // The builder may add several labels to the instructions of a single block
+            let mut current = None;
+            let instructions = func
+                .basic_blocks
+                .iter()
+                .flat_map(|block| block.label.iter().chain(&block.instructions));
+
+            for inst in instructions {
+                let op = inst.class.opcode;
+                match (op, current) {
+                    (Op::Label, Some(label)) => {
+                        self.errors.push(ValidationError::UnterminatedBlock(label));
+                        current = inst.result_id;
+                    }
+                    (Op::Label, None) => current = inst.result_id,
+                    (Op::Line, _) | (Op::NoLine, _) => {}
+                    (_, None) => self
+                        .errors
+                        .push(ValidationError::InstructionOutsideBlock(op)),
+                    _ if is_terminator(op) => current = None,
+                    _ => {}
+                }
+            }
+
+            if let Some(label) = current {
+                self.errors.push(ValidationError::UnterminatedBlock(label));
+            }
+        }
+    }
+
+    /// Find the decorations applied to an ID, or to a member of a struct
+    fn decorations(
+        &self,
+        target: Word,
+        member: Option<u32>,
+    ) -> impl Iterator<Item = (Decoration, Option<u32>)> + 'a {
+        let start = if member.is_some() { 2 } else { 1 };
+        let op = if member.is_some() {
+            Op::MemberDecorate
+        } else {
+            Op::Decorate
+        };
+
+        let module = self.module;
+        module.annotations.iter().filter_map(move |inst| {
+            if inst.class.opcode != op
+                || id_operand(inst, 0) != Some(target)
+                || member.map_or(false, |member| literal_operand(inst, 1) != Some(member))
+            {
+                return None;
+            }
+
+            match inst.operands.get(start) {
+                Some(&Operand::Decoration(decoration)) => {
+                    Some((decoration, literal_operand(inst, start + 1)))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    fn has_decoration(&self, target: Word, member: Option<u32>, decoration: Decoration) -> bool {
+        self.decorations(target, member)
+            .any(|(other, _)| other == decoration)
+    }
+
+    fn decoration_value(
+        &self,
+        target: Word,
+        member: Option<u32>,
+        decoration: Decoration,
+    ) -> Option<u32> {
+        self.decorations(target, member)
+            .find(|&(other, _)| other == decoration)
+            .and_then(|(_, value)| value)
+    }
+
+    /// Get the size and std140 alignment of a member of a block struct
+    fn layout(&self, ty: Word, matrix_stride: Option<u32>) -> Option<(u32, u32)> {
+        let inst = self.definitions.get(&ty)?;
+        match inst.class.opcode {
+            Op::TypeInt | Op::TypeFloat => {
+                let size = literal_operand(inst, 0)? / 8;
+                Some((size, size))
+            }
+            Op::TypeVector => {
+                let (size, _) = self.layout(id_operand(inst, 0)?, None)?;
+                let count = literal_operand(inst, 1)?;
+                let alignment = if count == 2 { size * 2 } else { size * 4 };
+                Some((size * count, alignment))
+            }
+            Op::TypeMatrix => {
+                let (column, alignment) = self.layout(id_operand(inst, 0)?, None)?;
+                let stride = matrix_stride.unwrap_or_else(|| round_up(column, 16));
+                Some((stride * literal_operand(inst, 1)?, round_up(alignment, 16)))
+            }
+            Op::TypeArray => {
+                let elem = id_operand(inst, 0)?;
+                let len = self.definitions.get(&id_operand(inst, 1)?)?;
+                let len = literal_operand(len, 0)?;
+
+                let (size, alignment) = self.layout(elem, matrix_stride)?;
+                let stride = self
+                    .decoration_value(ty, None, Decoration::ArrayStride)
+                    .unwrap_or_else(|| round_up(size, 16));
+                Some((stride * len, round_up(alignment, 16)))
+            }
+            Op::TypeStruct => {
+                let mut size = 0;
+                let mut alignment = 16;
+                for (index, member) in inst.operands.iter().enumerate() {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let index = Some(index as u32);
+                    let member = match *member {
+                        Operand::IdRef(member) => member,
+                        _ => return None,
+                    };
+
+                    let offset = self.decoration_value(ty, index, Decoration::Offset)?;
+                    let stride = self.decoration_value(ty, index, Decoration::MatrixStride);
+                    let (member_size, member_alignment) = self.layout(member, stride)?;
+                    size = size.max(offset + member_size);
+                    alignment = alignment.max(member_alignment);
+                }
+
+                Some((round_up(size, alignment), alignment))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_matrix(&self, ty: Word) -> bool {
+        match self.definitions.get(&ty) {
+            Some(inst) if inst.class.opcode == Op::TypeMatrix => true,
+            Some(inst) if inst.class.opcode == Op::TypeArray => {
+                id_operand(inst, 0).map_or(false, |elem| self.is_matrix(elem))
+            }
+            _ => false,
+        }
+    }
+
+    fn check_block(&mut self, ty: Word) {
+        let inst = if let Some(inst) = self.definitions.get(&ty).cloned() {
+            inst
+        } else {
+            return;
+        };
+
+        #[allow(clippy::cast_possible_truncation)]
+        let members: Vec<_> = {
+            inst.operands
+                .iter()
+                .enumerate()
+                .filter_map(|(index, member)| match *member {
+                    Operand::IdRef(member) => Some((index as u32, member)),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // Builtin blocks are laid out by the implementation
+        let is_builtin = members
+            .iter()
+            .any(|&(index, _)| self.has_decoration(ty, Some(index), Decoration::BuiltIn));
+        if is_builtin {
+            return;
+        }
+
+        let mut ranges = Vec::with_capacity(members.len());
+        for (index, member) in members {
+            let stride = self.decoration_value(ty, Some(index), Decoration::MatrixStride);
+            let is_major = self.has_decoration(ty, Some(index), Decoration::ColMajor)
+                || self.has_decoration(ty, Some(index), Decoration::RowMajor);
+            if self.is_matrix(member) && (stride.is_none() || !is_major) {
+                self.errors
+                    .push(ValidationError::MissingMatrixLayout(ty, index));
+            }
+
+            let offset =
+                if let Some(offset) = self.decoration_value(ty, Some(index), Decoration::Offset) {
+                    offset
+                } else {
+                    self.errors.push(ValidationError::MissingOffset(ty, index));
+                    continue;
+                };
+
+            if let Some((size, alignment)) = self.layout(member, stride) {
+                if offset % alignment != 0 {
+                    self.errors
+                        .push(ValidationError::MisalignedMember(ty, index));
+                }
+
+                ranges.push((offset, offset + size, index));
+            }
+        }
+
+        ranges.sort();
+        for pair in ranges.windows(2) {
+            let (_, end, _) = pair[0];
+            let (start, _, index) = pair[1];
+            if start < end {
+                self.errors
+                    .push(ValidationError::OverlappingMembers(ty, index));
+            }
+        }
+    }
+
+    fn check_layouts(&mut self) {
+        let blocks: Vec<_> = {
+            self.module
+                .annotations
+                .iter()
+                .filter(|inst| {
+                    inst.class.opcode == Op::Decorate
+                        && match inst.operands.get(1) {
+                            Some(&Operand::Decoration(Decoration::Block))
+                            | Some(&Operand::Decoration(Decoration::BufferBlock)) => true,
+                            _ => false,
+                        }
+                })
+                .filter_map(|inst| id_operand(inst, 0))
+                .collect()
+        };
+
+        for ty in blocks {
+            self.check_block(ty);
+        }
+    }
+
+    /// Collect the functions called from a function, including itself
+    fn reachable(&self, entry: Word) -> HashSet<Word> {
+        let functions: HashMap<Word, &Function> = {
+            self.module
+                .functions
+                .iter()
+                .filter_map(|func| Some((func.def.as_ref()?.result_id?, func)))
+                .collect()
+        };
+
+        let mut res = HashSet::default();
+        let mut stack = vec![entry];
+        while let Some(id) = stack.pop() {
+            if !res.insert(id) {
+                continue;
+            }
+
+            if let Some(func) = functions.get(&id) {
+                stack.extend(
+                    function_instructions(func)
+                        .filter(|inst| inst.class.opcode == Op::FunctionCall)
+                        .filter_map(|inst| id_operand(inst, 0)),
+                );
+            }
+        }
+
+        res
+    }
+
+    fn storage_class(&self, var: Word) -> Option<StorageClass> {
+        match self.definitions.get(&var) {
+            Some(inst) if inst.class.opcode == Op::Variable => match inst.operands.get(0) {
+                Some(&Operand::StorageClass(storage)) => Some(storage),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Check if an input variable holds values that cannot be interpolated
+    fn needs_flat(&self, var: Word) -> bool {
+        let mut ty = self.type_of(var).and_then(|ty| self.pointee(ty));
+        while let Some(inst) = ty.and_then(|ty| self.definitions.get(&ty)) {
+            match inst.class.opcode {
+                Op::TypeArray | Op::TypeVector | Op::TypeMatrix => ty = id_operand(inst, 0),
+                Op::TypeInt => return true,
+                Op::TypeFloat => return literal_operand(inst, 0) == Some(64),
+                _ => return false,
+            }
+        }
+
+        false
+    }
+
+    fn check_interfaces(&mut self, version: Option<Word>) {
+        let module = self.module;
+        let all_globals = version.map_or(false, |version| ((version >> 8) & 0xff) >= 4);
+        let mut flat_checked = HashSet::default();
+
+        for inst in &module.entry_points {
+            let (model, entry) = match (inst.operands.get(0), id_operand(inst, 1)) {
+                (Some(&Operand::ExecutionModel(model)), Some(entry)) => (model, entry),
+                _ => continue,
+            };
+
+            let listed: HashSet<Word> = {
+                inst.operands
+                    .iter()
+                    .skip(3)
+                    .filter_map(|op| match *op {
+                        Operand::IdRef(id) => Some(id),
+                        _ => None,
+                    })
+                    .collect()
+            };
+
+            let reachable = self.reachable(entry);
+            let mut used: Vec<Word> = {
+                module
+                    .functions
+                    .iter()
+                    .filter(|func| {
+                        func.def
+                            .as_ref()
+                            .and_then(|def| def.result_id)
+                            .map_or(false, |id| reachable.contains(&id))
+                    })
+                    .flat_map(function_instructions)
+                    .flat_map(used_ids)
+                    .filter(|&id| match self.storage_class(id) {
+                        Some(StorageClass::Input) | Some(StorageClass::Output) => true,
+                        Some(StorageClass::Function) | None => false,
+                        Some(_) => all_globals,
+                    })
+                    .collect()
+            };
+
+            used.sort();
+            used.dedup();
+
+            for id in used {
+                if !listed.contains(&id) {
+                    self.errors
+                        .push(ValidationError::MissingInterface(entry, id));
+                }
+            }
+
+            if model != ExecutionModel::Fragment {
+                continue;
+            }
+
+            let mut listed: Vec<_> = listed.into_iter().collect();
+            listed.sort();
+
+            for id in listed {
+                if self.storage_class(id) == Some(StorageClass::Input)
+                    && flat_checked.insert(id)
+                    && self.needs_flat(id)
+                    && !self.has_decoration(id, None, Decoration::Flat)
+                    && !self.has_decoration(id, None, Decoration::BuiltIn)
+                {
+                    self.errors.push(ValidationError::MissingFlat(id));
+                }
+            }
+        }
+    }
+
+    fn check_duplicate_decorations(&mut self) {
+        let module = self.module;
+        let annotations = &module.annotations;
+        for (index, inst) in annotations.iter().enumerate() {
+            let decoration_index = match inst.class.opcode {
+                Op::Decorate => 1,
+                Op::MemberDecorate => 2,
+                _ => continue,
+            };
+
+            let is_duplicate = annotations[..index].iter().any(|other| {
+                other.class.opcode == inst.class.opcode && other.operands == inst.operands
+            });
+
+            if let (true, Some(target), Some(&Operand::Decoration(decoration))) = (
+                is_duplicate,
+                id_operand(inst, 0),
+                inst.operands.get(decoration_index),
+            ) {
+                self.errors
+                    .push(ValidationError::DuplicateDecoration(target, decoration));
+            }
+        }
+    }
+}
+
+/// Check the structure of a module, returning all the errors found
+///
+/// Modules emitted by the builder are validated automatically when `Settings::validate`
+/// is set, which is the default in debug builds
+pub fn validate(module: &Module) -> Vec<ValidationError> {
+    let mut validator = Validator::new(module);
+
+    let bound = validator.check_header();
+    validator.collect_definitions(bound);
+    validator.check_definitions();
+    validator.check_result_types();
+    validator.check_operand_types();
+    validator.check_blocks();
+    validator.check_layouts();
+
+    let version = module.header.as_ref().map(|header| header.version);
+    validator.check_interfaces(version);
+    validator.check_duplicate_decorations();
+
+    validator.errors
+}
@@ -0,0 +1,108 @@
+extern crate rasen;
+
+mod common;
+
+use common::has_line;
+use rasen::{errors::Error, prelude::*};
+
+fn passthrough(ty: &'static TypeName, input: Attributes, output: Attributes) -> Graph {
+    let mut graph = Graph::default();
+
+    let value = graph.add_node(Node::Input(0, ty, VariableName::None, input));
+    let result = graph.add_node(Node::Output(0, ty, VariableName::None, output));
+    graph.add_edge(value, result, 0);
+
+    graph
+}
+
+#[test]
+fn test_flat_integer_input() {
+    let graph = passthrough(
+        TypeName::UVEC2,
+        Attributes {
+            flat: true,
+            ..Attributes::default()
+        },
+        Attributes::default(),
+    );
+
+    let assembly = build_program_assembly(&graph, ShaderType::Fragment).unwrap();
+    assert!(has_line(&assembly, "Flat"));
+
+    let graph = passthrough(
+        TypeName::UVEC2,
+        Attributes::default(),
+        Attributes::default(),
+    );
+    let err = build_program_assembly(&graph, ShaderType::Fragment).unwrap_err();
+    match err {
+        Error::BuildError { name: "Input", .. } => {}
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+    assert!(err.to_string().contains("Flat"));
+
+    // Integer vertex inputs don't need to be flat
+    let assembly = build_program_assembly(&graph, ShaderType::Vertex).unwrap();
+    assert!(!has_line(&assembly, "Flat"));
+}
+
+#[test]
+fn test_interpolation() {
+    let graph = passthrough(
+        TypeName::VEC2,
+        Attributes::default(),
+        Attributes {
+            no_perspective: true,
+            centroid: true,
+            component: Some(2),
+            ..Attributes::default()
+        },
+    );
+
+    let assembly = build_program_assembly(&graph, ShaderType::Vertex).unwrap();
+    assert!(has_line(&assembly, "NoPerspective"));
+    assert!(has_line(&assembly, "Centroid"));
+    assert!(has_line(&assembly, "Component 2"));
+
+    let graph = passthrough(
+        TypeName::VEC4,
+        Attributes {
+            sample: true,
+            ..Attributes::default()
+        },
+        Attributes::default(),
+    );
+
+    let assembly = build_program_assembly(&graph, ShaderType::Fragment).unwrap();
+    assert!(has_line(&assembly, "Sample"));
+    assert!(has_line(&assembly, "OpCapability SampleRateShading"));
+}
+
+#[test]
+fn test_blend_index() {
+    let output = Attributes {
+        index: Some(1),
+        ..Attributes::default()
+    };
+
+    let graph = passthrough(TypeName::VEC4, Attributes::default(), output);
+    let assembly = build_program_assembly(&graph, ShaderType::Fragment).unwrap();
+    assert!(has_line(&assembly, "Index 1"));
+
+    assert!(build_program_assembly(&graph, ShaderType::Vertex).is_err());
+}
+
+#[test]
+fn test_conflicting_attributes() {
+    let graph = passthrough(
+        TypeName::VEC4,
+        Attributes {
+            flat: true,
+            no_perspective: true,
+            ..Attributes::default()
+        },
+        Attributes::default(),
+    );
+
+    assert!(build_program_assembly(&graph, ShaderType::Fragment).is_err());
+}
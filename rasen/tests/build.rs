@@ -15,6 +15,7 @@ fn test_build_basic_vert() {
         Settings {
             mod_type: ShaderType::Vertex,
             uniforms_name: Some(String::from("Uniforms")),
+            ..Settings::default()
         },
     )
     .unwrap();
@@ -30,6 +31,7 @@ fn test_build_basic_frag() {
         Settings {
             mod_type: ShaderType::Fragment,
             uniforms_name: Some(String::from("Test1")),
+            ..Settings::default()
         },
     )
     .unwrap();
@@ -56,9 +58,15 @@ fn test_build_function() {
             0,
             TypeName::FLOAT,
             VariableName::Named(String::from("a_input")),
+            Attributes::default(),
         ));
         let call = graph.add_node(Node::Call(func));
-        let output = graph.add_node(Node::Output(0, TypeName::FLOAT, VariableName::None));
+        let output = graph.add_node(Node::Output(
+            0,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
         graph.add_edge(input, call, 0);
         graph.add_edge(call, output, 0);
     }
@@ -68,6 +76,7 @@ fn test_build_function() {
         Settings {
             mod_type: ShaderType::Vertex,
             uniforms_name: Some(String::from("Uniforms")),
+            ..Settings::default()
         },
     )
     .unwrap();
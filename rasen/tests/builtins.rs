@@ -0,0 +1,174 @@
+extern crate rasen;
+
+mod common;
+
+use common::has_line;
+use rasen::prelude::*;
+
+fn build_vertex(per_vertex_block: bool) -> String {
+    let mut graph = Graph::default();
+
+    let pos = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let size = graph.add_node(Node::Constant(TypedValue::Float(2.0)));
+    let clip = graph.add_node(Node::Constant(TypedValue::Float(0.5)));
+
+    let o_pos = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::Position),
+        Attributes::default(),
+    ));
+    let o_size = graph.add_node(Node::Output(
+        1,
+        TypeName::FLOAT,
+        VariableName::BuiltIn(BuiltIn::PointSize),
+        Attributes::default(),
+    ));
+    let o_clip = graph.add_node(Node::Output(
+        2,
+        TypeName::FLOAT,
+        VariableName::BuiltIn(BuiltIn::ClipDistance),
+        Attributes::default(),
+    ));
+
+    graph.add_edge(pos, o_pos, 0);
+    graph.add_edge(size, o_size, 0);
+    graph.add_edge(clip, o_clip, 0);
+
+    build_program_assembly(
+        &graph,
+        Settings {
+            mod_type: ShaderType::Vertex,
+            per_vertex_block,
+            ..Settings::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_builtin_location() {
+    let assembly = build_vertex(false);
+
+    // Only the non-builtin input gets a location
+    assert_eq!(assembly.matches("Location").count(), 1);
+    assert!(has_line(&assembly, "BuiltIn Position"));
+    assert!(has_line(&assembly, "BuiltIn PointSize"));
+    assert!(!assembly.contains("gl_PerVertex"));
+}
+
+#[test]
+fn test_clip_distance_array() {
+    let assembly = build_vertex(false);
+
+    // The standalone builtin is an array of floats, written through its first element
+    assert!(has_line(&assembly, "BuiltIn ClipDistance"));
+    assert!(has_line(&assembly, "OpCapability ClipDistance"));
+    assert_eq!(assembly.matches("OpTypeArray").count(), 1);
+    assert_eq!(assembly.matches("OpAccessChain").count(), 1);
+}
+
+#[test]
+fn test_per_vertex_block() {
+    let assembly = build_vertex(true);
+
+    assert!(has_line(&assembly, "\"gl_PerVertex\""));
+    assert!(has_line(&assembly, "0 BuiltIn Position"));
+    assert!(has_line(&assembly, "1 BuiltIn PointSize"));
+    assert!(has_line(&assembly, "2 BuiltIn ClipDistance"));
+    assert!(has_line(&assembly, "OpCapability ClipDistance"));
+    assert!(assembly.contains("OpTypeArray"));
+    assert_eq!(assembly.matches("OpAccessChain").count(), 3);
+    assert_eq!(assembly.matches("Location").count(), 1);
+
+    // The interface holds the input and the block
+    let entry = assembly
+        .lines()
+        .find(|line| line.trim().starts_with("OpEntryPoint"))
+        .unwrap();
+    assert_eq!(entry.split_whitespace().count(), 6);
+}
+
+#[test]
+fn test_per_vertex_point_size() {
+    let mut graph = Graph::default();
+
+    let size = graph.add_node(Node::Constant(TypedValue::Float(2.0)));
+    let o_size = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::BuiltIn(BuiltIn::PointSize),
+        Attributes::default(),
+    ));
+
+    graph.add_edge(size, o_size, 0);
+
+    // The capability depends on the stage of the block declaring the builtin
+    let assembly = build_program_assembly(
+        &graph,
+        Settings {
+            mod_type: ShaderType::TessellationEvaluation,
+            per_vertex_block: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert!(has_line(&assembly, "0 BuiltIn PointSize"));
+    assert!(has_line(&assembly, "OpCapability TessellationPointSize"));
+}
+
+#[test]
+fn test_fragment_builtins() {
+    let mut graph = Graph::default();
+
+    let coord = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::FragCoord),
+        Attributes::default(),
+    ));
+    let depth = graph.add_node(Node::Extract(2));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::BuiltIn(BuiltIn::FragDepth),
+        Attributes::default(),
+    ));
+
+    graph.add_edge(coord, depth, 0);
+    graph.add_edge(depth, output, 0);
+
+    let assembly = build_program_assembly(&graph, ShaderType::Fragment).unwrap();
+
+    assert!(has_line(&assembly, "BuiltIn FragCoord"));
+    assert!(has_line(&assembly, "BuiltIn FragDepth"));
+    assert!(has_line(&assembly, "DepthReplacing"));
+    assert!(!assembly.contains("Location"));
+}
+
+#[test]
+fn test_builtin_type() {
+    let mut graph = Graph::default();
+
+    let facing = graph.add_node(Node::Input(
+        0,
+        TypeName::FLOAT,
+        VariableName::BuiltIn(BuiltIn::FrontFacing),
+        Attributes::default(),
+    ));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(facing, output, 0);
+
+    assert!(build_program_assembly(&graph, ShaderType::Fragment).is_err());
+}
@@ -0,0 +1,22 @@
+//! Helpers shared by the tests inspecting the disassembly of a module
+
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
+/// Check if a line of the disassembly ends with `line`
+pub fn has_line(assembly: &str, line: &str) -> bool {
+    assembly.lines().any(|l| l.trim().ends_with(line))
+}
+
+/// Count the lines of the disassembly ending with `line`
+pub fn count_lines(assembly: &str, line: &str) -> usize {
+    assembly
+        .lines()
+        .filter(|l| l.trim().ends_with(line))
+        .count()
+}
+
+/// Count the lines of the disassembly containing `pattern`
+pub fn count_matches(assembly: &str, pattern: &str) -> usize {
+    assembly.lines().filter(|l| l.contains(pattern)).count()
+}
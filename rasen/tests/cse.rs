@@ -0,0 +1,180 @@
+extern crate rasen;
+
+use rasen::{errors::Error, passes::eliminate_common_subexpressions, prelude::*};
+
+fn add_input(graph: &mut Graph, ty: &'static TypeName) -> NodeIndex {
+    graph.add_node(Node::Input(
+        0,
+        ty,
+        VariableName::Named(String::from("a_normal")),
+        Attributes::default(),
+    ))
+}
+
+#[test]
+fn test_merge() {
+    let mut graph = Graph::default();
+
+    let input_a = add_input(&mut graph, TypeName::VEC3);
+    let input_b = add_input(&mut graph, TypeName::VEC3);
+    let normalize_a = graph.add_node(Node::Normalize);
+    let normalize_b = graph.add_node(Node::Normalize);
+    let add = graph.add_node(Node::Add);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(input_a, normalize_a, 0);
+    graph.add_edge(input_b, normalize_b, 0);
+    graph.add_edge(normalize_a, add, 0);
+    graph.add_edge(normalize_b, add, 1);
+    graph.add_edge(add, output, 0);
+
+    assert_eq!(eliminate_common_subexpressions(&mut graph).unwrap(), 2);
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.edge_count(), 4);
+
+    let inputs: Vec<_> = graph
+        .inputs_of(add)
+        .into_iter()
+        .map(|edge| edge.from)
+        .collect();
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(inputs[0], inputs[1]);
+
+    assert!(build_program(&graph, ShaderType::Vertex).is_ok());
+}
+
+#[test]
+fn test_conflicting_inputs() {
+    let mut graph = Graph::default();
+
+    let input_a = add_input(&mut graph, TypeName::VEC3);
+    let input_b = add_input(&mut graph, TypeName::VEC4);
+    let output_a = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let output_b = graph.add_node(Node::Output(
+        1,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(input_a, output_a, 0);
+    graph.add_edge(input_b, output_b, 0);
+
+    match eliminate_common_subexpressions(&mut graph) {
+        Err(Error::ConflictingDeclarations(first, second)) => {
+            let mut nodes = [first, second];
+            nodes.sort();
+            assert_eq!(nodes, [input_a, input_b]);
+        }
+        res => panic!("unexpected result {:?}", res),
+    }
+}
+
+#[test]
+fn test_conflict_leaves_graph() {
+    let mut graph = Graph::default();
+
+    let input_a = add_input(&mut graph, TypeName::VEC3);
+    let input_b = add_input(&mut graph, TypeName::VEC3);
+    let input_c = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC3,
+        VariableName::Named(String::from("a_position")),
+        Attributes::default(),
+    ));
+    let normalize_a = graph.add_node(Node::Normalize);
+    let normalize_b = graph.add_node(Node::Normalize);
+    let add = graph.add_node(Node::Add);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let output_c = graph.add_node(Node::Output(
+        1,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(input_a, normalize_a, 0);
+    graph.add_edge(input_b, normalize_b, 0);
+    graph.add_edge(normalize_a, add, 0);
+    graph.add_edge(normalize_b, add, 1);
+    graph.add_edge(add, output, 0);
+    graph.add_edge(input_c, output_c, 0);
+
+    match eliminate_common_subexpressions(&mut graph) {
+        Err(Error::ConflictingDeclarations(..)) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+
+    // The mergeable nodes are only merged once all the declarations are known to match
+    assert_eq!(graph.node_count(), 8);
+    assert_eq!(graph.edge_count(), 6);
+}
+
+#[test]
+fn test_sampler_and_uniform() {
+    let mut graph = Graph::default();
+
+    // Samplers are declared apart from the uniform block, at their own locations
+    let sampler = graph.add_node(Node::Uniform(0, TypeName::SAMPLER2D, VariableName::None));
+    let scale = graph.add_node(Node::Uniform(0, TypeName::FLOAT, VariableName::None));
+    let uv = graph.add_node(Node::Constant(TypedValue::Vec2(0.5, 0.5)));
+    let sample = graph.add_node(Node::Sample);
+    let multiply = graph.add_node(Node::Multiply);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(sampler, sample, 0);
+    graph.add_edge(uv, sample, 1);
+    graph.add_edge(sample, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, output, 0);
+
+    assert_eq!(eliminate_common_subexpressions(&mut graph).unwrap(), 0);
+    assert!(build_program(&graph, ShaderType::Fragment).is_ok());
+}
+
+#[test]
+fn test_signed_zero() {
+    let mut graph = Graph::default();
+
+    let zero = graph.add_node(Node::Constant(TypedValue::Float(0.0)));
+    let negative_zero = graph.add_node(Node::Constant(TypedValue::Float(-0.0)));
+    let other_zero = graph.add_node(Node::Constant(TypedValue::Float(0.0)));
+    let add = graph.add_node(Node::Add);
+    let subtract = graph.add_node(Node::Subtract);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(zero, add, 0);
+    graph.add_edge(negative_zero, add, 1);
+    graph.add_edge(add, subtract, 0);
+    graph.add_edge(other_zero, subtract, 1);
+    graph.add_edge(subtract, output, 0);
+
+    // Only the constants with the same bits are merged
+    assert_eq!(eliminate_common_subexpressions(&mut graph).unwrap(), 1);
+    assert_eq!(graph.node_count(), 5);
+}
@@ -0,0 +1,114 @@
+extern crate rasen;
+
+use rasen::{errors::Error, module::GraphRef, prelude::*};
+
+/// Build a graph with two independent broken branches
+fn broken_graph() -> (Graph, NodeIndex, NodeIndex) {
+    let mut graph = Graph::default();
+
+    let normal = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let uv = graph.add_node(Node::Input(
+        1,
+        TypeName::VEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    // Normalize doesn't take a vec3 and a vec2
+    let normalize = graph.add_node(Node::Normalize);
+    let color = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(normal, normalize, 0);
+    graph.add_edge(uv, normalize, 1);
+    graph.add_edge(normalize, color, 0);
+
+    // A vec3 cannot be multiplied by a vec2
+    let multiply = graph.add_node(Node::Multiply);
+    let extract = graph.add_node(Node::Extract(0));
+    let value = graph.add_node(Node::Output(
+        1,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(normal, multiply, 0);
+    graph.add_edge(uv, multiply, 1);
+    graph.add_edge(multiply, extract, 0);
+    graph.add_edge(extract, value, 0);
+
+    (graph, normalize, multiply)
+}
+
+#[test]
+fn test_first_error() {
+    let (graph, _, _) = broken_graph();
+
+    let err = build_program(&graph, ShaderType::Fragment).unwrap_err();
+    match err {
+        Error::BuildError {
+            location:
+                SourceLocation {
+                    graph: GraphRef::Main,
+                    ..
+                },
+            ..
+        } => {}
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+}
+
+#[test]
+fn test_collect_errors() {
+    let (graph, normalize, multiply) = broken_graph();
+
+    let err = build_program(
+        &graph,
+        Settings {
+            collect_errors: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap_err();
+
+    let errors = match err {
+        Error::MultipleErrors(errors) => errors,
+        ref kind => panic!("unexpected error {:?}", kind),
+    };
+
+    // The dependents of the failing nodes are not reported
+    let mut nodes: Vec<_> = {
+        errors
+            .iter()
+            .map(|err| match *err {
+                Error::BuildError {
+                    location,
+                    name,
+                    ref args,
+                    ..
+                } => {
+                    assert_eq!(location.graph, GraphRef::Main);
+                    (location.node, name, args.clone())
+                }
+                ref kind => panic!("unexpected error {:?}", kind),
+            })
+            .collect()
+    };
+    nodes.sort_by_key(|&(index, _, _)| index);
+
+    assert_eq!(
+        nodes,
+        vec![
+            (normalize, "Normalize", vec![TypeName::VEC3, TypeName::VEC2]),
+            (multiply, "Multiply", vec![TypeName::VEC3, TypeName::VEC2]),
+        ]
+    );
+}
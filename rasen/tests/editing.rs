@@ -0,0 +1,107 @@
+extern crate rasen;
+
+use rasen::{errors::Error, prelude::*};
+
+fn build_graph() -> (Graph, [NodeIndex; 4]) {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let scale = graph.add_node(Node::Constant(TypedValue::Float(2.0)));
+    let multiply = graph.add_node(Node::Multiply);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(input, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, output, 0);
+
+    (graph, [input, scale, multiply, output])
+}
+
+#[test]
+fn test_ports() {
+    let (graph, [input, scale, multiply, output]) = build_graph();
+
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.edge_count(), 3);
+
+    let inputs: Vec<_> = {
+        graph
+            .inputs_of(multiply)
+            .into_iter()
+            .map(|edge| (edge.port, edge.from))
+            .collect()
+    };
+    assert_eq!(inputs, vec![(0, input), (1, scale)]);
+
+    let outputs: Vec<_> = graph.outputs_of(multiply).map(|edge| edge.to).collect();
+    assert_eq!(outputs, vec![output]);
+
+    assert_eq!(graph.edges().count(), 3);
+    assert_eq!(graph.nodes().count(), 4);
+}
+
+#[test]
+fn test_remove() {
+    let (mut graph, [input, scale, multiply, output]) = build_graph();
+
+    let edge = graph.inputs_of(multiply)[1].index;
+    assert_eq!(graph.remove_edge(edge), Some(1));
+    assert_eq!(graph.inputs_of(multiply).len(), 1);
+
+    match graph.remove_node(scale) {
+        Some(Node::Constant(TypedValue::Float(_))) => {}
+        node => panic!("unexpected node {:?}", node),
+    }
+    assert!(!graph.contains_node(scale));
+
+    // The indices of the remaining nodes are still valid
+    graph.remove_node(multiply);
+    graph.add_edge(input, output, 0);
+    assert_eq!(graph.inputs_of(output)[0].from, input);
+    assert!(build_program(&graph, ShaderType::Vertex).is_ok());
+}
+
+#[test]
+fn test_replace() {
+    let (mut graph, [_, scale, multiply, _]) = build_graph();
+
+    match graph.replace_node(multiply, Node::Add) {
+        Some(Node::Multiply) => {}
+        node => panic!("unexpected node {:?}", node),
+    }
+    match graph[multiply] {
+        Node::Add => {}
+        ref node => panic!("unexpected node {:?}", node),
+    }
+
+    graph[scale] = Node::Constant(TypedValue::Float(3.0));
+    assert_eq!(graph.inputs_of(multiply).len(), 2);
+    assert!(build_program(&graph, ShaderType::Vertex).is_ok());
+}
+
+#[test]
+fn test_duplicate_ports() {
+    let (mut graph, [input, _, multiply, _]) = build_graph();
+    assert!(graph.duplicate_ports(multiply).is_empty());
+
+    graph.add_edge(input, multiply, 1);
+    assert_eq!(graph.duplicate_ports(multiply), vec![1]);
+
+    match build_program(&graph, ShaderType::Vertex) {
+        Err(err) => match err {
+            Error::DuplicateEdge(node, 1) => assert_eq!(node, multiply),
+            ref kind => panic!("unexpected error {:?}", kind),
+        },
+        Ok(_) => panic!("duplicate port not detected"),
+    }
+}
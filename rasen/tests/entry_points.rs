@@ -0,0 +1,222 @@
+extern crate rasen;
+
+mod common;
+
+use common::count_matches;
+use rasen::{
+    errors::Error,
+    module::{FunctionRef, GraphRef},
+    prelude::*,
+};
+
+/// Get the interface variables listed by the entry point with the given name
+fn interface<'a>(assembly: &'a str, name: &str) -> Vec<&'a str> {
+    let quoted = format!("\"{}\"", name);
+    let line = assembly
+        .lines()
+        .find(|l| l.starts_with("OpEntryPoint") && l.contains(&quoted))
+        .unwrap();
+
+    line.split('"').nth(2).unwrap().split_whitespace().collect()
+}
+
+/// Forward a float value through a function shared by all the entry points
+fn forward(graph: &mut Graph, func: FunctionRef, input: &str, output: &str) {
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::FLOAT,
+        VariableName::Named(String::from(input)),
+        Attributes::default(),
+    ));
+    let call = graph.add_node(Node::Call(func));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::Named(String::from(output)),
+        Attributes::default(),
+    ));
+
+    graph.add_edge(input, call, 0);
+    graph.add_edge(call, output, 0);
+}
+
+fn build_module() -> Module {
+    let mut module = Module::default();
+
+    let func = module.add_function();
+    {
+        let graph = &mut module[func];
+        let input = graph.add_node(Node::Parameter(0, TypeName::FLOAT));
+        let half = graph.add_node(Node::Constant(TypedValue::Float(0.5)));
+        let multiply = graph.add_node(Node::Multiply);
+        let output = graph.add_node(Node::Return);
+
+        graph.add_edge(input, multiply, 0);
+        graph.add_edge(half, multiply, 1);
+        graph.add_edge(multiply, output, 0);
+    }
+
+    let vertex = module.add_entry_point("vs_main", ShaderType::Vertex);
+    forward(&mut module[vertex], func, "a_value", "v_value");
+
+    let fragment = module.add_entry_point("fs_main", ShaderType::Fragment);
+    forward(&mut module[fragment], func, "v_value", "o_value");
+
+    module
+}
+
+#[test]
+fn test_multiple_entry_points() {
+    let assembly = build_program_assembly(&build_module(), ShaderType::Vertex).unwrap();
+
+    assert_eq!(count_matches(&assembly, "OpEntryPoint"), 2);
+    assert_eq!(count_matches(&assembly, "OpEntryPoint Vertex"), 1);
+    assert_eq!(count_matches(&assembly, "OpEntryPoint Fragment"), 1);
+    assert_eq!(count_matches(&assembly, "\"main\""), 0);
+    assert_eq!(count_matches(&assembly, "OriginUpperLeft"), 1);
+
+    // The function, types and constants are shared by both entry points
+    assert_eq!(count_matches(&assembly, "= OpFunction "), 3);
+    assert_eq!(count_matches(&assembly, "OpTypeFloat 32"), 1);
+    assert_eq!(count_matches(&assembly, "0.5"), 1);
+}
+
+#[test]
+fn test_entry_point_interface() {
+    let assembly = build_program_assembly(&build_module(), ShaderType::Vertex).unwrap();
+
+    let vertex = interface(&assembly, "vs_main");
+    let fragment = interface(&assembly, "fs_main");
+
+    assert_eq!(vertex.len(), 2);
+    assert_eq!(fragment.len(), 2);
+    assert!(vertex.iter().all(|id| !fragment.contains(id)));
+}
+
+#[test]
+fn test_main_entry_point() {
+    let mut module = build_module();
+
+    let value = module.main.add_node(Node::Constant(TypedValue::Float(1.0)));
+    let output = module.main.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    module.main.add_edge(value, output, 0);
+
+    let assembly = build_program_assembly(&module, ShaderType::Fragment).unwrap();
+
+    assert_eq!(count_matches(&assembly, "OpEntryPoint"), 3);
+    assert_eq!(
+        count_matches(&assembly, "OpEntryPoint Fragment %4 \"main\""),
+        1
+    );
+    assert_eq!(count_matches(&assembly, "OriginUpperLeft"), 2);
+}
+
+#[test]
+fn test_duplicate_entry_point() {
+    let mut module = build_module();
+    module.add_entry_point("vs_main", ShaderType::Vertex);
+
+    let err = build_program_assembly(&module, ShaderType::Vertex).unwrap_err();
+    match err {
+        Error::DuplicateEntryPoint(ref name) => assert_eq!(name, "vs_main"),
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+
+    // The same name can be reused for different stages
+    let mut module = build_module();
+    module.add_entry_point("fs_main", ShaderType::Vertex);
+    assert!(build_program_assembly(&module, ShaderType::Vertex).is_ok());
+}
+
+/// Build a function returning the value of an input
+fn read_input(module: &mut Module, ty: &'static TypeName) -> FunctionRef {
+    let func = module.add_function();
+    let graph = &mut module[func];
+
+    let input = graph.add_node(Node::Input(
+        0,
+        ty,
+        VariableName::Named(String::from("a_value")),
+        Attributes::default(),
+    ));
+    let output = graph.add_node(Node::Return);
+    graph.add_edge(input, output, 0);
+
+    func
+}
+
+/// Write the result of a function to an output
+fn call(graph: &mut Graph, func: FunctionRef, ty: &'static TypeName) {
+    let call = graph.add_node(Node::Call(func));
+    let output = graph.add_node(Node::Output(
+        0,
+        ty,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(call, output, 0);
+}
+
+#[test]
+fn test_function_interface() {
+    let mut module = Module::default();
+    let func = read_input(&mut module, TypeName::FLOAT);
+
+    call(&mut module.main, func, TypeName::FLOAT);
+    let fragment = module.add_entry_point("fs_main", ShaderType::Fragment);
+    call(&mut module[fragment], func, TypeName::FLOAT);
+
+    let assembly = build_program_assembly(&module, ShaderType::Vertex).unwrap();
+
+    // The function is built for each entry point, reading its own input
+    assert_eq!(count_matches(&assembly, "= OpFunction "), 4);
+
+    let vertex = interface(&assembly, "main");
+    let fragment = interface(&assembly, "fs_main");
+
+    assert_eq!(vertex.len(), 2);
+    assert_eq!(fragment.len(), 2);
+    assert!(vertex.iter().all(|id| !fragment.contains(id)));
+}
+
+#[test]
+fn test_function_stage() {
+    let mut module = Module::default();
+    let func = read_input(&mut module, TypeName::INT);
+
+    // Integer inputs only need to be Flat in fragment shaders
+    let vertex = module.add_entry_point("vs_main", ShaderType::Vertex);
+    call(&mut module[vertex], func, TypeName::INT);
+
+    let value = module.main.add_node(Node::Constant(TypedValue::Float(1.0)));
+    let output = module.main.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    module.main.add_edge(value, output, 0);
+
+    assert!(build_program_assembly(&module, ShaderType::Fragment).is_ok());
+
+    let fragment = module.add_entry_point("fs_main", ShaderType::Fragment);
+    call(&mut module[fragment], func, TypeName::INT);
+
+    match build_program_assembly(&module, ShaderType::Fragment).unwrap_err() {
+        Error::BuildError {
+            name: "Input",
+            ref path,
+            ..
+        } => {
+            assert_eq!(path.len(), 1);
+            assert_eq!(path[0].graph, GraphRef::EntryPoint(fragment));
+        }
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+}
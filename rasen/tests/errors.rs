@@ -0,0 +1,137 @@
+extern crate rasen;
+
+use std::error::Error as StdError;
+
+use rasen::{errors::Error, module::GraphRef, prelude::*};
+
+#[test]
+fn test_type_mismatch() {
+    let mut graph = Graph::default();
+
+    let x = graph.add_node(Node::Constant(TypedValue::Float(1.0)));
+    let y = graph.add_node(Node::Constant(TypedValue::Float(0.0)));
+    let z = graph.add_node(Node::Constant(TypedValue::Int(0)));
+    let construct = graph.add_node(Node::Construct(TypeName::VEC3));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(x, construct, 0);
+    graph.add_edge(y, construct, 1);
+    graph.add_edge(z, construct, 2);
+    graph.add_edge(construct, output, 0);
+
+    let err = build_program(&graph, ShaderType::Fragment).unwrap_err();
+    match err {
+        Error::BuildError {
+            location,
+            name: "Construct",
+            ref path,
+            ref source,
+            ..
+        } => {
+            assert_eq!(location.graph, GraphRef::Main);
+            assert_eq!(location.node, construct);
+            assert!(path.is_empty());
+            assert_eq!(
+                **source,
+                Error::TypeMismatch {
+                    port: 2,
+                    expected: TypeName::FLOAT,
+                    actual: TypeName::INT,
+                }
+            );
+        }
+        ref err => panic!("unexpected error {:?}", err),
+    }
+
+    assert!(err.source().is_some());
+}
+
+#[test]
+fn test_call_path() {
+    let mut module = Module::default();
+
+    // Extracting a component of a float fails on the argument of the node
+    let inner = module.add_function();
+    let extract = {
+        let graph = &mut module[inner];
+        let param = graph.add_node(Node::Parameter(0, TypeName::FLOAT));
+        let extract = graph.add_node(Node::Extract(0));
+        let ret = graph.add_node(Node::Return);
+
+        graph.add_edge(param, extract, 0);
+        graph.add_edge(extract, ret, 0);
+        extract
+    };
+
+    let outer = module.add_function();
+    let inner_call = {
+        let graph = &mut module[outer];
+        let param = graph.add_node(Node::Parameter(0, TypeName::FLOAT));
+        let call = graph.add_node(Node::Call(inner));
+        let ret = graph.add_node(Node::Return);
+
+        graph.add_edge(param, call, 0);
+        graph.add_edge(call, ret, 0);
+        call
+    };
+
+    let outer_call = {
+        let graph = &mut module.main;
+        let value = graph.add_node(Node::Constant(TypedValue::Float(1.0)));
+        let call = graph.add_node(Node::Call(outer));
+        let output = graph.add_node(Node::Output(
+            0,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+
+        graph.add_edge(value, call, 0);
+        graph.add_edge(call, output, 0);
+        call
+    };
+
+    let err = build_program(&module, ShaderType::Fragment).unwrap_err();
+    match err {
+        Error::BuildError {
+            location,
+            ref path,
+            ref source,
+            ..
+        } => {
+            assert_eq!(
+                location,
+                SourceLocation {
+                    graph: GraphRef::Function(inner),
+                    node: extract,
+                }
+            );
+            assert_eq!(
+                *path,
+                vec![
+                    SourceLocation {
+                        graph: GraphRef::Main,
+                        node: outer_call,
+                    },
+                    SourceLocation {
+                        graph: GraphRef::Function(outer),
+                        node: inner_call,
+                    },
+                ]
+            );
+            assert_eq!(
+                **source,
+                Error::BadArguments {
+                    args: Box::new([TypeName::FLOAT]),
+                    port: Some(0),
+                }
+            );
+        }
+        ref err => panic!("unexpected error {:?}", err),
+    }
+}
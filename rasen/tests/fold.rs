@@ -0,0 +1,99 @@
+extern crate rasen;
+
+use rasen::{passes::fold_constants, prelude::*};
+
+fn add_output(graph: &mut Graph, ty: &'static TypeName) -> NodeIndex {
+    graph.add_node(Node::Output(
+        0,
+        ty,
+        VariableName::None,
+        Attributes::default(),
+    ))
+}
+
+#[test]
+fn test_fold_subtree() {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::INT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let two = graph.add_node(Node::Constant(TypedValue::Int(2)));
+    let three = graph.add_node(Node::Constant(TypedValue::Int(3)));
+    let four = graph.add_node(Node::Constant(TypedValue::Int(4)));
+    let sum = graph.add_node(Node::Add);
+    let product = graph.add_node(Node::Multiply);
+    let add = graph.add_node(Node::Add);
+    let output = add_output(&mut graph, TypeName::INT);
+
+    graph.add_edge(two, sum, 0);
+    graph.add_edge(three, sum, 1);
+    graph.add_edge(sum, product, 0);
+    graph.add_edge(four, product, 1);
+    graph.add_edge(input, add, 0);
+    graph.add_edge(product, add, 1);
+    graph.add_edge(add, output, 0);
+
+    assert_eq!(fold_constants(&mut graph), 1);
+
+    // The constant subtree is replaced by its root, the input is left untouched
+    assert_eq!(graph.node_count(), 4);
+    assert_eq!(graph.edge_count(), 3);
+    match graph[product] {
+        Node::Constant(TypedValue::Int(20)) => {}
+        ref node => panic!("unexpected node {:?}", node),
+    }
+
+    for &index in &[two, three, four, sum] {
+        assert!(!graph.contains_node(index));
+    }
+
+    assert!(build_program(&graph, ShaderType::Vertex).is_ok());
+}
+
+#[test]
+fn test_division_by_zero() {
+    let mut graph = Graph::default();
+
+    let one = graph.add_node(Node::Constant(TypedValue::Int(1)));
+    let zero = graph.add_node(Node::Constant(TypedValue::Int(0)));
+    let divide = graph.add_node(Node::Divide);
+    let output = add_output(&mut graph, TypeName::INT);
+
+    graph.add_edge(one, divide, 0);
+    graph.add_edge(zero, divide, 1);
+    graph.add_edge(divide, output, 0);
+
+    assert_eq!(fold_constants(&mut graph), 0);
+    assert_eq!(graph.node_count(), 4);
+    match graph[divide] {
+        Node::Divide => {}
+        ref node => panic!("unexpected node {:?}", node),
+    }
+}
+
+#[test]
+fn test_fold_vector() {
+    let mut graph = Graph::default();
+
+    let vector = graph.add_node(Node::Constant(TypedValue::Vec3(3.0, 0.0, 4.0)));
+    let normalize = graph.add_node(Node::Normalize);
+    let extract = graph.add_node(Node::Extract(2));
+    let output = add_output(&mut graph, TypeName::FLOAT);
+
+    graph.add_edge(vector, normalize, 0);
+    graph.add_edge(normalize, extract, 0);
+    graph.add_edge(extract, output, 0);
+
+    assert_eq!(fold_constants(&mut graph), 1);
+    assert_eq!(graph.node_count(), 2);
+    match graph[extract] {
+        Node::Constant(TypedValue::Float(val)) => assert!((val - 0.8).abs() < 1e-6),
+        ref node => panic!("unexpected node {:?}", node),
+    }
+
+    assert!(build_program(&graph, ShaderType::Fragment).is_ok());
+}
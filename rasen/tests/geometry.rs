@@ -0,0 +1,129 @@
+extern crate insta;
+extern crate rasen;
+extern crate rspirv;
+
+mod common;
+
+use common::has_line;
+use rasen::{errors::Error, prelude::*};
+
+include!("../../tests/update.rs");
+
+/// Build a geometry shader forwarding the position and color of each vertex of a triangle
+fn build_passthrough() -> Graph {
+    let mut graph = Graph::default();
+    let mut prev = None;
+
+    for i in 0..3 {
+        let index = graph.add_node(Node::Constant(TypedValue::Int(i)));
+
+        let pos = graph.add_node(Node::ArrayedInput(
+            0,
+            TypeName::VEC4,
+            VariableName::BuiltIn(BuiltIn::Position),
+            Attributes::default(),
+        ));
+        let color = graph.add_node(Node::ArrayedInput(
+            0,
+            TypeName::VEC4,
+            VariableName::Named(String::from("v_color")),
+            Attributes::default(),
+        ));
+
+        let o_pos = graph.add_node(Node::Output(
+            0,
+            TypeName::VEC4,
+            VariableName::BuiltIn(BuiltIn::Position),
+            Attributes::default(),
+        ));
+        let o_color = graph.add_node(Node::Output(
+            0,
+            TypeName::VEC4,
+            VariableName::Named(String::from("f_color")),
+            Attributes::default(),
+        ));
+
+        let emit = graph.add_node(Node::EmitVertex);
+
+        graph.add_edge(index, pos, 0);
+        graph.add_edge(index, color, 0);
+        graph.add_edge(pos, o_pos, 0);
+        graph.add_edge(color, o_color, 0);
+
+        // Chain the emits on port 0 so they happen in order,
+        // after the outputs of the vertex have been written
+        if let Some(prev) = prev {
+            graph.add_edge(prev, emit, 0);
+        }
+        graph.add_edge(o_pos, emit, 1);
+        graph.add_edge(o_color, emit, 2);
+
+        prev = Some(emit);
+    }
+
+    let end = graph.add_node(Node::EndPrimitive);
+    graph.add_edge(prev.unwrap(), end, 0);
+
+    graph
+}
+
+#[test]
+fn test_build_passthrough() {
+    let graph = build_passthrough();
+    let assembly = build_module(&graph, ShaderType::Geometry).unwrap();
+
+    assert_spirv_snapshot_matches!("passthrough.geom", assembly);
+}
+
+#[test]
+fn test_geometry_settings() {
+    let graph = build_passthrough();
+    let assembly = build_program_assembly(
+        &graph,
+        Settings {
+            mod_type: ShaderType::Geometry,
+            geometry: GeometrySettings {
+                input: InputPrimitive::Lines,
+                output: OutputPrimitive::LineStrip,
+                max_vertices: 6,
+                invocations: 2,
+            },
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert!(has_line(&assembly, "InputLines"));
+    assert!(has_line(&assembly, "Invocations 2"));
+    assert!(has_line(&assembly, "OutputLineStrip"));
+    assert!(has_line(&assembly, "OutputVertices 6"));
+    assert!(assembly
+        .lines()
+        .any(|line| line.contains("OpConstant") && line.trim().ends_with(" 2")));
+}
+
+#[test]
+fn test_emit_outside_geometry() {
+    let graph = build_passthrough();
+    assert!(build_program_assembly(&graph, ShaderType::Vertex).is_err());
+}
+
+#[test]
+fn test_sequencing_arguments() {
+    let wrong_count = |graph: &Graph| match build_program_assembly(graph, ShaderType::Geometry) {
+        Err(Error::BuildError { source, .. }) => match *source {
+            Error::WrongArgumentsCount(actual, 1) => actual,
+            ref err => panic!("unexpected error {:?}", err),
+        },
+        res => panic!("unexpected result {:?}", res),
+    };
+
+    // Both nodes need an argument to be ordered with the rest of the graph
+    let mut graph = Graph::default();
+    graph.add_node(Node::EmitVertex);
+    assert_eq!(wrong_count(&graph), 0);
+
+    let mut graph = Graph::default();
+    graph.add_node(Node::EndPrimitive);
+    assert_eq!(wrong_count(&graph), 0);
+}
@@ -0,0 +1,94 @@
+extern crate rasen;
+
+use rasen::{errors::Error, prelude::*};
+
+include!("../../tests/graph.rs");
+
+#[test]
+fn test_infer_complete() {
+    let graph = build_basic_frag();
+    let types = graph.infer_types();
+
+    assert_eq!(types.len(), graph.node_count());
+    for (index, node) in graph.nodes() {
+        let ty = types[&index].as_ref().unwrap();
+        match *node {
+            Node::Normalize => assert_eq!(*ty, TypeName::VEC3),
+            Node::Dot | Node::Clamp => assert_eq!(*ty, TypeName::FLOAT),
+            Node::Sample | Node::Multiply | Node::Output(..) => assert_eq!(*ty, TypeName::VEC4),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_infer_partial() {
+    let mut graph = Graph::default();
+
+    let normal = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let scale = graph.add_node(Node::Constant(TypedValue::Float(2.0)));
+    let multiply = graph.add_node(Node::Multiply);
+    let dot = graph.add_node(Node::Dot);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let unconnected = graph.add_node(Node::Normalize);
+
+    graph.add_edge(normal, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, dot, 0);
+    graph.add_edge(dot, output, 0);
+
+    let types = graph.infer_types();
+
+    assert_eq!(types[&normal].as_ref().unwrap(), &TypeName::VEC3);
+    assert_eq!(types[&scale].as_ref().unwrap(), &TypeName::FLOAT);
+    assert_eq!(types[&multiply].as_ref().unwrap(), &TypeName::VEC3);
+
+    // The dot product is missing its second argument
+    match *types[&dot].as_ref().unwrap_err() {
+        Error::WrongArgumentsCount(1, 2) => {}
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+    match *types[&output].as_ref().unwrap_err() {
+        Error::UntypedArgument(0) => {}
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+    match *types[&unconnected].as_ref().unwrap_err() {
+        Error::WrongArgumentsCount(0, 1) => {}
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+
+    // Connecting the missing argument makes the whole graph valid
+    graph.add_edge(multiply, dot, 1);
+    graph.remove_node(unconnected);
+
+    let types = graph.infer_types();
+    assert!(types.values().all(Result::is_ok));
+    assert_eq!(types[&output].as_ref().unwrap(), &TypeName::FLOAT);
+}
+
+#[test]
+fn test_infer_cycle() {
+    let mut graph = Graph::default();
+
+    let add = graph.add_node(Node::Add);
+    let multiply = graph.add_node(Node::Multiply);
+    graph.add_edge(add, multiply, 0);
+    graph.add_edge(multiply, add, 0);
+
+    let types = graph.infer_types();
+    assert!(types.values().all(Result::is_err));
+    assert!(types.values().any(|res| match *res.as_ref().unwrap_err() {
+        Error::CyclicGraph => true,
+        _ => false,
+    }));
+}
@@ -0,0 +1,191 @@
+extern crate rasen;
+
+use rasen::{
+    module::{FunctionRef, InlineHint},
+    passes::inline_functions,
+    prelude::*,
+};
+
+/// Build a module doubling its input in a function
+fn build_module() -> (Module, FunctionRef) {
+    let mut module = Module::default();
+
+    let double = module.add_function();
+    {
+        let graph = &mut module[double];
+        let param = graph.add_node(Node::Parameter(0, TypeName::FLOAT));
+        let add = graph.add_node(Node::Add);
+        let ret = graph.add_node(Node::Return);
+
+        graph.add_edge(param, add, 0);
+        graph.add_edge(param, add, 1);
+        graph.add_edge(add, ret, 0);
+    }
+
+    {
+        let graph = &mut module.main;
+        let input = graph.add_node(Node::Input(
+            0,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+        let call = graph.add_node(Node::Call(double));
+        let output = graph.add_node(Node::Output(
+            0,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+
+        graph.add_edge(input, call, 0);
+        graph.add_edge(call, output, 0);
+    }
+
+    (module, double)
+}
+
+#[test]
+fn test_inline() {
+    let (mut module, _) = build_module();
+
+    assert_eq!(inline_functions(&mut module, &Settings::default()), 1);
+    assert_eq!(module.main.node_count(), 3);
+    assert_eq!(module.main.edge_count(), 3);
+    assert!(module.main.nodes().all(|(_, node)| match *node {
+        Node::Call(_) => false,
+        _ => true,
+    }));
+
+    let assembly = build_program_assembly(&module, ShaderType::Fragment).unwrap();
+    assert!(!assembly.contains("OpFunctionCall"));
+}
+
+#[test]
+fn test_inline_settings() {
+    let (module, _) = build_module();
+
+    let settings = Settings {
+        inlining: InlineSettings {
+            enabled: true,
+            ..InlineSettings::default()
+        },
+        ..Settings::default()
+    };
+
+    let assembly = build_program_assembly(&module, settings).unwrap();
+    assert!(!assembly.contains("OpFunctionCall"));
+}
+
+#[test]
+fn test_never_inline() {
+    let (mut module, double) = build_module();
+    assert!(module.set_inline_hint(double, InlineHint::Never));
+
+    assert_eq!(inline_functions(&mut module, &Settings::default()), 0);
+
+    let assembly = build_program_assembly(&module, ShaderType::Fragment).unwrap();
+    assert!(assembly.contains("OpFunctionCall"));
+    assert!(assembly.contains("DontInline"));
+}
+
+#[test]
+fn test_inline_hint_unknown_function() {
+    let (_, double) = build_module();
+    let mut module = Module::default();
+
+    assert!(!module.set_inline_hint(double, InlineHint::Always));
+    assert_eq!(module.inline_hint(double), InlineHint::Auto);
+}
+
+#[test]
+fn test_inline_entry_point_stage() {
+    let (mut module, double) = build_module();
+
+    // Patch inputs can only be typed in a tessellation evaluation shader
+    let entry = module.add_entry_point("tese", ShaderType::TessellationEvaluation);
+    {
+        let graph = &mut module[entry];
+        let input = graph.add_node(Node::Input(
+            1,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes {
+                patch: true,
+                ..Attributes::default()
+            },
+        ));
+        let call = graph.add_node(Node::Call(double));
+        let output = graph.add_node(Node::Output(
+            0,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+
+        graph.add_edge(input, call, 0);
+        graph.add_edge(call, output, 0);
+    }
+
+    assert!(module.set_inline_hint(double, InlineHint::Always));
+    assert_eq!(inline_functions(&mut module, &Settings::default()), 2);
+    assert!(module[entry].nodes().all(|(_, node)| match *node {
+        Node::Call(_) => false,
+        _ => true,
+    }));
+}
+
+#[test]
+fn test_inline_chained_calls() {
+    let (mut module, double) = build_module();
+
+    // Calls `double` twice, each call taking the result of the previous one
+    let quadruple = module.add_function();
+    {
+        let graph = &mut module[quadruple];
+        let param = graph.add_node(Node::Parameter(0, TypeName::FLOAT));
+        let first = graph.add_node(Node::Call(double));
+        let second = graph.add_node(Node::Call(double));
+        let ret = graph.add_node(Node::Return);
+
+        graph.add_edge(param, first, 0);
+        graph.add_edge(first, second, 0);
+        graph.add_edge(second, ret, 0);
+    }
+
+    {
+        let graph = &mut module.main;
+        let input = graph.add_node(Node::Input(
+            1,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+        let call = graph.add_node(Node::Call(quadruple));
+        let add = graph.add_node(Node::Add);
+        let outer = graph.add_node(Node::Call(double));
+        let output = graph.add_node(Node::Output(
+            1,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+
+        // The argument of the outer call can only be typed once the inner one is inlined
+        graph.add_edge(input, call, 0);
+        graph.add_edge(call, add, 0);
+        graph.add_edge(input, add, 1);
+        graph.add_edge(add, outer, 0);
+        graph.add_edge(outer, output, 0);
+    }
+
+    // All the calls of the main graph, and the two calls of `quadruple`
+    assert_eq!(inline_functions(&mut module, &Settings::default()), 7);
+    assert!(module.main.nodes().all(|(_, node)| match *node {
+        Node::Call(_) => false,
+        _ => true,
+    }));
+
+    let assembly = build_program_assembly(&module, ShaderType::Fragment).unwrap();
+    assert!(!assembly.contains("OpFunctionCall"));
+}
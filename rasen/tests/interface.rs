@@ -0,0 +1,103 @@
+extern crate rasen;
+
+use rasen::{
+    interface::{check_interface, Mismatch},
+    prelude::*,
+};
+
+include!("../../tests/graph.rs");
+
+/// Build a fragment shader reading a single input
+fn build_consumer(location: u32, attr_type: &'static TypeName, attributes: Attributes) -> Graph {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        location,
+        attr_type,
+        VariableName::Named(String::from("f_uv")),
+        attributes,
+    ));
+    let output = graph.add_node(Node::Output(
+        0,
+        attr_type,
+        VariableName::Named(String::from("o_col")),
+        Attributes::default(),
+    ));
+    graph.add_edge(input, output, 0);
+
+    graph
+}
+
+#[test]
+fn test_compatible() {
+    let vert = build_basic_vert();
+    let frag = build_consumer(2, TypeName::VEC2, Attributes::default());
+
+    assert!(check_interface(&vert, &frag).is_empty());
+}
+
+#[test]
+fn test_mismatches() {
+    let vert = build_basic_vert();
+
+    // The fragment shader expects normals at location 0, but they're written at location 1
+    assert_eq!(
+        check_interface(&vert, &build_basic_frag()),
+        vec![
+            Mismatch::MissingOutput {
+                location: 0,
+                component: 0,
+                name: Some(String::from("f_normal")),
+            },
+            Mismatch::Type {
+                location: 1,
+                component: 0,
+                output: TypeName::VEC3,
+                input: TypeName::VEC2,
+            },
+        ]
+    );
+
+    let frag = build_consumer(3, TypeName::VEC2, Attributes::default());
+    assert_eq!(
+        check_interface(&vert, &frag),
+        vec![Mismatch::Location {
+            name: String::from("f_uv"),
+            output: 2,
+            input: 3,
+        }]
+    );
+
+    let flat = Attributes {
+        flat: true,
+        ..Attributes::default()
+    };
+    let frag = build_consumer(2, TypeName::VEC2, flat);
+    assert_eq!(
+        check_interface(&vert, &frag),
+        vec![Mismatch::Interpolation {
+            location: 2,
+            component: 0,
+            output: Attributes::default(),
+            input: flat,
+        }]
+    );
+}
+
+#[test]
+fn test_reflection() {
+    let vert = ModuleBuilder::from_graph(&build_basic_vert(), Settings::from(ShaderType::Vertex))
+        .unwrap()
+        .reflection();
+
+    // Interpolation decorations are not checked when using a reflection
+    let flat = Attributes {
+        flat: true,
+        ..Attributes::default()
+    };
+    let frag = build_consumer(2, TypeName::VEC2, flat);
+    assert!(check_interface(&vert.entry_points[0], &frag).is_empty());
+
+    let frag = build_consumer(2, TypeName::VEC3, Attributes::default());
+    assert_eq!(check_interface(&vert.entry_points[0], &frag).len(), 1);
+}
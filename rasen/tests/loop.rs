@@ -44,12 +44,14 @@ fn test_build_loop() {
             0,
             TypeName::FLOAT,
             VariableName::Named(String::from("i_value")),
+            Attributes::default(),
         ));
         let reduce = graph.add_node(Node::Loop(func_cond, func_body));
         let output = graph.add_node(Node::Output(
             0,
             TypeName::FLOAT,
             VariableName::Named(String::from("o_value")),
+            Attributes::default(),
         ));
 
         graph.add_edge(input, reduce, 0);
@@ -61,6 +63,7 @@ fn test_build_loop() {
         Settings {
             mod_type: ShaderType::Fragment,
             uniforms_name: Some(String::from("uniforms")),
+            ..Settings::default()
         },
     )
     .expect("from_module");
@@ -0,0 +1,106 @@
+extern crate rasen;
+extern crate rspirv;
+extern crate spirv_headers;
+
+use rasen::prelude::*;
+use rspirv::mr::{Module as SpirvModule, Operand};
+use spirv_headers::{Decoration, Op};
+
+fn id(operand: &Operand) -> u32 {
+    match *operand {
+        Operand::IdRef(id) => id,
+        ref operand => panic!("unexpected operand {:?}", operand),
+    }
+}
+
+/// Get the locations of the inputs loaded by the arguments of an instruction
+fn argument_locations(module: &SpirvModule, opcode: Op) -> Vec<u32> {
+    let location = |var| {
+        module
+            .annotations
+            .iter()
+            .find(|inst| {
+                inst.operands.get(0) == Some(&Operand::IdRef(var))
+                    && inst.operands.get(1) == Some(&Operand::Decoration(Decoration::Location))
+            })
+            .and_then(|inst| match inst.operands[2] {
+                Operand::LiteralInt32(location) => Some(location),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let instructions: Vec<_> = {
+        module.functions[0]
+            .basic_blocks
+            .iter()
+            .flat_map(|block| block.instructions.iter())
+            .collect()
+    };
+
+    let load = |value| {
+        instructions
+            .iter()
+            .find(|inst| inst.class.opcode == Op::Load && inst.result_id == Some(value))
+            .map(|inst| id(&inst.operands[0]))
+            .unwrap()
+    };
+
+    let inst = instructions
+        .iter()
+        .find(|inst| inst.class.opcode == opcode)
+        .unwrap();
+
+    inst.operands
+        .iter()
+        .map(|operand| location(load(id(operand))))
+        .collect()
+}
+
+#[test]
+fn test_integer_vector_operand_order() {
+    let mut graph = Graph::default();
+
+    let lhs = graph.add_node(Node::Input(
+        0,
+        TypeName::IVEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let rhs = graph.add_node(Node::Input(
+        1,
+        TypeName::IVEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    let subtract = graph.add_node(Node::Subtract);
+    let divide = graph.add_node(Node::Divide);
+    let o_sub = graph.add_node(Node::Output(
+        0,
+        TypeName::IVEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let o_div = graph.add_node(Node::Output(
+        1,
+        TypeName::IVEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(lhs, subtract, 0);
+    graph.add_edge(rhs, subtract, 1);
+    graph.add_edge(lhs, divide, 0);
+    graph.add_edge(rhs, divide, 1);
+    graph.add_edge(subtract, o_sub, 0);
+    graph.add_edge(divide, o_div, 0);
+
+    let module = ModuleBuilder::from_graph(&graph, Settings::from(ShaderType::Vertex))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(argument_locations(&module, Op::ISub), [0, 1]);
+    assert_eq!(argument_locations(&module, Op::SDiv), [0, 1]);
+}
@@ -0,0 +1,310 @@
+extern crate rasen;
+extern crate rspirv;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use rasen::{
+    errors::Result,
+    passes::{GraphPass, PassManager, SpirvPass},
+    prelude::*,
+};
+use rspirv::mr::Module as SpirvModule;
+
+/// Build a graph adding a constant subtree and an unused constant to an input
+fn build_graph() -> (Graph, [NodeIndex; 4]) {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let two = graph.add_node(Node::Constant(TypedValue::Float(2.0)));
+    let three = graph.add_node(Node::Constant(TypedValue::Float(3.0)));
+    let unused = graph.add_node(Node::Constant(TypedValue::Float(4.0)));
+    let sum = graph.add_node(Node::Add);
+    let add = graph.add_node(Node::Add);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(two, sum, 0);
+    graph.add_edge(three, sum, 1);
+    graph.add_edge(input, add, 0);
+    graph.add_edge(sum, add, 1);
+    graph.add_edge(add, output, 0);
+
+    (graph, [sum, two, three, unused])
+}
+
+#[test]
+fn test_from_settings() {
+    let (mut graph, [sum, two, three, unused]) = build_graph();
+
+    let settings = Settings {
+        opt_level: OptLevel::Basic,
+        ..Settings::default()
+    };
+
+    let mut manager = PassManager::from_settings(&settings);
+    manager.run_graph(&mut graph, &settings).unwrap();
+
+    let names: Vec<_> = manager.timings().iter().map(|timing| timing.name).collect();
+    assert_eq!(
+        names,
+        [
+            "fold-constants",
+            "eliminate-common-subexpressions",
+            "eliminate-dead-code",
+        ]
+    );
+
+    let changed: Vec<_> = {
+        manager
+            .timings()
+            .iter()
+            .map(|timing| timing.changed)
+            .collect()
+    };
+    assert_eq!(changed, [true, false, true]);
+
+    match graph[sum] {
+        Node::Constant(TypedValue::Float(val)) => assert_eq!(val.to_bits(), 5f32.to_bits()),
+        ref node => panic!("unexpected node {:?}", node),
+    }
+
+    for &index in &[two, three, unused] {
+        assert!(!graph.contains_node(index));
+    }
+
+    assert_eq!(graph.node_count(), 4);
+}
+
+#[test]
+fn test_no_passes() {
+    let (graph, _) = build_graph();
+
+    let mut manager = PassManager::from_settings(&Settings::default());
+    let module = manager.build_graph(&graph, Settings::default()).unwrap();
+    assert!(manager.timings().is_empty());
+    assert_eq!(module.functions.len(), 1);
+}
+
+#[test]
+fn test_build_program_opt_level() {
+    let (graph, _) = build_graph();
+
+    let settings = Settings {
+        opt_level: OptLevel::Full,
+        ..Settings::default()
+    };
+
+    let optimized = build_program(&graph, settings).unwrap();
+    let unoptimized = build_program(&graph, Settings::default()).unwrap();
+    assert!(optimized.len() < unoptimized.len());
+}
+
+#[test]
+fn test_builder_timings() {
+    let (graph, _) = build_graph();
+
+    let settings = Settings {
+        opt_level: OptLevel::Basic,
+        ..Settings::default()
+    };
+
+    let builder = ModuleBuilder::from_graph(&graph, settings).unwrap();
+    let names: Vec<_> = builder
+        .pass_timings()
+        .iter()
+        .map(|timing| timing.name)
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "fold-constants",
+            "eliminate-common-subexpressions",
+            "eliminate-dead-code",
+        ]
+    );
+
+    let builder = ModuleBuilder::from_graph(&graph, Settings::default()).unwrap();
+    assert!(builder.pass_timings().is_empty());
+}
+
+#[test]
+fn test_dead_uniforms() {
+    let mut graph = Graph::default();
+
+    // The uniform at location 0 is only used by a node removed as dead code
+    let scale = graph.add_node(Node::Uniform(0, TypeName::FLOAT, VariableName::None));
+    let unused = graph.add_node(Node::Multiply);
+    graph.add_edge(scale, unused, 0);
+    graph.add_edge(scale, unused, 1);
+
+    let color = graph.add_node(Node::Uniform(1, TypeName::VEC4, VariableName::None));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(color, output, 0);
+
+    let settings = Settings {
+        opt_level: OptLevel::Basic,
+        validate: true,
+        ..Settings::default()
+    };
+
+    // The remaining uniform is the first member of the block
+    let builder = ModuleBuilder::from_graph(&graph, settings).unwrap();
+    let reflection = builder.reflection();
+    assert_eq!(reflection.uniforms.len(), 1);
+    assert_eq!(reflection.uniforms[0].location, 1);
+    assert_eq!(reflection.uniforms[0].offset, 0);
+    assert!(builder.build().is_ok());
+}
+
+#[test]
+fn test_debug_info_disables_passes() {
+    let (graph, _) = build_graph();
+
+    // The nodes must be built as given for the locations to refer to them
+    for &(debug_info, collect_errors) in &[(true, false), (false, true)] {
+        let settings = Settings {
+            debug_info,
+            collect_errors,
+            ..Settings::default()
+        };
+
+        let optimized = build_program(
+            &graph,
+            Settings {
+                opt_level: OptLevel::Full,
+                ..settings.clone()
+            },
+        )
+        .unwrap();
+        let unoptimized = build_program(&graph, settings).unwrap();
+        assert_eq!(optimized, unoptimized);
+    }
+}
+
+/// Replace the constants of a graph with their negation
+struct NegateConstants;
+
+impl GraphPass for NegateConstants {
+    fn name(&self) -> &'static str {
+        "negate-constants"
+    }
+
+    fn run_graph(&mut self, graph: &mut Graph, _: &Settings) -> Result<bool> {
+        let constants: Vec<_> = {
+            graph
+                .nodes()
+                .filter_map(|(index, node)| match *node {
+                    Node::Constant(TypedValue::Float(val)) => Some((index, val)),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        for &(index, val) in &constants {
+            graph.replace_node(index, Node::Constant(TypedValue::Float(-val)));
+        }
+
+        Ok(!constants.is_empty())
+    }
+}
+
+/// Record the number of functions of the built module
+struct CountFunctions(Rc<Cell<usize>>);
+
+impl SpirvPass for CountFunctions {
+    fn name(&self) -> &'static str {
+        "count-functions"
+    }
+
+    fn run(&mut self, module: &mut SpirvModule, _: &Settings) -> Result<bool> {
+        self.0.set(module.functions.len());
+        Ok(false)
+    }
+}
+
+#[test]
+fn test_custom_passes() {
+    let (graph, [sum, ..]) = build_graph();
+    let functions = Rc::new(Cell::new(0));
+
+    let mut manager = PassManager::new();
+    manager.add_graph_pass(NegateConstants);
+    manager.add_spirv_pass(CountFunctions(functions.clone()));
+
+    // The passes run on a copy, and the optimization level of the settings is ignored
+    let settings = Settings {
+        opt_level: OptLevel::Full,
+        ..Settings::default()
+    };
+
+    manager.build_graph(&graph, settings).unwrap();
+    assert_eq!(functions.get(), 1);
+    match graph[sum] {
+        Node::Add => {}
+        ref node => panic!("unexpected node {:?}", node),
+    }
+
+    let names: Vec<_> = manager.timings().iter().map(|timing| timing.name).collect();
+    assert_eq!(names, ["negate-constants", "count-functions"]);
+    assert!(manager.timings()[0].changed);
+    assert!(!manager.timings()[1].changed);
+}
+
+/// Record the stage of the settings each graph is run with
+struct RecordStages(Rc<RefCell<Vec<ShaderType>>>);
+
+impl GraphPass for RecordStages {
+    fn name(&self) -> &'static str {
+        "record-stages"
+    }
+
+    fn run_graph(&mut self, _: &mut Graph, settings: &Settings) -> Result<bool> {
+        self.0.borrow_mut().push(settings.mod_type);
+        Ok(false)
+    }
+}
+
+#[test]
+fn test_entry_point_settings() {
+    let (graph, _) = build_graph();
+    let stages = Rc::new(RefCell::new(Vec::new()));
+
+    let mut module = Module::default();
+    module.main = graph.clone();
+    let vertex = module.add_entry_point("vert", ShaderType::Vertex);
+    module[vertex] = graph;
+    module.add_function();
+
+    let mut manager = PassManager::new();
+    manager.add_graph_pass(RecordStages(stages.clone()));
+    manager
+        .run_module(&mut module, &Settings::from(ShaderType::Fragment))
+        .unwrap();
+
+    // The main graph and the functions keep the stage of the settings
+    assert_eq!(
+        *stages.borrow(),
+        [
+            ShaderType::Fragment,
+            ShaderType::Vertex,
+            ShaderType::Fragment
+        ]
+    );
+}
@@ -0,0 +1,223 @@
+extern crate rasen;
+
+mod common;
+
+use common::{count_lines, has_line};
+use rasen::prelude::*;
+
+#[test]
+fn test_build_double() {
+    let mut graph = Graph::default();
+
+    let pos = graph.add_node(Node::Input(
+        0,
+        TypeName::DVEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let scale = graph.add_node(Node::Constant(TypedValue::Double(2.0)));
+    let offset = graph.add_node(Node::Constant(TypedValue::DVec3(0.5, 0.5, 0.5)));
+
+    let normalize = graph.add_node(Node::Normalize);
+    let multiply = graph.add_node(Node::Multiply);
+    let add = graph.add_node(Node::Add);
+    let length = graph.add_node(Node::Length);
+
+    let o_pos = graph.add_node(Node::Output(
+        0,
+        TypeName::DVEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let o_len = graph.add_node(Node::Output(
+        1,
+        TypeName::DOUBLE,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(pos, normalize, 0);
+    graph.add_edge(normalize, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, add, 0);
+    graph.add_edge(offset, add, 1);
+    graph.add_edge(add, o_pos, 0);
+    graph.add_edge(add, length, 0);
+    graph.add_edge(length, o_len, 0);
+
+    let assembly = build_program_assembly(&graph, ShaderType::Vertex).unwrap();
+
+    assert!(has_line(&assembly, "OpCapability Float64"));
+    assert_eq!(count_lines(&assembly, "OpCapability Float64"), 1);
+    assert!(has_line(&assembly, "OpTypeFloat 64"));
+    assert!(!has_line(&assembly, "OpTypeFloat 32"));
+    assert!(assembly.contains("OpVectorTimesScalar"));
+    assert!(assembly.contains("OpFAdd"));
+    assert!(assembly.contains("Normalize"));
+    assert!(assembly.contains("Length"));
+}
+
+#[test]
+fn test_build_int64() {
+    let mut graph = Graph::default();
+
+    let value = graph.add_node(Node::Input(
+        0,
+        TypeName::I64VEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let factor = graph.add_node(Node::Constant(TypedValue::I64Vec2(3, 5_000_000_000)));
+    let divisor = graph.add_node(Node::Constant(TypedValue::UInt64(7)));
+    let count = graph.add_node(Node::Input(
+        1,
+        TypeName::UINT64,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    let multiply = graph.add_node(Node::Multiply);
+    let subtract = graph.add_node(Node::Subtract);
+    let divide = graph.add_node(Node::Divide);
+
+    let o_value = graph.add_node(Node::Output(
+        0,
+        TypeName::I64VEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let o_count = graph.add_node(Node::Output(
+        1,
+        TypeName::UINT64,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(value, multiply, 0);
+    graph.add_edge(factor, multiply, 1);
+    graph.add_edge(multiply, subtract, 0);
+    graph.add_edge(value, subtract, 1);
+    graph.add_edge(subtract, o_value, 0);
+
+    graph.add_edge(count, divide, 0);
+    graph.add_edge(divisor, divide, 1);
+    graph.add_edge(divide, o_count, 0);
+
+    let assembly = build_program_assembly(&graph, ShaderType::Vertex).unwrap();
+
+    assert!(has_line(&assembly, "OpCapability Int64"));
+    assert!(!has_line(&assembly, "OpCapability Float64"));
+    assert!(has_line(&assembly, "OpTypeInt 64 1"));
+    assert!(has_line(&assembly, "OpTypeInt 64 0"));
+    assert!(assembly.contains("5000000000"));
+    assert!(assembly.contains("OpIMul"));
+    assert!(assembly.contains("OpISub"));
+    assert!(assembly.contains("OpUDiv"));
+}
+
+#[test]
+fn test_int64_type_names() {
+    assert_eq!(format!("{:?}", TypeName::INT64), "int64_t");
+    assert_eq!(format!("{:?}", TypeName::U64VEC3), "u64vec3");
+    assert_eq!(TypeName::from_string("i64vec4"), Some(TypeName::I64VEC4));
+}
+
+#[test]
+fn test_build_half() {
+    let mut graph = Graph::default();
+
+    let color = graph.add_node(Node::Input(
+        0,
+        TypeName::HVEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let scale = graph.add_node(Node::Constant(TypedValue::Half(f16::from(0.5f32))));
+    let multiply = graph.add_node(Node::Multiply);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::HVEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(color, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, output, 0);
+
+    let assembly = build_program_assembly(&graph, ShaderType::Vertex).unwrap();
+
+    assert!(has_line(&assembly, "OpCapability Float16"));
+    assert!(has_line(&assembly, "OpCapability StorageInputOutput16"));
+    assert_eq!(
+        count_lines(&assembly, "OpExtension \"SPV_KHR_16bit_storage\""),
+        1
+    );
+    assert!(has_line(&assembly, "OpTypeFloat 16"));
+    assert!(has_line(&assembly, &format!("{}", 0x3800)));
+    assert!(assembly.contains("OpVectorTimesScalar"));
+}
+
+#[test]
+fn test_build_half_uniform() {
+    let mut graph = Graph::default();
+
+    let value = graph.add_node(Node::Uniform(0, TypeName::HALF, VariableName::None));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::HALF,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(value, output, 0);
+
+    let assembly = build_program_assembly(&graph, ShaderType::Vertex).unwrap();
+
+    assert!(has_line(
+        &assembly,
+        "OpCapability UniformAndStorageBuffer16BitAccess"
+    ));
+}
+
+#[test]
+fn test_relaxed_precision() {
+    let mut graph = Graph::default();
+
+    let normal = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let light = graph.add_node(Node::Constant(TypedValue::Vec3(0.3, -0.5, 0.2)));
+    let normalize = graph.add_node(Node::Normalize);
+    let dot = graph.add_node(Node::Dot);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(normal, normalize, 0);
+    graph.add_edge(normalize, dot, 0);
+    graph.add_edge(light, dot, 1);
+    graph.add_edge(dot, output, 0);
+
+    let assembly = build_program_assembly(
+        &graph,
+        Settings {
+            mod_type: ShaderType::Fragment,
+            relaxed_precision: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    // The input load, the normalize and the dot product are decorated,
+    // but not the constant and the output variable
+    assert_eq!(count_lines(&assembly, "RelaxedPrecision"), 3);
+
+    let assembly = build_program_assembly(&graph, ShaderType::Fragment).unwrap();
+    assert_eq!(count_lines(&assembly, "RelaxedPrecision"), 0);
+}
@@ -0,0 +1,246 @@
+extern crate rasen;
+extern crate rspirv;
+extern crate spirv_headers;
+
+use rasen::{
+    passes::{prune, side_effecting_functions},
+    prelude::*,
+    validate::{validate, ValidationError},
+};
+use rspirv::mr::Operand;
+use spirv_headers::{Decoration, Op};
+
+#[test]
+fn test_prune() {
+    let mut graph = Graph::default();
+
+    let normal = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC3,
+        VariableName::Named(String::from("a_normal")),
+        Attributes::default(),
+    ));
+    let normal_out = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::Named(String::from("v_normal")),
+        Attributes::default(),
+    ));
+    graph.add_edge(normal, normal_out, 0);
+
+    let position = graph.add_node(Node::Constant(TypedValue::Vec4(0.0, 0.0, 0.0, 1.0)));
+    let position_out = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::Position),
+        Attributes::default(),
+    ));
+    graph.add_edge(position, position_out, 0);
+
+    // Only used to compute the output at location 1
+    let depth = graph.add_node(Node::Input(
+        1,
+        TypeName::FLOAT,
+        VariableName::Named(String::from("a_depth")),
+        Attributes::default(),
+    ));
+    let scale = graph.add_node(Node::Uniform(
+        0,
+        TypeName::FLOAT,
+        VariableName::Named(String::from("u_scale")),
+    ));
+    let multiply = graph.add_node(Node::Multiply);
+    let depth_out = graph.add_node(Node::Output(
+        1,
+        TypeName::FLOAT,
+        VariableName::Named(String::from("v_depth")),
+        Attributes::default(),
+    ));
+    graph.add_edge(depth, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, depth_out, 0);
+
+    let pruned = prune(&mut graph, &[0], &[]);
+    assert_eq!(pruned.nodes, 4);
+    assert_eq!(graph.node_count(), 4);
+    for &index in &[depth, scale, multiply, depth_out] {
+        assert!(!graph.contains_node(index));
+    }
+
+    assert_eq!(pruned.inputs.len(), 1);
+    assert_eq!(pruned.inputs[0].location, Some(1));
+    assert_eq!(pruned.inputs[0].name, Some(String::from("a_depth")));
+    assert_eq!(pruned.outputs.len(), 1);
+    assert_eq!(pruned.outputs[0].location, Some(1));
+    assert_eq!(pruned.uniforms.len(), 1);
+    assert_eq!(pruned.uniforms[0].name, Some(String::from("u_scale")));
+    assert!(pruned.samplers.is_empty());
+
+    let (_, reflection) = build_program_with_reflection(&graph, ShaderType::Vertex).unwrap();
+    assert!(reflection.uniforms.is_empty());
+
+    let outputs: Vec<_> = {
+        reflection.entry_points[0]
+            .outputs
+            .iter()
+            .map(|variable| variable.location)
+            .collect()
+    };
+    assert_eq!(outputs.len(), 2);
+    assert!(!outputs.contains(&Some(1)));
+}
+
+#[test]
+fn test_prune_calls() {
+    let mut module = Module::default();
+
+    // Writes its parameter to an output, without returning anything
+    let write = module.add_function();
+    {
+        let graph = &mut module[write];
+        let param = graph.add_node(Node::Parameter(0, TypeName::FLOAT));
+        let output = graph.add_node(Node::Output(
+            1,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+        graph.add_node(Node::Return);
+
+        graph.add_edge(param, output, 0);
+    }
+
+    let square = module.add_function();
+    {
+        let graph = &mut module[square];
+        let param = graph.add_node(Node::Parameter(0, TypeName::FLOAT));
+        let multiply = graph.add_node(Node::Multiply);
+        let ret = graph.add_node(Node::Return);
+
+        graph.add_edge(param, multiply, 0);
+        graph.add_edge(param, multiply, 1);
+        graph.add_edge(multiply, ret, 0);
+    }
+
+    let (call_write, call_square) = {
+        let graph = &mut module.main;
+        let input = graph.add_node(Node::Input(
+            0,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+        let call_write = graph.add_node(Node::Call(write));
+        let call_square = graph.add_node(Node::Call(square));
+        let output = graph.add_node(Node::Output(
+            0,
+            TypeName::FLOAT,
+            VariableName::None,
+            Attributes::default(),
+        ));
+
+        graph.add_edge(input, call_write, 0);
+        graph.add_edge(input, call_square, 0);
+        graph.add_edge(input, output, 0);
+
+        (call_write, call_square)
+    };
+
+    let effectful = side_effecting_functions(&module);
+    assert_eq!(effectful, vec![write]);
+
+    // The result of neither call is used, but the write must still happen
+    let pruned = prune(&mut module.main, &[0], &effectful);
+    assert_eq!(pruned.nodes, 1);
+    assert!(module.main.contains_node(call_write));
+    assert!(!module.main.contains_node(call_square));
+}
+
+#[test]
+fn test_prune_uniforms() {
+    let mut graph = Graph::default();
+
+    // Only used to compute the output at location 1
+    let u_a = graph.add_node(Node::Uniform(
+        0,
+        TypeName::FLOAT,
+        VariableName::Named(String::from("u_a")),
+    ));
+    let a_out = graph.add_node(Node::Output(
+        1,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(u_a, a_out, 0);
+
+    let u_b = graph.add_node(Node::Uniform(
+        1,
+        TypeName::VEC4,
+        VariableName::Named(String::from("u_b")),
+    ));
+    let b_out = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(u_b, b_out, 0);
+
+    let pruned = prune(&mut graph, &[0], &[]);
+    assert_eq!(pruned.uniforms.len(), 1);
+    assert_eq!(pruned.uniforms[0].location, 0);
+
+    let settings = Settings {
+        mod_type: ShaderType::Fragment,
+        validate: true,
+        ..Settings::default()
+    };
+    let builder = ModuleBuilder::from_graph(&graph, settings).unwrap();
+
+    // The remaining member is moved to the start of the block
+    let reflection = builder.reflection();
+    assert_eq!(reflection.uniforms.len(), 1);
+    assert_eq!(reflection.uniforms[0].location, 1);
+    assert_eq!(reflection.uniforms[0].offset, 0);
+
+    let module = builder.build().unwrap();
+    assert_eq!(validate(&module), Vec::<ValidationError>::new());
+
+    let offsets: Vec<_> = {
+        module
+            .annotations
+            .iter()
+            .filter(|inst| {
+                inst.class.opcode == Op::MemberDecorate
+                    && inst.operands[2] == Operand::Decoration(Decoration::Offset)
+            })
+            .map(|inst| (inst.operands[1].clone(), inst.operands[3].clone()))
+            .collect()
+    };
+    assert_eq!(
+        offsets,
+        vec![(Operand::LiteralInt32(0), Operand::LiteralInt32(0))]
+    );
+
+    // The access chain reads the member at index 0
+    let index_id = {
+        module
+            .functions
+            .iter()
+            .flat_map(|function| &function.basic_blocks)
+            .flat_map(|block| &block.instructions)
+            .find(|inst| inst.class.opcode == Op::AccessChain)
+            .map(|inst| inst.operands[1].clone())
+            .unwrap()
+    };
+    let index = {
+        module
+            .types_global_values
+            .iter()
+            .find(|inst| Operand::IdRef(inst.result_id.unwrap()) == index_id)
+            .unwrap()
+    };
+    assert_eq!(index.class.opcode, Op::Constant);
+    assert_eq!(index.operands, vec![Operand::LiteralInt32(0)]);
+}
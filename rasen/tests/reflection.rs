@@ -0,0 +1,140 @@
+extern crate rasen;
+
+use rasen::prelude::*;
+
+include!("../../tests/graph.rs");
+
+fn reflect(graph: &Graph, settings: Settings) -> Reflection {
+    ModuleBuilder::from_graph(graph, settings)
+        .unwrap()
+        .reflection()
+}
+
+#[test]
+fn test_reflect_vert() {
+    let reflection = reflect(
+        &build_basic_vert(),
+        Settings {
+            mod_type: ShaderType::Vertex,
+            uniforms_name: Some(String::from("Uniforms")),
+            ..Settings::default()
+        },
+    );
+
+    assert_eq!(reflection.entry_points.len(), 1);
+    let entry = &reflection.entry_points[0];
+    assert_eq!(entry.name, "main");
+    assert_eq!(entry.stage, ShaderType::Vertex);
+
+    let mut inputs: Vec<_> = {
+        entry
+            .inputs
+            .iter()
+            .map(|input| (input.location, input.type_name, input.name.clone()))
+            .collect()
+    };
+    inputs.sort_by_key(|&(location, _, _)| location);
+
+    assert_eq!(
+        inputs,
+        vec![
+            (Some(0), TypeName::VEC3, Some(String::from("a_pos"))),
+            (Some(1), TypeName::VEC3, Some(String::from("a_normal"))),
+            (Some(2), TypeName::VEC2, Some(String::from("a_uv"))),
+        ]
+    );
+
+    assert_eq!(entry.outputs.len(), 3);
+    assert!(entry.outputs.contains(&InterfaceVariable {
+        location: None,
+        built_in: Some(BuiltIn::Position),
+        type_name: TypeName::VEC4,
+        array_len: None,
+        name: None,
+    }));
+
+    // The members of the uniform block are laid out in location order
+    assert_eq!(reflection.uniforms_name, Some(String::from("Uniforms")));
+    let uniforms: Vec<_> = {
+        reflection
+            .uniforms
+            .iter()
+            .map(|uniform| (uniform.location, uniform.offset))
+            .collect()
+    };
+    assert_eq!(uniforms, vec![(0, 0), (1, 64), (2, 128)]);
+    assert!(reflection.samplers.is_empty());
+}
+
+#[test]
+fn test_reflect_frag() {
+    let (_, reflection) =
+        build_program_with_reflection(&build_basic_frag(), Settings::default()).unwrap();
+
+    assert!(reflection.uniforms.is_empty());
+    assert_eq!(reflection.uniforms_name, None);
+    assert_eq!(
+        reflection.samplers,
+        vec![SamplerVariable {
+            location: 0,
+            type_name: TypeName::SAMPLER2D,
+            name: Some(String::from("u_material")),
+        }]
+    );
+
+    let entry = &reflection.entry_points[0];
+    assert_eq!(entry.inputs.len(), 2);
+    assert_eq!(entry.outputs.len(), 1);
+}
+
+#[test]
+fn test_reflect_std140_offsets() {
+    let mut graph = Graph::default();
+
+    let scale = graph.add_node(Node::Uniform(0, TypeName::FLOAT, VariableName::None));
+    let offset = graph.add_node(Node::Uniform(1, TypeName::VEC3, VariableName::None));
+    let bias = graph.add_node(Node::Uniform(2, TypeName::FLOAT, VariableName::None));
+    let uv = graph.add_node(Node::Uniform(3, TypeName::VEC2, VariableName::None));
+
+    let multiply = graph.add_node(Node::Multiply);
+    let add = graph.add_node(Node::Add);
+    let o_pos = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let o_uv = graph.add_node(Node::Output(
+        1,
+        TypeName::VEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let o_bias = graph.add_node(Node::Output(
+        2,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(offset, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, add, 0);
+    graph.add_edge(offset, add, 1);
+    graph.add_edge(add, o_pos, 0);
+    graph.add_edge(uv, o_uv, 0);
+    graph.add_edge(bias, o_bias, 0);
+
+    let reflection = reflect(&graph, Settings::from(ShaderType::Vertex));
+
+    // The vec3 is aligned to 16 bytes, and the following float packed after it
+    let offsets: Vec<_> = {
+        reflection
+            .uniforms
+            .iter()
+            .map(|uniform| uniform.offset)
+            .collect()
+    };
+    assert_eq!(offsets, vec![0, 16, 28, 32]);
+    assert_eq!(reflection.push_constants, None);
+}
@@ -0,0 +1,116 @@
+extern crate rasen;
+
+use std::convert::TryFrom;
+
+use rasen::prelude::*;
+
+fn requirements(graph: &Graph, settings: Settings) -> Requirements {
+    ModuleBuilder::try_from((graph, settings))
+        .unwrap()
+        .requirements()
+}
+
+#[test]
+fn test_requirements_basic() {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(input, output, 0);
+
+    let res = requirements(&graph, Settings::from(ShaderType::Vertex));
+    assert_eq!(res.capabilities(), vec![Capability::Shader]);
+    assert!(res.extensions().is_empty());
+
+    // Geometry implicitly declares Shader
+    let res = requirements(&graph, Settings::from(ShaderType::Geometry));
+    assert_eq!(res.capabilities(), vec![Capability::Geometry]);
+}
+
+#[test]
+fn test_requirements_sorted() {
+    let mut graph = Graph::default();
+
+    let value = graph.add_node(Node::Input(
+        0,
+        TypeName::HVEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let count = graph.add_node(Node::Input(
+        1,
+        TypeName::UINT64,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let o_value = graph.add_node(Node::Output(
+        0,
+        TypeName::HVEC2,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let o_count = graph.add_node(Node::Output(
+        1,
+        TypeName::UINT64,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(value, o_value, 0);
+    graph.add_edge(count, o_count, 0);
+
+    let res = requirements(&graph, Settings::from(ShaderType::Vertex));
+    assert_eq!(
+        res.capabilities(),
+        vec![
+            Capability::Shader,
+            Capability::Float16,
+            Capability::Int64,
+            Capability::StorageInputOutput16,
+        ]
+    );
+    assert_eq!(res.extensions(), vec!["SPV_KHR_16bit_storage"]);
+}
+
+#[test]
+fn test_requirements_sampler() {
+    let mut graph = Graph::default();
+
+    let sampler = graph.add_node(Node::Uniform(0, TypeName::SAMPLER1D, VariableName::None));
+    let coords = graph.add_node(Node::Input(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let sample = graph.add_node(Node::Sample);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(sampler, sample, 0);
+    graph.add_edge(coords, sample, 1);
+    graph.add_edge(sample, output, 0);
+
+    let res = requirements(&graph, Settings::default());
+    assert_eq!(
+        res.capabilities(),
+        vec![Capability::Shader, Capability::Sampled1D]
+    );
+
+    let assembly = build_program_assembly(&graph, Settings::default()).unwrap();
+    assert!(assembly.contains("OpCapability Sampled1D"));
+}
@@ -0,0 +1,129 @@
+extern crate rasen;
+
+use rasen::{
+    passes::{simplify, Rewrite, RewriteTable},
+    prelude::*,
+};
+
+/// Build a graph passing the result of a binary node to an output
+fn build_binary(node: Node, value: TypedValue) -> (Graph, [NodeIndex; 4]) {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let constant = graph.add_node(Node::Constant(value));
+    let binary = graph.add_node(node);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(input, binary, 0);
+    graph.add_edge(constant, binary, 1);
+    graph.add_edge(binary, output, 0);
+
+    (graph, [input, constant, binary, output])
+}
+
+fn output_source(graph: &Graph, output: NodeIndex) -> NodeIndex {
+    graph.inputs_of(output)[0].from
+}
+
+#[test]
+fn test_multiply_by_one() {
+    let (mut graph, [input, _, multiply, output]) =
+        build_binary(Node::Multiply, TypedValue::Float(1.0));
+
+    let count = simplify(&mut graph, &RewriteTable::new(), &Settings::default()).unwrap();
+    assert_eq!(count, 1);
+    assert!(!graph.contains_node(multiply));
+    assert_eq!(output_source(&graph, output), input);
+
+    assert!(build_program(&graph, ShaderType::Fragment).is_ok());
+}
+
+#[test]
+fn test_add_zero() {
+    let (mut graph, [input, _, add, output]) = build_binary(Node::Add, TypedValue::Float(0.0));
+
+    // -0.0 + 0.0 is 0.0, so the addition is only removed with fast math
+    let count = simplify(&mut graph, &RewriteTable::new(), &Settings::default()).unwrap();
+    assert_eq!(count, 0);
+    assert_eq!(output_source(&graph, output), add);
+
+    let settings = Settings {
+        fast_math: true,
+        ..Settings::default()
+    };
+
+    let count = simplify(&mut graph, &RewriteTable::new(), &settings).unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(output_source(&graph, output), input);
+}
+
+#[test]
+fn test_pow() {
+    let (mut graph, [input, _, pow, output]) = build_binary(Node::Pow, TypedValue::Float(2.0));
+
+    let count = simplify(&mut graph, &RewriteTable::new(), &Settings::default()).unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(output_source(&graph, output), pow);
+    match graph[pow] {
+        Node::Multiply => {}
+        ref node => panic!("unexpected node {:?}", node),
+    }
+
+    let args: Vec<_> = graph
+        .inputs_of(pow)
+        .into_iter()
+        .map(|edge| edge.from)
+        .collect();
+    assert_eq!(args, vec![input, input]);
+
+    assert!(build_program(&graph, ShaderType::Fragment).is_ok());
+}
+
+#[test]
+fn test_custom_rule() {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let inner = graph.add_node(Node::Abs);
+    let outer = graph.add_node(Node::Abs);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(input, inner, 0);
+    graph.add_edge(inner, outer, 0);
+    graph.add_edge(outer, output, 0);
+
+    // abs(abs(x)) is abs(x)
+    let mut table = RewriteTable::empty();
+    table.add_rule(|graph, index, _| {
+        let arg = graph.inputs_of(index).first()?.from;
+        match (&graph[index], &graph[arg]) {
+            (&Node::Abs, &Node::Abs) => Some(Rewrite::Forward(arg)),
+            _ => None,
+        }
+    });
+
+    let count = simplify(&mut graph, &table, &Settings::default()).unwrap();
+    assert_eq!(count, 1);
+    assert!(!graph.contains_node(outer));
+    assert_eq!(output_source(&graph, output), inner);
+}
@@ -0,0 +1,193 @@
+extern crate rasen;
+
+mod common;
+
+use common::has_line;
+use rasen::{
+    errors::Error,
+    module::{FunctionRef, GraphRef},
+    prelude::*,
+};
+
+fn basic_graph() -> Graph {
+    let mut graph = Graph::default();
+
+    let value = graph.add_node(Node::Constant(TypedValue::Vec4(1.0, 0.0, 0.0, 1.0)));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::Named(String::from("o_color")),
+        Attributes::default(),
+    ));
+    graph.add_edge(value, output, 0);
+
+    graph
+}
+
+#[test]
+fn test_entry_name() {
+    let assembly = build_program_assembly(
+        &basic_graph(),
+        Settings {
+            entry_name: String::from("fs_material"),
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert!(assembly.contains("OpEntryPoint Fragment %4 \"fs_material\""));
+    assert!(!assembly.contains("\"main\""));
+}
+
+#[test]
+fn test_source() {
+    let assembly = build_program_assembly(
+        &basic_graph(),
+        Settings {
+            source: Some(Source {
+                language: SourceLanguage::GLSL,
+                version: 450,
+                file: Some(String::from("materials/red.json")),
+            }),
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert!(has_line(&assembly, "OpString \"materials/red.json\""));
+    assert!(assembly.contains("OpSource GLSL 450 %"));
+
+    // The source comes before the names of the variables
+    let source = assembly.find("OpSource").unwrap();
+    let name = assembly.find("OpName").unwrap();
+    assert!(source < name);
+}
+
+#[test]
+fn test_module_processed() {
+    let settings = Settings {
+        processes: vec![String::from("rasen-plugin"), String::from("opt -O")],
+        ..Settings::default()
+    };
+
+    let err = build_program_assembly(&basic_graph(), settings.clone()).unwrap_err();
+    match err {
+        Error::UnsupportedVersion(_, Version::V1_1, Version::V1_0) => {}
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+
+    let assembly = build_program_assembly(
+        &basic_graph(),
+        Settings {
+            version: Version::V1_1,
+            target_env: TargetEnv::Vulkan1_1,
+            ..settings
+        },
+    )
+    .unwrap();
+
+    assert!(has_line(&assembly, "OpModuleProcessed \"rasen-plugin\""));
+    assert!(has_line(&assembly, "OpModuleProcessed \"opt -O\""));
+
+    // Processes come after the names of the variables
+    let process = assembly.find("OpModuleProcessed").unwrap();
+    let name = assembly.rfind("OpName").unwrap();
+    assert!(name < process);
+}
+
+/// Build a module forwarding an input through a function normalizing it
+fn function_module() -> (Module, FunctionRef) {
+    let mut module = Module::default();
+
+    let func = module.add_function();
+    {
+        let graph = &mut module[func];
+        let input = graph.add_node(Node::Parameter(0, TypeName::VEC4));
+        let normalize = graph.add_node(Node::Normalize);
+        let output = graph.add_node(Node::Return);
+
+        graph.add_edge(input, normalize, 0);
+        graph.add_edge(normalize, output, 0);
+    }
+    {
+        let graph = &mut module.main;
+        let input = graph.add_node(Node::Input(
+            0,
+            TypeName::VEC4,
+            VariableName::Named(String::from("v_color")),
+            Attributes::default(),
+        ));
+        let call = graph.add_node(Node::Call(func));
+        let output = graph.add_node(Node::Output(
+            0,
+            TypeName::VEC4,
+            VariableName::Named(String::from("o_color")),
+            Attributes::default(),
+        ));
+
+        graph.add_edge(input, call, 0);
+        graph.add_edge(call, output, 0);
+    }
+
+    (module, func)
+}
+
+#[test]
+fn test_debug_info() {
+    let (module, _) = function_module();
+
+    let assembly = build_program_assembly(&module, Settings::default()).unwrap();
+    assert!(!assembly.contains("OpLine"));
+    assert!(!assembly.contains("OpString"));
+
+    let assembly = build_program_assembly(
+        &module,
+        Settings {
+            debug_info: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    // Each graph is declared as a virtual file
+    assert!(has_line(&assembly, "OpString \"function:0\""));
+    assert!(has_line(&assembly, "OpString \"entry:main\""));
+
+    // Every node emitting instructions is mapped to a line
+    assert_eq!(assembly.lines().filter(|l| l.contains("OpLine")).count(), 6);
+    assert!(assembly.contains("\"Normalize_1\""));
+    assert!(assembly.contains("\"Call_1\""));
+    assert!(assembly.contains("\"Input_0\""));
+    assert!(!assembly.contains("\"Output_2\""));
+}
+
+#[test]
+fn test_source_map() {
+    let (module, func) = function_module();
+
+    let builder = ModuleBuilder::from_module(&module, Settings::default()).unwrap();
+    let (_, source_map) = builder.build_with_source_map().unwrap();
+
+    // Parameter and Normalize in the function, Input and Call in the main graph
+    assert_eq!(source_map.len(), 4);
+
+    let normalize = SourceLocation {
+        graph: GraphRef::Function(func),
+        node: NodeIndex::new(1),
+    };
+    let id = source_map.find(normalize).unwrap();
+    assert_eq!(source_map.get(id), Some(normalize));
+
+    let call = SourceLocation {
+        graph: GraphRef::Main,
+        node: NodeIndex::new(1),
+    };
+    assert!(source_map.find(call).is_some());
+
+    // Outputs don't create a new result
+    let output = SourceLocation {
+        graph: GraphRef::Main,
+        node: NodeIndex::new(2),
+    };
+    assert!(source_map.find(output).is_none());
+}
@@ -0,0 +1,238 @@
+extern crate rasen;
+
+mod common;
+
+use common::{count_lines, has_line};
+use rasen::prelude::*;
+
+fn build_control() -> Graph {
+    let mut graph = Graph::default();
+
+    let invocation = graph.add_node(Node::Input(
+        0,
+        TypeName::INT,
+        VariableName::BuiltIn(BuiltIn::InvocationId),
+        Attributes::default(),
+    ));
+    let pos = graph.add_node(Node::ArrayedInput(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::Position),
+        Attributes::default(),
+    ));
+    let outer = graph.add_node(Node::Constant(TypedValue::Vec4(4.0, 4.0, 4.0, 4.0)));
+    let inner = graph.add_node(Node::Constant(TypedValue::Vec2(2.0, 2.0)));
+    let weight = graph.add_node(Node::Constant(TypedValue::Float(0.5)));
+
+    let o_pos = graph.add_node(Node::ArrayedOutput(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::Position),
+        Attributes::default(),
+    ));
+    let o_outer = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::TessLevelOuter),
+        Attributes::default(),
+    ));
+    let o_inner = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC2,
+        VariableName::BuiltIn(BuiltIn::TessLevelInner),
+        Attributes::default(),
+    ));
+    let o_weight = graph.add_node(Node::Output(
+        1,
+        TypeName::FLOAT,
+        VariableName::Named(String::from("p_weight")),
+        Attributes {
+            patch: true,
+            ..Attributes::default()
+        },
+    ));
+
+    let barrier = graph.add_node(Node::Barrier);
+
+    graph.add_edge(invocation, pos, 0);
+    graph.add_edge(pos, o_pos, 0);
+    graph.add_edge(outer, o_outer, 0);
+    graph.add_edge(inner, o_inner, 0);
+    graph.add_edge(weight, o_weight, 0);
+
+    graph.add_edge(o_pos, barrier, 0);
+    graph.add_edge(o_outer, barrier, 1);
+    graph.add_edge(o_inner, barrier, 2);
+    graph.add_edge(o_weight, barrier, 3);
+
+    graph
+}
+
+fn build_evaluation() -> Graph {
+    let mut graph = Graph::default();
+
+    let coord = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC3,
+        VariableName::BuiltIn(BuiltIn::TessCoord),
+        Attributes::default(),
+    ));
+    let outer = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::TessLevelOuter),
+        Attributes::default(),
+    ));
+    let weight = graph.add_node(Node::Input(
+        1,
+        TypeName::FLOAT,
+        VariableName::Named(String::from("p_weight")),
+        Attributes {
+            patch: true,
+            ..Attributes::default()
+        },
+    ));
+    let index = graph.add_node(Node::Constant(TypedValue::Int(0)));
+    let pos = graph.add_node(Node::ArrayedInput(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::Position),
+        Attributes::default(),
+    ));
+
+    let x = graph.add_node(Node::Extract(0));
+    let multiply = graph.add_node(Node::Multiply);
+    let scale = graph.add_node(Node::Multiply);
+
+    let o_pos = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::Position),
+        Attributes::default(),
+    ));
+    let o_level = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::Named(String::from("f_level")),
+        Attributes::default(),
+    ));
+
+    graph.add_edge(index, pos, 0);
+    graph.add_edge(coord, x, 0);
+    graph.add_edge(pos, multiply, 0);
+    graph.add_edge(x, multiply, 1);
+    graph.add_edge(multiply, scale, 0);
+    graph.add_edge(weight, scale, 1);
+    graph.add_edge(scale, o_pos, 0);
+    graph.add_edge(outer, o_level, 0);
+
+    graph
+}
+
+#[test]
+fn test_build_control() {
+    let assembly =
+        build_program_assembly(&build_control(), ShaderType::TessellationControl).unwrap();
+
+    assert!(has_line(&assembly, "OpCapability Tessellation"));
+    assert!(has_line(&assembly, "OpExecutionMode %4 OutputVertices 3"));
+
+    // The invocation index is shared by the explicit input and the arrayed output
+    assert_eq!(count_lines(&assembly, "BuiltIn InvocationId"), 1);
+    assert!(has_line(&assembly, "BuiltIn TessLevelOuter"));
+    assert!(has_line(&assembly, "BuiltIn TessLevelInner"));
+    assert!(has_line(&assembly, "Patch"));
+    assert!(has_line(&assembly, "Location 1"));
+    assert!(assembly.contains("OpTypeArray"));
+    assert!(assembly.contains("OpControlBarrier"));
+}
+
+#[test]
+fn test_build_evaluation() {
+    let assembly = build_program_assembly(
+        &build_evaluation(),
+        Settings {
+            mod_type: ShaderType::TessellationEvaluation,
+            tessellation: TessellationSettings {
+                patch_vertices: 4,
+                primitive: TessellationPrimitive::Quads,
+                spacing: Spacing::FractionalOdd,
+                winding: Winding::Clockwise,
+                point_mode: true,
+                ..TessellationSettings::default()
+            },
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    assert!(has_line(&assembly, "OpExecutionMode %4 Quads"));
+    assert!(has_line(
+        &assembly,
+        "OpExecutionMode %4 SpacingFractionalOdd"
+    ));
+    assert!(has_line(&assembly, "OpExecutionMode %4 VertexOrderCw"));
+    assert!(has_line(&assembly, "OpExecutionMode %4 PointMode"));
+    assert!(!has_line(&assembly, "OutputVertices 3"));
+
+    assert!(has_line(&assembly, "BuiltIn TessCoord"));
+    assert!(has_line(&assembly, "BuiltIn TessLevelOuter"));
+    assert!(has_line(&assembly, "Patch"));
+    assert!(assembly.contains("OpCompositeConstruct"));
+}
+
+#[test]
+fn test_per_vertex_not_arrayed() {
+    let mut graph = Graph::default();
+
+    let pos = graph.add_node(Node::Constant(TypedValue::Vec4(0.0, 0.0, 0.0, 1.0)));
+    let o_pos = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::BuiltIn(BuiltIn::Position),
+        Attributes::default(),
+    ));
+    graph.add_edge(pos, o_pos, 0);
+
+    assert!(build_program_assembly(&graph, ShaderType::TessellationControl).is_err());
+    assert!(build_program_assembly(&graph, ShaderType::TessellationEvaluation).is_ok());
+}
+
+#[test]
+fn test_invalid_stage() {
+    // Barriers and arrayed outputs are only available in control shaders
+    assert!(build_program_assembly(&build_control(), ShaderType::TessellationEvaluation).is_err());
+    assert!(build_program_assembly(&build_evaluation(), ShaderType::TessellationControl).is_err());
+
+    let mut graph = Graph::default();
+    let value = graph.add_node(Node::Constant(TypedValue::Float(1.0)));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::FLOAT,
+        VariableName::None,
+        Attributes {
+            patch: true,
+            ..Attributes::default()
+        },
+    ));
+    graph.add_edge(value, output, 0);
+
+    assert!(build_program_assembly(&graph, ShaderType::Vertex).is_err());
+}
+
+#[test]
+fn test_invalid_settings() {
+    let res = build_program_assembly(
+        &build_control(),
+        Settings {
+            mod_type: ShaderType::TessellationControl,
+            tessellation: TessellationSettings {
+                output_vertices: 0,
+                ..TessellationSettings::default()
+            },
+            ..Settings::default()
+        },
+    );
+
+    assert!(res.is_err());
+}
@@ -0,0 +1,202 @@
+extern crate rasen;
+extern crate rspirv;
+extern crate spirv_headers;
+
+use rasen::{
+    prelude::*,
+    validate::{validate, ValidationError},
+};
+use rspirv::mr::{Module as SpirvModule, Operand};
+use spirv_headers::{Decoration, Op};
+
+include!("../../tests/graph.rs");
+
+fn build(graph: &Graph, mod_type: ShaderType) -> SpirvModule {
+    ModuleBuilder::from_graph(graph, Settings::from(mod_type))
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_valid_modules() {
+    assert_eq!(
+        validate(&build(&build_basic_vert(), ShaderType::Vertex)),
+        Vec::<ValidationError>::new()
+    );
+    assert_eq!(
+        validate(&build(&build_basic_frag(), ShaderType::Fragment)),
+        Vec::<ValidationError>::new()
+    );
+}
+
+#[test]
+fn test_invalid_header() {
+    let mut module = build(&build_basic_frag(), ShaderType::Fragment);
+    module.header.as_mut().unwrap().version = 0x0001_0a00;
+
+    assert_eq!(
+        validate(&module),
+        vec![ValidationError::InvalidVersion(0x0001_0a00)]
+    );
+}
+
+#[test]
+fn test_use_before_definition() {
+    let mut module = build(&build_basic_frag(), ShaderType::Fragment);
+    module.types_global_values.reverse();
+
+    let errors = validate(&module);
+    assert!(!errors.is_empty());
+    assert!(errors.iter().all(|err| match *err {
+        ValidationError::UseBeforeDefinition(..) => true,
+        _ => false,
+    }));
+}
+
+#[test]
+fn test_unterminated_block() {
+    let mut module = build(&build_basic_frag(), ShaderType::Fragment);
+
+    let label = {
+        let block = &mut module.functions[0].basic_blocks[0];
+        let ret = block.instructions.pop().unwrap();
+        assert_eq!(ret.class.opcode, Op::Return);
+        block.label.as_ref().unwrap().result_id.unwrap()
+    };
+
+    assert_eq!(
+        validate(&module),
+        vec![ValidationError::UnterminatedBlock(label)]
+    );
+}
+
+#[test]
+fn test_block_layout() {
+    let mut module = build(&build_basic_vert(), ShaderType::Vertex);
+    module.annotations.retain(|inst| {
+        inst.class.opcode != Op::MemberDecorate
+            || inst.operands.get(1) != Some(&Operand::LiteralInt32(1))
+    });
+
+    let block = module
+        .annotations
+        .iter()
+        .find(|inst| inst.operands.get(1) == Some(&Operand::Decoration(Decoration::Block)))
+        .and_then(|inst| match inst.operands[0] {
+            Operand::IdRef(id) => Some(id),
+            _ => None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        validate(&module),
+        vec![
+            ValidationError::MissingMatrixLayout(block, 1),
+            ValidationError::MissingOffset(block, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_interface() {
+    let mut module = build(&build_basic_frag(), ShaderType::Fragment);
+
+    let entry = &mut module.entry_points[0];
+    let removed = match entry.operands.pop() {
+        Some(Operand::IdRef(id)) => id,
+        _ => panic!("empty interface"),
+    };
+
+    let entry_id = match entry.operands[1] {
+        Operand::IdRef(id) => id,
+        _ => panic!("missing entry point ID"),
+    };
+
+    assert_eq!(
+        validate(&module),
+        vec![ValidationError::MissingInterface(entry_id, removed)]
+    );
+}
+
+#[test]
+fn test_duplicate_decoration() {
+    let mut module = build(&build_basic_frag(), ShaderType::Fragment);
+
+    let inst = module.annotations[0].clone();
+    let target = match inst.operands[0] {
+        Operand::IdRef(id) => id,
+        _ => panic!("missing decoration target"),
+    };
+
+    module.annotations.push(inst);
+
+    assert_eq!(
+        validate(&module),
+        vec![ValidationError::DuplicateDecoration(
+            target,
+            Decoration::Location
+        )]
+    );
+}
+
+#[test]
+fn test_std140_layout() {
+    let mut graph = Graph::default();
+
+    let scale = graph.add_node(Node::Uniform(0, TypeName::FLOAT, VariableName::None));
+    let offset = graph.add_node(Node::Uniform(1, TypeName::VEC3, VariableName::None));
+    let transform = graph.add_node(Node::Uniform(2, TypeName::MAT3, VariableName::None));
+
+    let transformed = graph.add_node(Node::Multiply);
+    let scaled = graph.add_node(Node::Multiply);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC3,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(transform, transformed, 0);
+    graph.add_edge(offset, transformed, 1);
+    graph.add_edge(transformed, scaled, 0);
+    graph.add_edge(scale, scaled, 1);
+    graph.add_edge(scaled, output, 0);
+
+    // The builder runs the validator on the module when it's enabled in the settings
+    let settings = Settings {
+        mod_type: ShaderType::Vertex,
+        validate: true,
+        ..Settings::default()
+    };
+    let module = ModuleBuilder::from_graph(&graph, settings)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(validate(&module), Vec::<ValidationError>::new());
+
+    let member_decoration = |decoration| -> Vec<_> {
+        module
+            .annotations
+            .iter()
+            .filter(|inst| {
+                inst.class.opcode == Op::MemberDecorate
+                    && inst.operands.get(2) == Some(&Operand::Decoration(decoration))
+            })
+            .map(|inst| (inst.operands[1].clone(), inst.operands[3].clone()))
+            .collect()
+    };
+
+    assert_eq!(
+        member_decoration(Decoration::Offset),
+        vec![
+            (Operand::LiteralInt32(0), Operand::LiteralInt32(0)),
+            (Operand::LiteralInt32(1), Operand::LiteralInt32(16)),
+            (Operand::LiteralInt32(2), Operand::LiteralInt32(32)),
+        ]
+    );
+    assert_eq!(
+        member_decoration(Decoration::MatrixStride),
+        vec![(Operand::LiteralInt32(2), Operand::LiteralInt32(16))]
+    );
+}
@@ -0,0 +1,159 @@
+extern crate rasen;
+
+use rasen::{errors::Error, prelude::*};
+
+fn basic_graph() -> Graph {
+    let mut graph = Graph::default();
+
+    let scale = graph.add_node(Node::Uniform(0, TypeName::FLOAT, VariableName::None));
+    let color = graph.add_node(Node::Input(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let multiply = graph.add_node(Node::Multiply);
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::VEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+
+    graph.add_edge(color, multiply, 0);
+    graph.add_edge(scale, multiply, 1);
+    graph.add_edge(multiply, output, 0);
+
+    graph
+}
+
+fn entry_point(assembly: &str) -> Vec<&str> {
+    let line = assembly
+        .lines()
+        .find(|line| line.trim().starts_with("OpEntryPoint"))
+        .unwrap();
+
+    line.split_whitespace().skip(4).collect()
+}
+
+#[test]
+fn test_header_version() {
+    let graph = basic_graph();
+
+    let bytecode = build_program(&graph, Settings::default()).unwrap();
+    assert_eq!(&bytecode[4..8], &[0x00, 0x00, 0x01, 0x00]);
+
+    let bytecode = build_program(
+        &graph,
+        Settings {
+            version: Version::V1_3,
+            target_env: TargetEnv::Vulkan1_1,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(&bytecode[4..8], &[0x00, 0x03, 0x01, 0x00]);
+
+    let bytecode = build_program(
+        &graph,
+        Settings {
+            version: Version::V1_6,
+            target_env: TargetEnv::Vulkan1_3,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(&bytecode[4..8], &[0x00, 0x06, 0x01, 0x00]);
+}
+
+#[test]
+fn test_unsupported_target() {
+    let err = build_program(
+        &basic_graph(),
+        Settings {
+            version: Version::V1_4,
+            target_env: TargetEnv::Vulkan1_1,
+            ..Settings::default()
+        },
+    )
+    .unwrap_err();
+
+    match err {
+        Error::UnsupportedTarget(Version::V1_4, TargetEnv::Vulkan1_1) => {}
+        ref kind => panic!("unexpected error {:?}", kind),
+    }
+}
+
+#[test]
+fn test_entry_point_interface() {
+    let graph = basic_graph();
+
+    let assembly = build_program_assembly(&graph, Settings::default()).unwrap();
+    assert_eq!(entry_point(&assembly).len(), 2);
+
+    // The uniform block gets listed in the interface from 1.4 onward
+    let assembly = build_program_assembly(
+        &graph,
+        Settings {
+            version: Version::V1_4,
+            target_env: TargetEnv::Vulkan1_2,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(entry_point(&assembly).len(), 3);
+}
+
+#[test]
+fn test_target_env() {
+    let graph = basic_graph();
+
+    let assembly = build_program_assembly(&graph, Settings::default()).unwrap();
+    assert!(assembly.contains("OriginUpperLeft"));
+
+    let assembly = build_program_assembly(
+        &graph,
+        Settings {
+            target_env: TargetEnv::OpenGL4_5,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+    assert!(assembly.contains("OriginLowerLeft"));
+}
+
+#[test]
+fn test_core_extensions() {
+    let mut graph = Graph::default();
+
+    let input = graph.add_node(Node::Input(
+        0,
+        TypeName::HVEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    let output = graph.add_node(Node::Output(
+        0,
+        TypeName::HVEC4,
+        VariableName::None,
+        Attributes::default(),
+    ));
+    graph.add_edge(input, output, 0);
+
+    let assembly = build_program_assembly(&graph, ShaderType::Vertex).unwrap();
+    assert!(assembly.contains("OpExtension \"SPV_KHR_16bit_storage\""));
+
+    // 16-bit storage is part of the core specification since 1.3
+    let assembly = build_program_assembly(
+        &graph,
+        Settings {
+            mod_type: ShaderType::Vertex,
+            version: Version::V1_3,
+            target_env: TargetEnv::Vulkan1_1,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+    assert!(!assembly.contains("OpExtension"));
+    assert!(assembly.contains("OpCapability StorageInputOutput16"));
+}
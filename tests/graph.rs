@@ -1,9 +1,9 @@
 fn build_basic_vert() -> Graph {
     let mut graph = Graph::default();
 
-    let pos = graph.add_node(Node::Input(0, TypeName::VEC3, VariableName::Named(String::from("a_pos"))));
-    let normal = graph.add_node(Node::Input(1, TypeName::VEC3, VariableName::Named(String::from("a_normal"))));
-    let uv = graph.add_node(Node::Input(2, TypeName::VEC2, VariableName::Named(String::from("a_uv"))));
+    let pos = graph.add_node(Node::Input(0, TypeName::VEC3, VariableName::Named(String::from("a_pos")), Attributes::default()));
+    let normal = graph.add_node(Node::Input(1, TypeName::VEC3, VariableName::Named(String::from("a_normal")), Attributes::default()));
+    let uv = graph.add_node(Node::Input(2, TypeName::VEC2, VariableName::Named(String::from("a_uv")), Attributes::default()));
 
     let projection = graph.add_node(Node::Uniform(0, TypeName::MAT4, VariableName::Named(String::from("u_projection"))));
     let view = graph.add_node(Node::Uniform(1, TypeName::MAT4, VariableName::Named(String::from("u_view"))));
@@ -28,9 +28,9 @@ fn build_basic_vert() -> Graph {
     let norm_4 = graph.add_node(Node::Construct(TypeName::VEC4));
     let norm_3 = graph.add_node(Node::Construct(TypeName::VEC3));
 
-    let o_pos = graph.add_node(Node::Output(0, TypeName::VEC4, VariableName::BuiltIn(BuiltIn::Position)));
-    let o_norm = graph.add_node(Node::Output(1, TypeName::VEC3, VariableName::Named(String::from("f_norm"))));
-    let o_uv = graph.add_node(Node::Output(2, TypeName::VEC2, VariableName::Named(String::from("f_uv"))));
+    let o_pos = graph.add_node(Node::Output(0, TypeName::VEC4, VariableName::BuiltIn(BuiltIn::Position), Attributes::default()));
+    let o_norm = graph.add_node(Node::Output(1, TypeName::VEC3, VariableName::Named(String::from("f_norm")), Attributes::default()));
+    let o_uv = graph.add_node(Node::Output(2, TypeName::VEC2, VariableName::Named(String::from("f_uv")), Attributes::default()));
 
     graph.add_edge(projection, vp, 0);
     graph.add_edge(view, vp, 1);
@@ -78,8 +78,8 @@ fn build_basic_vert() -> Graph {
 fn build_basic_frag() -> Graph {
     let mut graph = Graph::default();
 
-    let normal = graph.add_node(Node::Input(0, TypeName::VEC3, VariableName::Named(String::from("f_normal"))));
-    let uv = graph.add_node(Node::Input(1, TypeName::VEC2, VariableName::Named(String::from("f_uv"))));
+    let normal = graph.add_node(Node::Input(0, TypeName::VEC3, VariableName::Named(String::from("f_normal")), Attributes::default()));
+    let uv = graph.add_node(Node::Input(1, TypeName::VEC2, VariableName::Named(String::from("f_uv")), Attributes::default()));
 
     let material = graph.add_node(Node::Uniform(0, TypeName::SAMPLER2D, VariableName::Named(String::from("u_material"))));
 
@@ -93,7 +93,7 @@ fn build_basic_frag() -> Graph {
     let sample = graph.add_node(Node::Sample);
     let multiply = graph.add_node(Node::Multiply);
 
-    let color = graph.add_node(Node::Output(0, TypeName::VEC4, VariableName::Named(String::from("o_col"))));
+    let color = graph.add_node(Node::Output(0, TypeName::VEC4, VariableName::Named(String::from("o_col")), Attributes::default()));
 
     graph.add_edge(normal, normalize, 0);
 